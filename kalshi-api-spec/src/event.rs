@@ -2,14 +2,23 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    market::Market,
+    market::{Market, StatusFilter},
+    pagination::{CursorParams, Paginated, impl_cursor_params, impl_paginated},
     ticker::{EventTicker, SeriesTicker},
 };
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+/// How collateral is returned across the markets of a mutually exclusive
+/// event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CollateralReturnType {
+    #[serde(rename = "binary")]
     Binary,
+    #[serde(rename = "MECNET")]
+    MutuallyExclusiveNet,
+    #[serde(rename = "DIRECNET")]
+    DirectionalNet,
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,8 +27,54 @@ pub struct Event {
     series_ticker: SeriesTicker,
     title: String,
     sub_title: String,
+    #[serde(default)]
+    category: String,
     mutually_exclusive: bool,
-    strike_date: DateTime<Utc>,
+    #[serde(default)]
+    collateral_return_type: Option<CollateralReturnType>,
+    #[serde(default)]
+    strike_date: Option<DateTime<Utc>>,
+    /// Set instead of `strike_date` by events spanning a period, e.g. `week`
+    #[serde(default)]
+    strike_period: Option<String>,
+}
+
+impl Event {
+    pub fn event_ticker(&self) -> &EventTicker {
+        &self.event_ticker
+    }
+
+    pub fn series_ticker(&self) -> &SeriesTicker {
+        &self.series_ticker
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn sub_title(&self) -> &str {
+        &self.sub_title
+    }
+
+    pub fn category(&self) -> &str {
+        &self.category
+    }
+
+    pub fn collateral_return_type(&self) -> Option<CollateralReturnType> {
+        self.collateral_return_type
+    }
+
+    pub fn strike_period(&self) -> Option<&str> {
+        self.strike_period.as_deref()
+    }
+
+    pub fn mutually_exclusive(&self) -> bool {
+        self.mutually_exclusive
+    }
+
+    pub fn strike_date(&self) -> Option<DateTime<Utc>> {
+        self.strike_date
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -27,3 +82,83 @@ pub struct EventResponse {
     event: Event,
     markets: Vec<Market>,
 }
+
+impl EventResponse {
+    pub fn event(&self) -> &Event {
+        &self.event
+    }
+
+    pub fn markets(&self) -> &[Market] {
+        &self.markets
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetEventsParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series_ticker: Option<SeriesTicker>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<StatusFilter>,
+    /// Only events with a market closing at or after this unix timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_close_ts: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetEventsResponse {
+    pub events: Vec<Event>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+impl_paginated!(GetEventsResponse => events: Event);
+impl_cursor_params!(GetEventsParams);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        market::{MarketResult, MarketStatus, StrikeType},
+        usd,
+    };
+
+    #[test]
+    fn test_settled_event() {
+        let response: EventResponse =
+            serde_json::from_str(include_str!("../fixtures/market/settled_event.json")).unwrap();
+        let event = response.event();
+        assert_eq!(event.category(), "Climate and Weather");
+        assert_eq!(
+            event.collateral_return_type(),
+            Some(CollateralReturnType::MutuallyExclusiveNet)
+        );
+        assert_eq!(
+            event.strike_date().unwrap().to_rfc3339(),
+            "2025-10-11T03:59:00+00:00"
+        );
+
+        let [lost, won] = response.markets() else {
+            panic!("Expected two markets");
+        };
+        for market in [lost, won] {
+            assert_eq!(market.status(), MarketStatus::Finalized);
+            assert!(market.status().is_determined());
+            assert_eq!(market.expiration_value(), "63");
+            assert!(market.expiration_time().is_some());
+        }
+        assert_eq!(lost.strike_type(), StrikeType::Less);
+        assert_eq!(lost.result(), Some(MarketResult::No));
+        assert_eq!(lost.settlement_value(), Some(usd!(0)));
+        assert_eq!(won.result(), Some(MarketResult::Yes));
+        assert_eq!(won.settlement_value(), Some(usd!(1)));
+        assert_eq!(won.volume(), 130877);
+        assert_eq!(won.open_interest(), 60312);
+        // Books of settled markets are empty
+        assert_eq!(won.yes_bid(), None);
+        assert_eq!(won.no_ask(), None);
+    }
+}
@@ -0,0 +1,88 @@
+//! Kalshi trading fees: `ceil(rate × count × price × (1 − price))` to the
+//! next cent, charged per order execution.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::money::{Money, Price};
+
+/// Fee rate of orders taking liquidity
+pub const TAKER_FEE_RATE: Decimal = Decimal::from_parts(7, 0, 0, false, 2);
+/// Fee rate of resting orders on the markets that charge makers
+pub const MAKER_FEE_RATE: Decimal = Decimal::from_parts(175, 0, 0, false, 4);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+impl Liquidity {
+    pub fn from_is_taker(is_taker: bool) -> Self {
+        if is_taker {
+            Liquidity::Taker
+        } else {
+            Liquidity::Maker
+        }
+    }
+
+    pub fn fee_rate(&self) -> Decimal {
+        match self {
+            Liquidity::Maker => MAKER_FEE_RATE,
+            Liquidity::Taker => TAKER_FEE_RATE,
+        }
+    }
+}
+
+/// Fee of trading `count` contracts at `price` with a given rate
+pub fn fee_with_rate(rate: Decimal, count: u32, price: Price) -> Money {
+    let p = price.value();
+    let fee = rate * Decimal::from(count) * p * (Decimal::ONE - p);
+    Money::new(fee).ceil_cents()
+}
+
+pub fn fee(liquidity: Liquidity, count: u32, price: Price) -> Money {
+    fee_with_rate(liquidity.fee_rate(), count, price)
+}
+
+/// What buying `count` contracts at `price` costs, fees included
+pub fn total_cost(liquidity: Liquidity, count: u32, price: Price) -> Money {
+    price.cost(count) + fee(liquidity, count, price)
+}
+
+/// Profit of buying `count` contracts at `price` if they settle YES, fees
+/// included
+pub fn profit_if_won(liquidity: Liquidity, count: u32, price: Price) -> Money {
+    Money::from_cents(100 * i64::from(count)) - total_cost(liquidity, count, price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usd;
+
+    fn price(cents: u32) -> Price {
+        Price::from_cents(cents).unwrap()
+    }
+
+    #[test]
+    fn test_taker_fee() {
+        // 0.07 * 1 * 0.5 * 0.5 = 0.0175, rounded up
+        assert_eq!(fee(Liquidity::Taker, 1, price(50)), usd!(0.02));
+        // 0.07 * 100 * 0.5 * 0.5 = 1.75
+        assert_eq!(fee(Liquidity::Taker, 100, price(50)), usd!(1.75));
+        // 0.07 * 10 * 0.23 * 0.77 = 0.12397
+        assert_eq!(fee(Liquidity::Taker, 10, price(23)), usd!(0.13));
+        // Cheap contracts still pay a cent
+        assert_eq!(fee(Liquidity::Taker, 1, price(1)), usd!(0.01));
+    }
+
+    #[test]
+    fn test_maker_fee() {
+        // 0.0175 * 100 * 0.5 * 0.5 = 0.4375
+        assert_eq!(fee(Liquidity::Maker, 100, price(50)), usd!(0.44));
+        assert_eq!(total_cost(Liquidity::Maker, 100, price(50)), usd!(50.44));
+        assert_eq!(profit_if_won(Liquidity::Maker, 100, price(50)), usd!(49.56));
+    }
+}
@@ -0,0 +1,260 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::{
+    money::{Money, Price},
+    order::Side,
+    pagination::{CursorParams, Paginated, impl_cursor_params, impl_paginated},
+    ticker::MarketTicker,
+};
+
+/// Length of a candlestick period. Kalshi only accepts these three.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "u32")]
+pub enum CandlestickInterval {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl CandlestickInterval {
+    pub fn minutes(&self) -> u32 {
+        match self {
+            CandlestickInterval::Minute => 1,
+            CandlestickInterval::Hour => 60,
+            CandlestickInterval::Day => 1440,
+        }
+    }
+}
+
+impl TryFrom<u32> for CandlestickInterval {
+    type Error = String;
+
+    fn try_from(minutes: u32) -> Result<Self, Self::Error> {
+        match minutes {
+            1 => Ok(CandlestickInterval::Minute),
+            60 => Ok(CandlestickInterval::Hour),
+            1440 => Ok(CandlestickInterval::Day),
+            other => Err(format!(
+                "Unsupported candlestick interval of {} minutes",
+                other
+            )),
+        }
+    }
+}
+
+impl Serialize for CandlestickInterval {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u32(self.minutes())
+    }
+}
+
+/// Open, high, low and close of a quote or of traded prices over a period.
+/// Every field is `None` when there was nothing to measure.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ohlc {
+    #[serde(default)]
+    open_dollars: Option<Money>,
+    #[serde(default)]
+    high_dollars: Option<Money>,
+    #[serde(default)]
+    low_dollars: Option<Money>,
+    #[serde(default)]
+    close_dollars: Option<Money>,
+}
+
+impl Ohlc {
+    pub fn open(&self) -> Option<Money> {
+        self.open_dollars
+    }
+
+    pub fn high(&self) -> Option<Money> {
+        self.high_dollars
+    }
+
+    pub fn low(&self) -> Option<Money> {
+        self.low_dollars
+    }
+
+    pub fn close(&self) -> Option<Money> {
+        self.close_dollars
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Candlestick {
+    /// Unix timestamp of the end of the period
+    end_period_ts: i64,
+    #[serde(default)]
+    yes_bid: Ohlc,
+    #[serde(default)]
+    yes_ask: Ohlc,
+    /// Prices of the trades during the period
+    #[serde(default)]
+    price: Ohlc,
+    #[serde(default)]
+    volume: i64,
+    #[serde(default)]
+    open_interest: i64,
+}
+
+impl Candlestick {
+    pub fn end_period_ts(&self) -> i64 {
+        self.end_period_ts
+    }
+
+    pub fn end_period(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.end_period_ts, 0).unwrap_or_default()
+    }
+
+    pub fn yes_bid(&self) -> &Ohlc {
+        &self.yes_bid
+    }
+
+    pub fn yes_ask(&self) -> &Ohlc {
+        &self.yes_ask
+    }
+
+    pub fn price(&self) -> &Ohlc {
+        &self.price
+    }
+
+    pub fn volume(&self) -> i64 {
+        self.volume
+    }
+
+    pub fn open_interest(&self) -> i64 {
+        self.open_interest
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GetCandlesticksParams {
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub period_interval: CandlestickInterval,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetCandlesticksResponse {
+    pub ticker: MarketTicker,
+    pub candlesticks: Vec<Candlestick>,
+}
+
+/// A trade between two other accounts, as listed by the public trade history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trade {
+    trade_id: String,
+    ticker: MarketTicker,
+    count: u32,
+    yes_price_dollars: Price,
+    no_price_dollars: Price,
+    taker_side: Side,
+    created_time: DateTime<Utc>,
+}
+
+impl Trade {
+    pub fn trade_id(&self) -> &str {
+        &self.trade_id
+    }
+
+    pub fn ticker(&self) -> &MarketTicker {
+        &self.ticker
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn yes_price(&self) -> Price {
+        self.yes_price_dollars
+    }
+
+    pub fn no_price(&self) -> Price {
+        self.no_price_dollars
+    }
+
+    pub fn taker_side(&self) -> Side {
+        self.taker_side
+    }
+
+    pub fn created_time(&self) -> DateTime<Utc> {
+        self.created_time
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetTradesParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticker: Option<MarketTicker>,
+    /// Only trades at or after this unix timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_ts: Option<i64>,
+    /// Only trades at or before this unix timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ts: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetTradesResponse {
+    pub trades: Vec<Trade>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+impl_paginated!(GetTradesResponse => trades: Trade);
+impl_cursor_params!(GetTradesParams);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usd;
+
+    #[test]
+    fn test_candlesticks() {
+        let response: GetCandlesticksResponse =
+            serde_json::from_str(include_str!("../fixtures/history/candlesticks.json")).unwrap();
+        let [quiet, traded] = &response.candlesticks[..] else {
+            panic!("Expected two candlesticks");
+        };
+        assert_eq!(quiet.end_period().to_rfc3339(), "2025-10-17T14:00:00+00:00");
+        assert_eq!(quiet.price(), &Ohlc::default());
+        assert_eq!(quiet.yes_bid().close(), Some(usd!(0.22)));
+
+        assert_eq!(traded.volume(), 412);
+        assert_eq!(traded.price().high(), Some(usd!(0.25)));
+        assert_eq!(traded.yes_ask().low(), Some(usd!(0.23)));
+    }
+
+    #[test]
+    fn test_trades() {
+        let response: GetTradesResponse =
+            serde_json::from_str(include_str!("../fixtures/history/trades.json")).unwrap();
+        let (trades, cursor) = response.into_page();
+        assert_eq!(cursor, None);
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].taker_side(), Side::No);
+        assert_eq!(trades[0].yes_price(), Price::from_cents(23).unwrap());
+        assert_eq!(trades[0].no_price(), Price::from_cents(77).unwrap());
+    }
+
+    #[test]
+    fn test_interval_query() {
+        let params = GetCandlesticksParams {
+            start_ts: 0,
+            end_ts: 60,
+            period_interval: CandlestickInterval::Hour,
+        };
+        assert_eq!(
+            serde_json::to_value(&params).unwrap()["period_interval"],
+            60
+        );
+    }
+}
@@ -1,4 +1,12 @@
 pub mod event;
+pub mod fee;
+pub mod history;
 pub mod market;
 pub mod money;
+pub mod order;
+pub mod orderbook;
+pub mod pagination;
+pub mod portfolio;
+pub mod series;
 pub mod ticker;
+pub mod websocket;
@@ -1,36 +1,429 @@
+use anyhow::bail;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize, de::IntoDeserializer};
+use std::str::FromStr;
 
 use crate::{
-    money::Price,
-    ticker::{EventTicker, MarketTicker},
+    money::{Money, Price},
+    pagination::{CursorParams, Paginated, impl_cursor_params, impl_paginated},
+    ticker::{EventTicker, MarketTicker, SeriesTicker},
 };
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+/// Variants Kalshi adds later deserialize as `Unknown` rather than failing
+/// the whole response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum StrikeType {
     Between,
     Greater,
+    GreaterOrEqual,
     Less,
+    LessOrEqual,
+    Functional,
+    Custom,
+    Structured,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketType {
+    Binary,
+    Scalar,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Lifecycle of a market as reported by the exchange, which differs from the
+/// values accepted by `StatusFilter`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketStatus {
+    Initialized,
+    Inactive,
+    Active,
+    Closed,
+    Determined,
+    Disputed,
+    Amended,
+    Finalized,
+    Settled,
+    #[serde(other)]
+    Unknown,
+}
+
+impl MarketStatus {
+    /// Whether orders can be placed
+    pub fn is_trading(&self) -> bool {
+        matches!(self, MarketStatus::Active)
+    }
+
+    /// Whether the outcome is known, even if payouts are still pending
+    pub fn is_determined(&self) -> bool {
+        matches!(
+            self,
+            MarketStatus::Determined
+                | MarketStatus::Disputed
+                | MarketStatus::Amended
+                | MarketStatus::Finalized
+                | MarketStatus::Settled
+        )
+    }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketResult {
+    Yes,
+    No,
+    Void,
+    Scalar,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Status values accepted by the list filters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusFilter {
+    Unopened,
+    Open,
+    Closed,
+    Settled,
+}
+
+impl FromStr for StatusFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "unopened" => Self::Unopened,
+            "open" => Self::Open,
+            "closed" => Self::Closed,
+            "settled" => Self::Settled,
+            other => bail!("Unknown market status {}", other),
+        })
+    }
+}
+
+/// A market as returned by the markets and events endpoints. Fields missing
+/// from older responses fall back to their defaults.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Market {
     ticker: MarketTicker,
     event_ticker: EventTicker,
+    #[serde(default = "binary")]
+    market_type: MarketType,
     title: String,
+    #[serde(default)]
+    yes_sub_title: String,
+    #[serde(default)]
+    no_sub_title: String,
+    status: MarketStatus,
     open_time: DateTime<Utc>,
     close_time: DateTime<Utc>,
+    /// When the outcome is expected to be known
+    #[serde(default)]
+    expected_expiration_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    expiration_time: Option<DateTime<Utc>>,
+    /// Latest the market can expire, e.g. when the data source is late
+    #[serde(default)]
+    latest_expiration_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    can_close_early: bool,
     strike_type: StrikeType,
-    floor_strike: Option<i64>,
-    cap_strike: Option<i64>,
-    yes_bid_dollars: Price,
-    yes_ask_dollars: Price,
-    no_bid_dollars: Price,
-    no_ask_dollars: Price,
+    /// Strikes can be fractional, e.g. 61.5
+    floor_strike: Option<f64>,
+    cap_strike: Option<f64>,
+    /// Quotes are `None` when nobody is bidding or offering
+    #[serde(deserialize_with = "quote")]
+    yes_bid_dollars: Option<Price>,
+    #[serde(deserialize_with = "quote")]
+    yes_ask_dollars: Option<Price>,
+    #[serde(deserialize_with = "quote")]
+    no_bid_dollars: Option<Price>,
+    #[serde(deserialize_with = "quote")]
+    no_ask_dollars: Option<Price>,
+    #[serde(default, deserialize_with = "quote")]
+    last_price_dollars: Option<Price>,
+    /// Contracts traded over the market's lifetime
+    #[serde(default)]
+    volume: i64,
+    #[serde(default)]
+    volume_24h: i64,
+    /// Contracts currently held
+    #[serde(default)]
+    open_interest: i64,
+    /// Value of the resting orders
+    #[serde(default)]
+    liquidity_dollars: Money,
+    /// Empty until the market is determined
+    #[serde(default, deserialize_with = "result")]
+    result: Option<MarketResult>,
+    /// What the YES side paid out per contract, once settled
+    #[serde(default)]
+    settlement_value_dollars: Option<Money>,
+    /// Value of the underlying the outcome was determined from, e.g. `63`
+    #[serde(default)]
+    expiration_value: String,
+    #[serde(default)]
+    rules_primary: String,
+    #[serde(default)]
+    rules_secondary: String,
+}
+
+fn binary() -> MarketType {
+    MarketType::Binary
+}
+
+/// Kalshi sends an empty result for markets that are not determined yet
+fn result<'de, D>(deserializer: D) -> Result<Option<MarketResult>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = Option::<String>::deserialize(deserializer)?;
+    match s.as_deref() {
+        None | Some("") => Ok(None),
+        Some(s) => MarketResult::deserialize(s.into_deserializer()).map(Some),
+    }
+}
+
+/// Kalshi quotes an empty side of the book as 0 or 1, which are not valid
+/// contract prices
+pub(crate) fn quote<'de, D>(deserializer: D) -> Result<Option<Price>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = Option::<String>::deserialize(deserializer)?;
+    let Some(s) = s else {
+        return Ok(None);
+    };
+    let dollars: Decimal = s.parse().map_err(serde::de::Error::custom)?;
+    if dollars <= Decimal::ZERO || dollars >= Decimal::ONE {
+        return Ok(None);
+    }
+    Price::new(dollars)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+impl Market {
+    pub fn ticker(&self) -> &MarketTicker {
+        &self.ticker
+    }
+
+    pub fn event_ticker(&self) -> &EventTicker {
+        &self.event_ticker
+    }
+
+    pub fn market_type(&self) -> MarketType {
+        self.market_type
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn yes_sub_title(&self) -> &str {
+        &self.yes_sub_title
+    }
+
+    pub fn no_sub_title(&self) -> &str {
+        &self.no_sub_title
+    }
+
+    pub fn status(&self) -> MarketStatus {
+        self.status
+    }
+
+    pub fn open_time(&self) -> DateTime<Utc> {
+        self.open_time
+    }
+
+    pub fn close_time(&self) -> DateTime<Utc> {
+        self.close_time
+    }
+
+    pub fn expected_expiration_time(&self) -> Option<DateTime<Utc>> {
+        self.expected_expiration_time
+    }
+
+    pub fn expiration_time(&self) -> Option<DateTime<Utc>> {
+        self.expiration_time
+    }
+
+    pub fn latest_expiration_time(&self) -> Option<DateTime<Utc>> {
+        self.latest_expiration_time
+    }
+
+    pub fn can_close_early(&self) -> bool {
+        self.can_close_early
+    }
+
+    pub fn strike_type(&self) -> StrikeType {
+        self.strike_type
+    }
+
+    /// Lower strike of `Between` and `Greater` markets
+    pub fn floor_strike(&self) -> Option<f64> {
+        self.floor_strike
+    }
+
+    /// Upper strike of `Between` and `Less` markets
+    pub fn cap_strike(&self) -> Option<f64> {
+        self.cap_strike
+    }
+
+    pub fn yes_bid(&self) -> Option<Price> {
+        self.yes_bid_dollars
+    }
+
+    pub fn yes_ask(&self) -> Option<Price> {
+        self.yes_ask_dollars
+    }
+
+    pub fn no_bid(&self) -> Option<Price> {
+        self.no_bid_dollars
+    }
+
+    pub fn no_ask(&self) -> Option<Price> {
+        self.no_ask_dollars
+    }
+
+    pub fn last_price(&self) -> Option<Price> {
+        self.last_price_dollars
+    }
+
+    pub fn volume(&self) -> i64 {
+        self.volume
+    }
+
+    pub fn volume_24h(&self) -> i64 {
+        self.volume_24h
+    }
+
+    pub fn open_interest(&self) -> i64 {
+        self.open_interest
+    }
+
+    pub fn liquidity(&self) -> Money {
+        self.liquidity_dollars
+    }
+
+    pub fn result(&self) -> Option<MarketResult> {
+        self.result
+    }
+
+    pub fn settlement_value(&self) -> Option<Money> {
+        self.settlement_value_dollars
+    }
+
+    pub fn expiration_value(&self) -> &str {
+        &self.expiration_value
+    }
+
+    pub fn rules_primary(&self) -> &str {
+        &self.rules_primary
+    }
+
+    pub fn rules_secondary(&self) -> &str {
+        &self.rules_secondary
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MarketResponse {
     market: Market,
 }
+
+impl MarketResponse {
+    pub fn market(&self) -> &Market {
+        &self.market
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetMarketsParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_ticker: Option<EventTicker>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series_ticker: Option<SeriesTicker>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<StatusFilter>,
+    /// Only markets closing at or after this unix timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_close_ts: Option<i64>,
+    /// Only markets closing at or before this unix timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_close_ts: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl GetMarketsParams {
+    pub fn with_close_time_range(mut self, from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        self.min_close_ts = Some(from.timestamp());
+        self.max_close_ts = Some(to.timestamp());
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetMarketsResponse {
+    pub markets: Vec<Market>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+impl_paginated!(GetMarketsResponse => markets: Market);
+impl_cursor_params!(GetMarketsParams);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usd;
+
+    #[test]
+    fn test_open_market_fields() {
+        let page: GetMarketsResponse =
+            serde_json::from_str(include_str!("../fixtures/market/markets_page.json")).unwrap();
+        let market = &page.markets[0];
+        assert_eq!(market.status(), MarketStatus::Active);
+        assert!(market.status().is_trading());
+        assert_eq!(market.market_type(), MarketType::Binary);
+        assert_eq!(market.yes_ask(), Some(Price::from_cents(25).unwrap()));
+        assert_eq!(market.last_price(), Some(Price::from_cents(23).unwrap()));
+        assert_eq!(market.floor_strike(), Some(61.0));
+        assert_eq!(market.volume(), 5120);
+        assert_eq!(market.volume_24h(), 4301);
+        assert_eq!(market.open_interest(), 3377);
+        assert_eq!(market.liquidity(), usd!(1843.21));
+        assert_eq!(market.result(), None);
+        assert_eq!(market.settlement_value(), None);
+        assert!(market.rules_primary().contains("between 61-62°"));
+        assert_eq!(market.expiration_time(), None);
+        assert_eq!(
+            market.latest_expiration_time().unwrap().to_rfc3339(),
+            "2025-10-24T14:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_unknown_variants_are_tolerated() {
+        let page: GetMarketsResponse =
+            serde_json::from_str(include_str!("../fixtures/market/markets_page.json")).unwrap();
+        let market = &page.markets[1];
+        assert_eq!(market.market_type(), MarketType::Unknown);
+        assert_eq!(market.status(), MarketStatus::Unknown);
+        assert_eq!(market.strike_type(), StrikeType::Unknown);
+        assert_eq!(market.result(), Some(MarketResult::Unknown));
+        assert_eq!(market.yes_bid(), None);
+        assert_eq!(market.yes_ask(), None);
+        assert_eq!(market.volume(), 0);
+    }
+}
@@ -1,12 +1,205 @@
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, RoundingStrategy, prelude::ToPrimitive};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt::Display,
+    iter::Sum,
+    ops::{Add, Neg, Sub},
+    str::FromStr,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoneyError {
+    Parse(String),
+    /// Contract prices must be within 0.01 and 0.99
+    PriceOutOfRange(Decimal),
+}
+
+impl Display for MoneyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoneyError::Parse(s) => write!(f, "Invalid amount {}", s),
+            MoneyError::PriceOutOfRange(price) => {
+                write!(f, "Price {} is outside of 0.01 to 0.99", price)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MoneyError {}
+
+/// An amount of dollars, possibly negative or fractional cents
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Money(Decimal);
 
-#[derive(Debug, Clone)]
+/// Price of one contract in dollars, which is also the implied probability
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Price(Decimal);
 
+impl Money {
+    pub const ZERO: Money = Money(Decimal::ZERO);
+
+    pub fn new(dollars: Decimal) -> Self {
+        Self(dollars)
+    }
+
+    pub fn from_cents(cents: i64) -> Self {
+        Self(Decimal::new(cents, 2))
+    }
+
+    pub fn value(&self) -> Decimal {
+        self.0
+    }
+
+    /// Whole cents, rounding half a cent away from zero
+    pub fn cents(&self) -> i64 {
+        self.0
+            .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+            .checked_mul(Decimal::ONE_HUNDRED)
+            .and_then(|cents| cents.to_i64())
+            .unwrap_or(if self.0.is_sign_negative() {
+                i64::MIN
+            } else {
+                i64::MAX
+            })
+    }
+
+    /// Round up to the next whole cent, the way Kalshi rounds fees
+    pub fn ceil_cents(&self) -> Money {
+        Self(
+            self.0
+                .round_dp_with_strategy(2, RoundingStrategy::AwayFromZero),
+        )
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0.is_sign_negative() && !self.0.is_zero()
+    }
+
+    pub fn checked_add(&self, other: Money) -> Option<Money> {
+        self.0.checked_add(other.0).map(Money)
+    }
+
+    pub fn checked_sub(&self, other: Money) -> Option<Money> {
+        self.0.checked_sub(other.0).map(Money)
+    }
+
+    pub fn checked_mul(&self, count: u32) -> Option<Money> {
+        self.0.checked_mul(Decimal::from(count)).map(Money)
+    }
+
+    /// How many contracts at `price` this amount pays for
+    pub fn contracts_at(&self, price: Price) -> u32 {
+        if self.is_negative() {
+            return 0;
+        }
+        (self.0 / price.0).floor().to_u32().unwrap_or(u32::MAX)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        self.checked_add(other).expect("Money overflow")
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        self.checked_sub(other).expect("Money overflow")
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+impl Price {
+    pub const MIN: Price = Price(Decimal::from_parts(1, 0, 0, false, 2));
+    pub const MAX: Price = Price(Decimal::from_parts(99, 0, 0, false, 2));
+
+    pub fn new(dollars: Decimal) -> Result<Self, MoneyError> {
+        if dollars < Self::MIN.0 || dollars > Self::MAX.0 {
+            return Err(MoneyError::PriceOutOfRange(dollars));
+        }
+        Ok(Self(dollars))
+    }
+
+    pub fn from_cents(cents: u32) -> Result<Self, MoneyError> {
+        Self::new(Decimal::new(cents.into(), 2))
+    }
+
+    pub fn value(&self) -> Decimal {
+        self.0
+    }
+
+    /// Whole cents, rounding sub-penny prices to the nearest cent
+    pub fn cents(&self) -> u32 {
+        (self.0 * Decimal::ONE_HUNDRED)
+            .round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+            .to_u32()
+            .unwrap_or_default()
+    }
+
+    /// Price of the opposite side of the same contract
+    pub fn complement(&self) -> Price {
+        Price(Decimal::ONE - self.0)
+    }
+
+    /// Cost of `count` contracts, before fees
+    pub fn cost(&self, count: u32) -> Money {
+        Money(self.0 * Decimal::from(count))
+    }
+}
+
+impl Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "${}", self.0.round_dp(4))
+    }
+}
+
+impl Display for Price {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.normalize())
+    }
+}
+
+impl FromStr for Money {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let dollars = s
+            .trim()
+            .parse()
+            .map_err(|_| MoneyError::Parse(s.to_string()))?;
+        Ok(Money(dollars))
+    }
+}
+
+impl FromStr for Price {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let dollars = s
+            .trim()
+            .parse()
+            .map_err(|_| MoneyError::Parse(s.to_string()))?;
+        Price::new(dollars)
+    }
+}
+
 macro_rules! impl_money {
     ($($t:ident),+ $(,)?) => {
         $(
@@ -25,8 +218,13 @@ macro_rules! impl_money {
                     D: Deserializer<'de>,
                 {
                     let s = String::deserialize(deserializer)?;
-                    let dec = s.parse::<Decimal>().map_err(serde::de::Error::custom)?;
-                    Ok($t(dec))
+                    s.parse().map_err(serde::de::Error::custom)
+                }
+            }
+
+            impl From<$t> for Decimal {
+                fn from(value: $t) -> Decimal {
+                    value.0
                 }
             }
         )+
@@ -35,9 +233,51 @@ macro_rules! impl_money {
 
 impl_money!(Money, Price);
 
+/// A `Money` literal, e.g. `usd!(12.50)`
 #[macro_export]
 macro_rules! usd {
     ($val:expr) => {
-        Money(Decimal::from_str(stringify!($val)).unwrap())
+        <$crate::money::Money as ::std::str::FromStr>::from_str(&stringify!($val).replace(' ', ""))
+            .unwrap()
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_range() {
+        assert!("0.01".parse::<Price>().is_ok());
+        assert!("0.99".parse::<Price>().is_ok());
+        assert_eq!(
+            "1".parse::<Price>(),
+            Err(MoneyError::PriceOutOfRange(Decimal::ONE))
+        );
+        assert!("0.00".parse::<Price>().is_err());
+        assert!(serde_json::from_str::<Price>("\"1.00\"").is_err());
+
+        let price = Price::from_cents(23).unwrap();
+        assert_eq!(price.complement(), Price::from_cents(77).unwrap());
+        assert_eq!(price.cents(), 23);
+    }
+
+    #[test]
+    fn test_money() {
+        assert_eq!(usd!(12.50), Money::from_cents(1250));
+        assert_eq!(usd!(-0.5), Money::from_cents(-50));
+        assert_eq!(usd!(0.125).cents(), 13);
+        assert_eq!(usd!(0.121).ceil_cents(), usd!(0.13));
+
+        let price: Price = "0.23".parse().unwrap();
+        assert_eq!(price.cost(10), usd!(2.30));
+        assert_eq!(usd!(2.29).contracts_at(price), 9);
+        assert_eq!(
+            vec![usd!(1), usd!(2.5), -usd!(0.5)]
+                .into_iter()
+                .sum::<Money>(),
+            usd!(3)
+        );
+        assert_eq!(Money::new(Decimal::MAX).checked_add(usd!(1)), None);
+    }
+}
@@ -0,0 +1,611 @@
+use anyhow::bail;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+use uuid::Uuid;
+
+use crate::{
+    money::Price,
+    pagination::{CursorParams, Paginated, impl_cursor_params, impl_paginated},
+    ticker::MarketTicker,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Yes,
+    No,
+}
+
+impl Side {
+    pub fn opposite(&self) -> Side {
+        match self {
+            Side::Yes => Side::No,
+            Side::No => Side::Yes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Buy,
+    Sell,
+}
+
+impl Display for Side {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Side::Yes => write!(f, "yes"),
+            Side::No => write!(f, "no"),
+        }
+    }
+}
+
+impl FromStr for Side {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "yes" => Side::Yes,
+            "no" => Side::No,
+            other => bail!("Unknown side {}", other),
+        })
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Buy => write!(f, "buy"),
+            Action::Sell => write!(f, "sell"),
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "buy" => Action::Buy,
+            "sell" => Action::Sell,
+            other => bail!("Unknown action {}", other),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderType {
+    Limit,
+    Market,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderStatus {
+    Pending,
+    Resting,
+    Canceled,
+    Executed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeInForce {
+    FillOrKill,
+    GoodTillCanceled,
+    ImmediateOrCancel,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OrderId(String);
+
+/// Idempotency key chosen by us. Re-sending a create request with the same id
+/// can never result in a second order on the exchange.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ClientOrderId(String);
+
+impl ClientOrderId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+
+    /// A new id starting with `prefix`, so that the orders of one bot can be
+    /// told apart from manual ones after a restart
+    pub fn with_prefix(prefix: &str) -> Self {
+        Self(format!("{}-{}", prefix, Uuid::new_v4()))
+    }
+
+    pub fn has_prefix(&self, prefix: &str) -> bool {
+        self.0
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('-'))
+    }
+}
+
+impl Default for ClientOrderId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for OrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderStatus::Pending => write!(f, "pending"),
+            OrderStatus::Resting => write!(f, "resting"),
+            OrderStatus::Canceled => write!(f, "canceled"),
+            OrderStatus::Executed => write!(f, "executed"),
+        }
+    }
+}
+
+impl From<String> for OrderId {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<String> for ClientOrderId {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for OrderId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Display for ClientOrderId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateOrderRequest {
+    ticker: MarketTicker,
+    client_order_id: ClientOrderId,
+    side: Side,
+    action: Action,
+    count: u32,
+    #[serde(rename = "type")]
+    order_type: OrderType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    yes_price_dollars: Option<Price>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    no_price_dollars: Option<Price>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time_in_force: Option<TimeInForce>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expiration_ts: Option<i64>,
+    /// Maximum cost in cents a market buy is allowed to spend
+    #[serde(skip_serializing_if = "Option::is_none")]
+    buy_max_cost: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reduce_only: Option<bool>,
+}
+
+impl CreateOrderRequest {
+    fn new(
+        ticker: MarketTicker,
+        side: Side,
+        action: Action,
+        count: u32,
+        order_type: OrderType,
+    ) -> Self {
+        Self {
+            ticker,
+            client_order_id: ClientOrderId::new(),
+            side,
+            action,
+            count,
+            order_type,
+            yes_price_dollars: None,
+            no_price_dollars: None,
+            time_in_force: None,
+            expiration_ts: None,
+            buy_max_cost: None,
+            post_only: None,
+            reduce_only: None,
+        }
+    }
+
+    /// A limit order. `price` is the price of the contract on `side`.
+    pub fn limit(
+        ticker: MarketTicker,
+        side: Side,
+        action: Action,
+        count: u32,
+        price: Price,
+    ) -> Self {
+        let mut request = Self::new(ticker, side, action, count, OrderType::Limit);
+        match side {
+            Side::Yes => request.yes_price_dollars = Some(price),
+            Side::No => request.no_price_dollars = Some(price),
+        }
+        request
+    }
+
+    pub fn market(ticker: MarketTicker, side: Side, action: Action, count: u32) -> Self {
+        Self::new(ticker, side, action, count, OrderType::Market)
+    }
+
+    /// Reuse the id of a previous attempt so that a retry is deduplicated by the exchange
+    pub fn with_client_order_id(mut self, client_order_id: ClientOrderId) -> Self {
+        self.client_order_id = client_order_id;
+        self
+    }
+
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = Some(time_in_force);
+        self
+    }
+
+    pub fn with_expiration(mut self, expiration: DateTime<Utc>) -> Self {
+        self.expiration_ts = Some(expiration.timestamp());
+        self
+    }
+
+    pub fn with_buy_max_cost(mut self, cents: i64) -> Self {
+        self.buy_max_cost = Some(cents);
+        self
+    }
+
+    pub fn post_only(mut self) -> Self {
+        self.post_only = Some(true);
+        self
+    }
+
+    pub fn reduce_only(mut self) -> Self {
+        self.reduce_only = Some(true);
+        self
+    }
+
+    pub fn ticker(&self) -> &MarketTicker {
+        &self.ticker
+    }
+
+    pub fn client_order_id(&self) -> &ClientOrderId {
+        &self.client_order_id
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    pub fn action(&self) -> Action {
+        self.action
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn order_type(&self) -> OrderType {
+        self.order_type
+    }
+
+    pub fn price(&self) -> Option<&Price> {
+        self.yes_price_dollars
+            .as_ref()
+            .or(self.no_price_dollars.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmendOrderRequest {
+    ticker: MarketTicker,
+    side: Side,
+    action: Action,
+    client_order_id: ClientOrderId,
+    updated_client_order_id: ClientOrderId,
+    count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    yes_price_dollars: Option<Price>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    no_price_dollars: Option<Price>,
+}
+
+impl AmendOrderRequest {
+    /// Amend `order` to a new price and total count. The amended order gets a
+    /// fresh client order id.
+    pub fn new(order: &Order, count: u32, price: Price) -> Self {
+        let (yes_price_dollars, no_price_dollars) = match order.side {
+            Side::Yes => (Some(price), None),
+            Side::No => (None, Some(price)),
+        };
+        Self {
+            ticker: order.ticker.clone(),
+            side: order.side,
+            action: order.action,
+            client_order_id: order.client_order_id.clone(),
+            updated_client_order_id: ClientOrderId::new(),
+            count,
+            yes_price_dollars,
+            no_price_dollars,
+        }
+    }
+
+    pub fn with_updated_client_order_id(mut self, client_order_id: ClientOrderId) -> Self {
+        self.updated_client_order_id = client_order_id;
+        self
+    }
+
+    pub fn updated_client_order_id(&self) -> &ClientOrderId {
+        &self.updated_client_order_id
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn price(&self) -> Option<&Price> {
+        self.yes_price_dollars
+            .as_ref()
+            .or(self.no_price_dollars.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecreaseOrderRequest {
+    ReduceBy(u32),
+    ReduceTo(u32),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Order {
+    order_id: OrderId,
+    client_order_id: ClientOrderId,
+    ticker: MarketTicker,
+    side: Side,
+    action: Action,
+    #[serde(rename = "type")]
+    order_type: OrderType,
+    status: OrderStatus,
+    yes_price_dollars: Price,
+    no_price_dollars: Price,
+    #[serde(default)]
+    fill_count: u32,
+    #[serde(default)]
+    remaining_count: u32,
+    #[serde(default)]
+    initial_count: u32,
+    created_time: Option<DateTime<Utc>>,
+    last_update_time: Option<DateTime<Utc>>,
+    expiration_time: Option<DateTime<Utc>>,
+}
+
+impl Order {
+    pub fn order_id(&self) -> &OrderId {
+        &self.order_id
+    }
+
+    pub fn client_order_id(&self) -> &ClientOrderId {
+        &self.client_order_id
+    }
+
+    pub fn ticker(&self) -> &MarketTicker {
+        &self.ticker
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    pub fn action(&self) -> Action {
+        self.action
+    }
+
+    pub fn order_type(&self) -> OrderType {
+        self.order_type
+    }
+
+    pub fn status(&self) -> OrderStatus {
+        self.status
+    }
+
+    /// Limit price of the contract on this order's side
+    pub fn price(&self) -> &Price {
+        match self.side {
+            Side::Yes => &self.yes_price_dollars,
+            Side::No => &self.no_price_dollars,
+        }
+    }
+
+    pub fn fill_count(&self) -> u32 {
+        self.fill_count
+    }
+
+    pub fn remaining_count(&self) -> u32 {
+        self.remaining_count
+    }
+
+    pub fn initial_count(&self) -> u32 {
+        self.initial_count
+    }
+
+    pub fn created_time(&self) -> Option<DateTime<Utc>> {
+        self.created_time
+    }
+
+    pub fn last_update_time(&self) -> Option<DateTime<Utc>> {
+        self.last_update_time
+    }
+
+    pub fn expiration_time(&self) -> Option<DateTime<Utc>> {
+        self.expiration_time
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetOrdersParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticker: Option<MarketTicker>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<OrderStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderResponse {
+    pub order: Order,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AmendOrderResponse {
+    pub old_order: Order,
+    pub order: Order,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CancelOrderResponse {
+    pub order: Order,
+    pub reduced_by: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetOrdersResponse {
+    pub orders: Vec<Order>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+impl_paginated!(GetOrdersResponse => orders: Order);
+impl_cursor_params!(GetOrdersParams);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ticker() -> MarketTicker {
+        "KXHIGHNY-25OCT17-B61.5".parse().unwrap()
+    }
+
+    fn price(cents: u32) -> Price {
+        Price::from_cents(cents).unwrap()
+    }
+
+    #[test]
+    fn test_create_order_requests() {
+        let client_order_id = ClientOrderId::with_prefix("mm");
+        let request = CreateOrderRequest::limit(ticker(), Side::No, Action::Buy, 10, price(78))
+            .with_client_order_id(client_order_id.clone())
+            .post_only();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "ticker": "KXHIGHNY-25OCT17-B61.5",
+                "client_order_id": client_order_id,
+                "side": "no",
+                "action": "buy",
+                "count": 10,
+                "type": "limit",
+                "no_price_dollars": "0.78",
+                "post_only": true,
+            })
+        );
+        assert_eq!(request.price(), Some(&price(78)));
+
+        let request = CreateOrderRequest::market(ticker(), Side::Yes, Action::Sell, 5)
+            .with_time_in_force(TimeInForce::ImmediateOrCancel);
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value["type"], "market");
+        assert_eq!(value["time_in_force"], "immediate_or_cancel");
+        assert!(value.get("yes_price_dollars").is_none());
+        assert!(
+            value["client_order_id"]
+                .as_str()
+                .is_some_and(|id| !id.is_empty())
+        );
+        assert_ne!(
+            request.client_order_id(),
+            CreateOrderRequest::market(ticker(), Side::Yes, Action::Sell, 5).client_order_id()
+        );
+    }
+
+    #[test]
+    fn test_orders_page() {
+        let page: GetOrdersResponse =
+            serde_json::from_str(include_str!("../fixtures/order/orders_page.json")).unwrap();
+        assert_eq!(
+            page.cursor.as_deref(),
+            Some("CgwI-pm8xwYQ4KWgjQMSJDBmNWU3ZWQ0")
+        );
+
+        let [resting, executed] = &page.orders[..] else {
+            panic!("Expected two orders");
+        };
+        assert_eq!(resting.status(), OrderStatus::Resting);
+        assert!(resting.client_order_id().has_prefix("mm"));
+        assert_eq!(*resting.price(), price(22));
+        assert_eq!(
+            (
+                resting.fill_count(),
+                resting.remaining_count(),
+                resting.initial_count()
+            ),
+            (4, 6, 10)
+        );
+        assert_eq!(resting.expiration_time(), None);
+
+        // The price of a NO order is the NO price
+        assert_eq!(executed.side(), Side::No);
+        assert_eq!(executed.action(), Action::Sell);
+        assert_eq!(*executed.price(), price(97));
+        assert_eq!(executed.status(), OrderStatus::Executed);
+    }
+
+    #[test]
+    fn test_amend_order() {
+        let response: AmendOrderResponse =
+            serde_json::from_str(include_str!("../fixtures/order/amend_response.json")).unwrap();
+        assert_eq!(response.old_order.status(), OrderStatus::Canceled);
+        assert_eq!(response.order.order_id(), response.old_order.order_id());
+
+        let updated = ClientOrderId::with_prefix("mm");
+        let request = AmendOrderRequest::new(&response.old_order, 12, price(24))
+            .with_updated_client_order_id(updated.clone());
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "ticker": "KXHIGHNY-25OCT17-B61.5",
+                "side": "yes",
+                "action": "buy",
+                "client_order_id": "mm-4f1c6f8e-2b9e-4c55-8a3c-9d5e2a7b1c03",
+                "updated_client_order_id": updated,
+                "count": 12,
+                "yes_price_dollars": "0.24",
+            })
+        );
+    }
+
+    #[test]
+    fn test_decrease_and_cancel() {
+        assert_eq!(
+            serde_json::to_value(DecreaseOrderRequest::ReduceBy(3)).unwrap(),
+            json!({"reduce_by": 3})
+        );
+        assert_eq!(
+            serde_json::to_value(DecreaseOrderRequest::ReduceTo(0)).unwrap(),
+            json!({"reduce_to": 0})
+        );
+
+        let response: CancelOrderResponse =
+            serde_json::from_str(include_str!("../fixtures/order/cancel_response.json")).unwrap();
+        assert_eq!(response.reduced_by, 8);
+        assert_eq!(response.order.status(), OrderStatus::Canceled);
+        assert_eq!(response.order.remaining_count(), 0);
+    }
+}
@@ -0,0 +1,333 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::{
+    money::Price,
+    order::Side,
+    ticker::MarketTicker,
+    websocket::{OrderbookDelta, OrderbookSnapshot, WebsocketMessage},
+};
+
+/// Resting bids of a single market. Kalshi only has bids: a YES ask is the
+/// complement of a NO bid and vice versa.
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    ticker: MarketTicker,
+    yes: BTreeMap<Price, i64>,
+    no: BTreeMap<Price, i64>,
+}
+
+impl OrderBook {
+    pub fn new(ticker: MarketTicker) -> Self {
+        Self {
+            ticker,
+            yes: BTreeMap::new(),
+            no: BTreeMap::new(),
+        }
+    }
+
+    pub fn from_snapshot(snapshot: &OrderbookSnapshot) -> Self {
+        Self::from_levels(
+            snapshot.market_ticker.clone(),
+            &snapshot.yes_dollars,
+            &snapshot.no_dollars,
+        )
+    }
+
+    pub fn from_levels(ticker: MarketTicker, yes: &[(Price, i64)], no: &[(Price, i64)]) -> Self {
+        let mut book = Self::new(ticker);
+        for (price, count) in yes {
+            book.add(Side::Yes, *price, *count);
+        }
+        for (price, count) in no {
+            book.add(Side::No, *price, *count);
+        }
+        book
+    }
+
+    pub fn apply_delta(&mut self, delta: &OrderbookDelta) {
+        self.add(delta.side, delta.price_dollars, delta.delta);
+    }
+
+    fn add(&mut self, side: Side, price: Price, count: i64) {
+        let levels = self.levels_mut(side);
+        let resting = levels.entry(price).or_insert(0);
+        *resting += count;
+        if *resting <= 0 {
+            levels.remove(&price);
+        }
+    }
+
+    fn levels(&self, side: Side) -> &BTreeMap<Price, i64> {
+        match side {
+            Side::Yes => &self.yes,
+            Side::No => &self.no,
+        }
+    }
+
+    fn levels_mut(&mut self, side: Side) -> &mut BTreeMap<Price, i64> {
+        match side {
+            Side::Yes => &mut self.yes,
+            Side::No => &mut self.no,
+        }
+    }
+
+    pub fn ticker(&self) -> &MarketTicker {
+        &self.ticker
+    }
+
+    /// Highest bid and its size for `side`
+    pub fn best_bid(&self, side: Side) -> Option<(Price, i64)> {
+        self.levels(side)
+            .iter()
+            .next_back()
+            .map(|(price, count)| (*price, *count))
+    }
+
+    /// Lowest ask and its size for `side`, implied by the bids on the other side
+    pub fn best_ask(&self, side: Side) -> Option<(Price, i64)> {
+        self.best_bid(side.opposite())
+            .map(|(price, count)| (price.complement(), count))
+    }
+
+    /// Bids for `side`, best first
+    pub fn bids(&self, side: Side) -> Vec<(Price, i64)> {
+        self.levels(side)
+            .iter()
+            .rev()
+            .map(|(price, count)| (*price, *count))
+            .collect()
+    }
+
+    /// Asks for `side` implied by the other side's bids, best first
+    pub fn asks(&self, side: Side) -> Vec<(Price, i64)> {
+        self.bids(side.opposite())
+            .into_iter()
+            .map(|(price, count)| (price.complement(), count))
+            .collect()
+    }
+
+    /// Contracts bid at exactly `price` on `side`
+    pub fn depth_at(&self, side: Side, price: Price) -> i64 {
+        self.levels(side).get(&price).copied().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.yes.is_empty() && self.no.is_empty()
+    }
+}
+
+/// Resting bids as returned by `GET /markets/{ticker}/orderbook`, where an
+/// empty side is `null`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderbookLevels {
+    #[serde(default)]
+    pub yes_dollars: Option<Vec<(Price, i64)>>,
+    #[serde(default)]
+    pub no_dollars: Option<Vec<(Price, i64)>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetOrderbookResponse {
+    pub orderbook: OrderbookLevels,
+}
+
+impl GetOrderbookResponse {
+    pub fn book(&self, ticker: MarketTicker) -> OrderBook {
+        OrderBook::from_levels(
+            ticker,
+            self.orderbook.yes_dollars.as_deref().unwrap_or_default(),
+            self.orderbook.no_dollars.as_deref().unwrap_or_default(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceGap {
+    pub sid: u64,
+    pub expected: u64,
+    pub received: u64,
+    /// Markets whose books were dropped and need a new snapshot
+    pub tickers: Vec<MarketTicker>,
+}
+
+impl Display for SequenceGap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Sequence gap on subscription {}: expected {} but got {}",
+            self.sid, self.expected, self.received
+        )
+    }
+}
+
+impl std::error::Error for SequenceGap {}
+
+/// Order books of many markets, built from `orderbook_delta` channel messages.
+/// Sequence numbers are per subscription, so a gap invalidates every book fed
+/// by that subscription.
+#[derive(Debug, Default)]
+pub struct OrderBooks {
+    books: HashMap<MarketTicker, OrderBook>,
+    /// Last sequence number seen per subscription
+    seqs: HashMap<u64, u64>,
+    /// Subscription feeding each book
+    sids: HashMap<MarketTicker, u64>,
+    /// Markets whose deltas are ignored until a new snapshot arrives
+    awaiting_snapshot: HashSet<MarketTicker>,
+}
+
+impl OrderBooks {
+    pub fn get(&self, ticker: &MarketTicker) -> Option<&OrderBook> {
+        self.books.get(ticker)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &OrderBook> {
+        self.books.values()
+    }
+
+    /// Drop every book, e.g. after reconnecting
+    pub fn clear(&mut self) {
+        self.books.clear();
+        self.seqs.clear();
+        self.sids.clear();
+        self.awaiting_snapshot.clear();
+    }
+
+    /// Apply a websocket message. Messages that are not order book updates are
+    /// ignored.
+    pub fn apply(&mut self, message: &WebsocketMessage) -> Result<(), SequenceGap> {
+        match message {
+            WebsocketMessage::OrderbookSnapshot { sid, seq, msg } => {
+                self.seqs.insert(*sid, *seq);
+                self.sids.insert(msg.market_ticker.clone(), *sid);
+                self.awaiting_snapshot.remove(&msg.market_ticker);
+                self.books
+                    .insert(msg.market_ticker.clone(), OrderBook::from_snapshot(msg));
+                Ok(())
+            }
+            WebsocketMessage::OrderbookDelta { sid, seq, msg } => {
+                let last = self.seqs.insert(*sid, *seq);
+                if let Some(last) = last
+                    && *seq != last + 1
+                {
+                    return Err(self.invalidate(*sid, last + 1, *seq, &msg.market_ticker));
+                }
+
+                if self.awaiting_snapshot.contains(&msg.market_ticker) {
+                    return Ok(());
+                }
+                match self.books.get_mut(&msg.market_ticker) {
+                    Some(book) => {
+                        book.apply_delta(msg);
+                        Ok(())
+                    }
+                    None => Err(self.invalidate(*sid, 1, *seq, &msg.market_ticker)),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn invalidate(
+        &mut self,
+        sid: u64,
+        expected: u64,
+        received: u64,
+        ticker: &MarketTicker,
+    ) -> SequenceGap {
+        let mut tickers: Vec<MarketTicker> = self
+            .sids
+            .iter()
+            .filter(|(_, s)| **s == sid)
+            .map(|(t, _)| t.clone())
+            .collect();
+        if !tickers.contains(ticker) {
+            tickers.push(ticker.clone());
+        }
+        tickers.sort();
+
+        for ticker in tickers.iter() {
+            self.books.remove(ticker);
+            self.sids.remove(ticker);
+            self.awaiting_snapshot.insert(ticker.clone());
+        }
+        SequenceGap {
+            sid,
+            expected,
+            received,
+            tickers,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay(fixture: &str) -> (OrderBooks, Vec<SequenceGap>) {
+        let mut books = OrderBooks::default();
+        let mut gaps = vec![];
+        for line in fixture.lines().filter(|l| !l.trim().is_empty()) {
+            let message: WebsocketMessage = serde_json::from_str(line).unwrap();
+            if let Err(gap) = books.apply(&message) {
+                gaps.push(gap);
+            }
+        }
+        (books, gaps)
+    }
+
+    fn price(s: &str) -> Price {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_replay_deltas() {
+        let (books, gaps) = replay(include_str!("../fixtures/orderbook/deltas.jsonl"));
+        assert!(gaps.is_empty());
+
+        let ticker: MarketTicker = "KXHIGHNY-25OCT17-B61.5".parse().unwrap();
+        let book = books.get(&ticker).unwrap();
+        assert_eq!(book.best_bid(Side::Yes), Some((price("0.23"), 120)));
+        assert_eq!(book.best_bid(Side::No), Some((price("0.74"), 15)));
+        assert_eq!(book.best_ask(Side::Yes), Some((price("0.26"), 15)));
+        assert_eq!(book.best_ask(Side::No), Some((price("0.77"), 120)));
+        assert_eq!(book.depth_at(Side::Yes, price("0.22")), 333);
+        // Fully consumed levels disappear
+        assert_eq!(book.depth_at(Side::No, price("0.75")), 0);
+        assert_eq!(
+            book.bids(Side::Yes),
+            vec![
+                (price("0.23"), 120),
+                (price("0.22"), 333),
+                (price("0.08"), 300)
+            ]
+        );
+
+        let ticker: MarketTicker = "KXHIGHNY-25OCT17-T60".parse().unwrap();
+        let book = books.get(&ticker).unwrap();
+        assert_eq!(book.best_bid(Side::Yes), Some((price("0.05"), 50)));
+        assert_eq!(book.best_ask(Side::Yes), None);
+    }
+
+    #[test]
+    fn test_sequence_gap_requests_snapshot() {
+        let (books, gaps) = replay(include_str!("../fixtures/orderbook/gap.jsonl"));
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].sid, 2);
+        assert_eq!(gaps[0].expected, 4);
+        assert_eq!(gaps[0].received, 5);
+        assert_eq!(gaps[0].tickers.len(), 2);
+
+        // Deltas after the gap are ignored until the snapshot comes back
+        let resnapshotted: MarketTicker = "KXHIGHNY-25OCT17-B61.5".parse().unwrap();
+        let book = books.get(&resnapshotted).unwrap();
+        assert_eq!(book.best_bid(Side::Yes), Some((price("0.30"), 10)));
+
+        let missing: MarketTicker = "KXHIGHNY-25OCT17-T60".parse().unwrap();
+        assert!(books.get(&missing).is_none());
+    }
+}
@@ -0,0 +1,43 @@
+/// A page of results from a list endpoint
+pub trait Paginated {
+    type Item;
+
+    /// The items of this page and the cursor of the next one. Kalshi returns an
+    /// empty cursor on the last page.
+    fn into_page(self) -> (Vec<Self::Item>, Option<String>);
+}
+
+/// Query parameters of a list endpoint
+pub trait CursorParams {
+    fn set_cursor(&mut self, cursor: String);
+}
+
+macro_rules! impl_paginated {
+    ($($t:ty => $field:ident: $item:ty),+ $(,)?) => {
+        $(
+            impl Paginated for $t {
+                type Item = $item;
+
+                fn into_page(self) -> (Vec<Self::Item>, Option<String>) {
+                    let cursor = self.cursor.filter(|cursor| !cursor.is_empty());
+                    (self.$field, cursor)
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! impl_cursor_params {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl CursorParams for $t {
+                fn set_cursor(&mut self, cursor: String) {
+                    self.cursor = Some(cursor);
+                }
+            }
+        )+
+    };
+}
+
+pub(crate) use impl_cursor_params;
+pub(crate) use impl_paginated;
@@ -0,0 +1,383 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    market::MarketResult,
+    money::{Money, Price},
+    order::{Action, ClientOrderId, OrderId, Side},
+    pagination::{CursorParams, Paginated, impl_cursor_params, impl_paginated},
+    ticker::{EventTicker, MarketTicker},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Balance {
+    /// Available balance in cents
+    balance: i64,
+    /// Value of all open positions in cents
+    #[serde(default)]
+    portfolio_value: i64,
+    updated_ts: Option<i64>,
+}
+
+impl Balance {
+    pub fn balance_cents(&self) -> i64 {
+        self.balance
+    }
+
+    pub fn portfolio_value_cents(&self) -> i64 {
+        self.portfolio_value
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketPosition {
+    ticker: MarketTicker,
+    /// Positive for YES contracts, negative for NO contracts
+    position: i64,
+    total_traded_dollars: Money,
+    market_exposure_dollars: Money,
+    realized_pnl_dollars: Money,
+    fees_paid_dollars: Money,
+    #[serde(default)]
+    resting_orders_count: i64,
+    last_updated_ts: Option<DateTime<Utc>>,
+}
+
+impl MarketPosition {
+    pub fn ticker(&self) -> &MarketTicker {
+        &self.ticker
+    }
+
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+
+    /// Side and number of contracts held, if any
+    pub fn holding(&self) -> Option<(Side, u32)> {
+        match self.position {
+            0 => None,
+            p if p > 0 => Some((Side::Yes, p as u32)),
+            p => Some((Side::No, p.unsigned_abs() as u32)),
+        }
+    }
+
+    pub fn total_traded(&self) -> &Money {
+        &self.total_traded_dollars
+    }
+
+    pub fn market_exposure(&self) -> &Money {
+        &self.market_exposure_dollars
+    }
+
+    pub fn realized_pnl(&self) -> &Money {
+        &self.realized_pnl_dollars
+    }
+
+    pub fn fees_paid(&self) -> &Money {
+        &self.fees_paid_dollars
+    }
+
+    pub fn resting_orders_count(&self) -> i64 {
+        self.resting_orders_count
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventPosition {
+    event_ticker: EventTicker,
+    total_cost_dollars: Money,
+    event_exposure_dollars: Money,
+    realized_pnl_dollars: Money,
+    fees_paid_dollars: Money,
+}
+
+impl EventPosition {
+    pub fn event_ticker(&self) -> &EventTicker {
+        &self.event_ticker
+    }
+
+    pub fn total_cost(&self) -> &Money {
+        &self.total_cost_dollars
+    }
+
+    pub fn event_exposure(&self) -> &Money {
+        &self.event_exposure_dollars
+    }
+
+    pub fn realized_pnl(&self) -> &Money {
+        &self.realized_pnl_dollars
+    }
+
+    pub fn fees_paid(&self) -> &Money {
+        &self.fees_paid_dollars
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    fill_id: String,
+    trade_id: String,
+    order_id: OrderId,
+    #[serde(default)]
+    client_order_id: Option<ClientOrderId>,
+    ticker: MarketTicker,
+    side: Side,
+    action: Action,
+    count: u32,
+    yes_price_dollars: Price,
+    no_price_dollars: Price,
+    is_taker: bool,
+    created_time: DateTime<Utc>,
+}
+
+impl Fill {
+    pub fn fill_id(&self) -> &str {
+        &self.fill_id
+    }
+
+    pub fn trade_id(&self) -> &str {
+        &self.trade_id
+    }
+
+    pub fn order_id(&self) -> &OrderId {
+        &self.order_id
+    }
+
+    pub fn client_order_id(&self) -> Option<&ClientOrderId> {
+        self.client_order_id.as_ref()
+    }
+
+    pub fn ticker(&self) -> &MarketTicker {
+        &self.ticker
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    pub fn action(&self) -> Action {
+        self.action
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Price of the contract on this fill's side
+    pub fn price(&self) -> &Price {
+        match self.side {
+            Side::Yes => &self.yes_price_dollars,
+            Side::No => &self.no_price_dollars,
+        }
+    }
+
+    pub fn is_taker(&self) -> bool {
+        self.is_taker
+    }
+
+    pub fn created_time(&self) -> DateTime<Utc> {
+        self.created_time
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settlement {
+    ticker: MarketTicker,
+    market_result: MarketResult,
+    yes_count: u32,
+    /// Cost of the YES contracts in cents
+    yes_total_cost: i64,
+    no_count: u32,
+    /// Cost of the NO contracts in cents
+    no_total_cost: i64,
+    /// Payout in cents
+    revenue: i64,
+    settled_time: DateTime<Utc>,
+}
+
+impl Settlement {
+    pub fn ticker(&self) -> &MarketTicker {
+        &self.ticker
+    }
+
+    pub fn market_result(&self) -> MarketResult {
+        self.market_result
+    }
+
+    pub fn yes_count(&self) -> u32 {
+        self.yes_count
+    }
+
+    pub fn yes_total_cost_cents(&self) -> i64 {
+        self.yes_total_cost
+    }
+
+    pub fn no_count(&self) -> u32 {
+        self.no_count
+    }
+
+    pub fn no_total_cost_cents(&self) -> i64 {
+        self.no_total_cost
+    }
+
+    pub fn revenue_cents(&self) -> i64 {
+        self.revenue
+    }
+
+    pub fn settled_time(&self) -> DateTime<Utc> {
+        self.settled_time
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettlementStatus {
+    All,
+    Settled,
+    Unsettled,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetPositionsParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticker: Option<MarketTicker>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_ticker: Option<EventTicker>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settlement_status: Option<SettlementStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetFillsParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticker: Option<MarketTicker>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_id: Option<OrderId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_ts: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ts: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetSettlementsParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticker: Option<MarketTicker>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_ticker: Option<EventTicker>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_ts: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ts: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetPositionsResponse {
+    pub market_positions: Vec<MarketPosition>,
+    pub event_positions: Vec<EventPosition>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetFillsResponse {
+    pub fills: Vec<Fill>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetSettlementsResponse {
+    pub settlements: Vec<Settlement>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+impl_paginated!(
+    GetFillsResponse => fills: Fill,
+    GetSettlementsResponse => settlements: Settlement,
+);
+impl_cursor_params!(GetPositionsParams, GetFillsParams, GetSettlementsParams);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usd;
+
+    #[test]
+    fn test_balance() {
+        let balance: Balance =
+            serde_json::from_str(include_str!("../fixtures/portfolio/balance.json")).unwrap();
+        assert_eq!(balance.balance_cents(), 48213);
+        assert_eq!(balance.portfolio_value_cents(), 12650);
+    }
+
+    #[test]
+    fn test_positions() {
+        let response: GetPositionsResponse =
+            serde_json::from_str(include_str!("../fixtures/portfolio/positions.json")).unwrap();
+        let [yes, no] = &response.market_positions[..] else {
+            panic!("Expected two positions");
+        };
+        assert_eq!(yes.holding(), Some((Side::Yes, 40)));
+        assert_eq!(*yes.market_exposure(), usd!(8.8));
+        assert_eq!(*yes.realized_pnl(), usd!(1.15));
+        assert_eq!(*yes.fees_paid(), usd!(0.37));
+        assert_eq!(yes.resting_orders_count(), 1);
+        assert_eq!(no.holding(), Some((Side::No, 25)));
+        assert_eq!(*response.event_positions[0].total_cost(), usd!(33.05));
+    }
+
+    #[test]
+    fn test_fills() {
+        let response: GetFillsResponse =
+            serde_json::from_str(include_str!("../fixtures/portfolio/fills.json")).unwrap();
+        let [taker, maker] = &response.fills[..] else {
+            panic!("Expected two fills");
+        };
+        // The price of a NO fill is the NO price
+        assert_eq!(taker.side(), Side::No);
+        assert_eq!(*taker.price(), Price::from_cents(97).unwrap());
+        assert!(taker.is_taker());
+        assert!(taker.client_order_id().unwrap().has_prefix("dump"));
+        assert_eq!(*maker.price(), Price::from_cents(22).unwrap());
+        assert!(!maker.is_taker());
+        assert_eq!(maker.client_order_id(), None);
+        assert_eq!(maker.count(), 4);
+    }
+
+    #[test]
+    fn test_settlements() {
+        let response: GetSettlementsResponse =
+            serde_json::from_str(include_str!("../fixtures/portfolio/settlements.json")).unwrap();
+        let [won, void, empty] = &response.settlements[..] else {
+            panic!("Expected three settlements");
+        };
+        assert_eq!(won.market_result(), MarketResult::Yes);
+        assert_eq!(won.yes_count(), 30);
+        assert_eq!(won.yes_total_cost_cents(), 1260);
+        assert_eq!(won.revenue_cents(), 3000);
+
+        // A voided market pays back what the contracts cost
+        assert_eq!(void.market_result(), MarketResult::Void);
+        assert_eq!(void.no_total_cost_cents(), 1020);
+        assert_eq!(void.revenue_cents(), 1020);
+
+        // An empty result is not read as a void one, the amounts still count
+        assert_eq!(empty.market_result(), MarketResult::Unknown);
+        assert_eq!(empty.yes_total_cost_cents(), 96);
+        assert_eq!(empty.revenue_cents(), 0);
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ticker::SeriesTicker;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementSource {
+    name: String,
+    url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Series {
+    ticker: SeriesTicker,
+    title: String,
+    category: String,
+    frequency: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    settlement_sources: Vec<SettlementSource>,
+}
+
+impl Series {
+    pub fn ticker(&self) -> &SeriesTicker {
+        &self.ticker
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn category(&self) -> &str {
+        &self.category
+    }
+
+    pub fn frequency(&self) -> &str {
+        &self.frequency
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetSeriesListParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Comma separated list of tags
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetSeriesListResponse {
+    pub series: Vec<Series>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeriesResponse {
+    pub series: Series,
+}
@@ -1,14 +1,19 @@
-use anyhow;
+use anyhow::{self, Context, bail};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::{fmt::Display, str::FromStr};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Date part of daily event tickers, e.g. 25OCT17
+const EVENT_DATE_FORMAT: &str = "%y%b%d";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct MarketTicker(String);
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct EventTicker(String);
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct SeriesTicker(String);
 
 macro_rules! impl_from_string {
@@ -46,3 +51,149 @@ macro_rules! impl_from_string {
 }
 
 impl_from_string!(MarketTicker, EventTicker, SeriesTicker);
+
+/// Last part of a market ticker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strike {
+    /// A range bucket named after its midpoint, e.g. B61.5 for 61-62°
+    Between(Decimal),
+    /// An open-ended bucket, e.g. T60. Whether it is above or below is only
+    /// known from the market's strike type.
+    Threshold(Decimal),
+}
+
+impl Display for Strike {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Strike::Between(value) => write!(f, "B{}", value),
+            Strike::Threshold(value) => write!(f, "T{}", value),
+        }
+    }
+}
+
+impl FromStr for Strike {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s.split_at_checked(1).context("Empty strike")?;
+        let value: Decimal = value
+            .parse()
+            .with_context(|| format!("Invalid strike {}", s))?;
+        let strike = match kind {
+            "B" => Strike::Between(value),
+            "T" => Strike::Threshold(value),
+            _ => bail!("Unknown strike kind in {}", s),
+        };
+        if strike.to_string() != s {
+            bail!("Strike {} does not round-trip", s);
+        }
+        Ok(strike)
+    }
+}
+
+impl EventTicker {
+    /// Ticker of a daily event, e.g. KXHIGHNY-25OCT17
+    pub fn new(series: &SeriesTicker, date: NaiveDate) -> Self {
+        let date = date.format(EVENT_DATE_FORMAT).to_string().to_uppercase();
+        Self(format!("{}-{}", series, date))
+    }
+
+    pub fn series(&self) -> SeriesTicker {
+        let series = self.0.split('-').next().unwrap_or_default();
+        SeriesTicker(series.to_string())
+    }
+
+    /// Date of a daily event. Fails for events that are not dated this way.
+    pub fn date(&self) -> anyhow::Result<NaiveDate> {
+        let (_, date) = self
+            .0
+            .split_once('-')
+            .with_context(|| format!("No date in event ticker {}", self))?;
+        let parsed = NaiveDate::parse_from_str(date, EVENT_DATE_FORMAT)
+            .with_context(|| format!("Invalid date in event ticker {}", self))?;
+        if Self::new(&self.series(), parsed) != *self {
+            bail!("Event ticker {} does not round-trip", self);
+        }
+        Ok(parsed)
+    }
+}
+
+impl From<(SeriesTicker, NaiveDate)> for EventTicker {
+    fn from((series, date): (SeriesTicker, NaiveDate)) -> Self {
+        EventTicker::new(&series, date)
+    }
+}
+
+impl TryFrom<&EventTicker> for (SeriesTicker, NaiveDate) {
+    type Error = anyhow::Error;
+
+    fn try_from(ticker: &EventTicker) -> Result<Self, Self::Error> {
+        Ok((ticker.series(), ticker.date()?))
+    }
+}
+
+impl MarketTicker {
+    pub fn new(event: &EventTicker, strike: Strike) -> Self {
+        Self(format!("{}-{}", event, strike))
+    }
+
+    pub fn event(&self) -> EventTicker {
+        let event = self.0.rsplit_once('-').map_or(self.0.as_str(), |(e, _)| e);
+        EventTicker(event.to_string())
+    }
+
+    pub fn series(&self) -> SeriesTicker {
+        self.event().series()
+    }
+
+    pub fn strike(&self) -> anyhow::Result<Strike> {
+        let (_, strike) = self
+            .0
+            .rsplit_once('-')
+            .with_context(|| format!("No strike in market ticker {}", self))?;
+        strike.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_market_ticker_round_trips() {
+        for ticker in ["KXHIGHNY-25OCT17-B61.5", "KXHIGHNY-25OCT17-T60"] {
+            let market: MarketTicker = ticker.parse().unwrap();
+            let event = market.event();
+            assert_eq!(event.to_string(), "KXHIGHNY-25OCT17");
+            assert_eq!(event.series().to_string(), "KXHIGHNY");
+
+            let (series, date) = (&event).try_into().unwrap();
+            assert_eq!(date, NaiveDate::from_ymd_opt(2025, 10, 17).unwrap());
+            let rebuilt = MarketTicker::new(&(series, date).into(), market.strike().unwrap());
+            assert_eq!(rebuilt.to_string(), ticker);
+        }
+    }
+
+    #[test]
+    fn test_strikes() {
+        let strike: Strike = "B61.5".parse().unwrap();
+        assert_eq!(strike, Strike::Between(Decimal::new(615, 1)));
+        assert!("X61".parse::<Strike>().is_err());
+        // Would come back as B61.5
+        assert!("B061.5".parse::<Strike>().is_err());
+    }
+
+    #[test]
+    fn test_event_dates() {
+        let series: SeriesTicker = "KXHIGHNY".parse().unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        let event = EventTicker::new(&series, date);
+        assert_eq!(event.to_string(), "KXHIGHNY-26JAN05");
+        assert_eq!(event.date().unwrap(), date);
+
+        let undated: EventTicker = "KXFEDDECISION-25DEC".parse().unwrap();
+        assert!(undated.date().is_err());
+        let unpadded: EventTicker = "KXHIGHNY-26JAN5".parse().unwrap();
+        assert!(unpadded.date().is_err());
+    }
+}
@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    market::quote,
+    money::Price,
+    order::{Action, ClientOrderId, OrderId, Side},
+    ticker::MarketTicker,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    OrderbookDelta,
+    Ticker,
+    Trade,
+    Fill,
+}
+
+impl Channel {
+    /// Whether subscriptions to this channel are scoped to a set of markets.
+    /// Fills are always delivered for the whole account.
+    pub fn is_market_scoped(&self) -> bool {
+        !matches!(self, Channel::Fill)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateAction {
+    AddMarkets,
+    DeleteMarkets,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "cmd", content = "params", rename_all = "snake_case")]
+pub enum WebsocketCommand {
+    Subscribe {
+        channels: Vec<Channel>,
+        #[serde(skip_serializing_if = "Vec::is_empty", default)]
+        market_tickers: Vec<MarketTicker>,
+    },
+    Unsubscribe {
+        sids: Vec<u64>,
+    },
+    UpdateSubscription {
+        sids: Vec<u64>,
+        market_tickers: Vec<MarketTicker>,
+        action: UpdateAction,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebsocketRequest {
+    pub id: u64,
+    #[serde(flatten)]
+    pub command: WebsocketCommand,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subscribed {
+    pub channel: Channel,
+    pub sid: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebsocketError {
+    pub code: i64,
+    pub msg: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderbookSnapshot {
+    pub market_ticker: MarketTicker,
+    /// Resting YES bids as (price, contracts)
+    #[serde(default)]
+    pub yes_dollars: Vec<(Price, i64)>,
+    /// Resting NO bids as (price, contracts)
+    #[serde(default)]
+    pub no_dollars: Vec<(Price, i64)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderbookDelta {
+    pub market_ticker: MarketTicker,
+    pub price_dollars: Price,
+    /// Change in resting contracts at `price_dollars`, negative when removed
+    pub delta: i64,
+    pub side: Side,
+    #[serde(default)]
+    pub client_order_id: Option<ClientOrderId>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerUpdate {
+    pub market_ticker: MarketTicker,
+    /// Last traded price, `None` before the first trade
+    #[serde(deserialize_with = "quote")]
+    pub price_dollars: Option<Price>,
+    /// Quotes are `None` when nobody is bidding or offering
+    #[serde(deserialize_with = "quote")]
+    pub yes_bid_dollars: Option<Price>,
+    #[serde(deserialize_with = "quote")]
+    pub yes_ask_dollars: Option<Price>,
+    #[serde(default)]
+    pub volume: i64,
+    #[serde(default)]
+    pub open_interest: i64,
+    pub ts: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeUpdate {
+    pub trade_id: String,
+    pub market_ticker: MarketTicker,
+    pub yes_price_dollars: Price,
+    pub no_price_dollars: Price,
+    pub count: i64,
+    pub taker_side: Side,
+    pub ts: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillUpdate {
+    pub trade_id: String,
+    pub order_id: OrderId,
+    #[serde(default)]
+    pub client_order_id: Option<ClientOrderId>,
+    pub market_ticker: MarketTicker,
+    pub is_taker: bool,
+    pub side: Side,
+    pub action: Action,
+    pub yes_price_dollars: Price,
+    pub count: i64,
+    pub ts: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WebsocketMessage {
+    Subscribed {
+        id: Option<u64>,
+        msg: Subscribed,
+    },
+    Unsubscribed {
+        id: Option<u64>,
+        sid: u64,
+    },
+    Ok {
+        id: Option<u64>,
+        sid: Option<u64>,
+    },
+    Error {
+        id: Option<u64>,
+        msg: WebsocketError,
+    },
+    OrderbookSnapshot {
+        sid: u64,
+        seq: u64,
+        msg: OrderbookSnapshot,
+    },
+    OrderbookDelta {
+        sid: u64,
+        seq: u64,
+        msg: OrderbookDelta,
+    },
+    Ticker {
+        sid: u64,
+        msg: TickerUpdate,
+    },
+    Trade {
+        sid: u64,
+        msg: TradeUpdate,
+    },
+    Fill {
+        sid: u64,
+        msg: FillUpdate,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticker_with_empty_bid() {
+        let message: WebsocketMessage = serde_json::from_str(
+            r#"{"type":"ticker","sid":3,"msg":{"market_ticker":"KXHIGHNY-25OCT17-T60","price_dollars":"0.0000","yes_bid_dollars":"0.0000","yes_ask_dollars":"0.0300","volume":12,"open_interest":4,"ts":1760716800}}"#,
+        )
+        .unwrap();
+        let WebsocketMessage::Ticker { msg, .. } = message else {
+            panic!("Not a ticker message: {:?}", message);
+        };
+        assert_eq!(msg.price_dollars, None);
+        assert_eq!(msg.yes_bid_dollars, None);
+        assert_eq!(msg.yes_ask_dollars, Some(Price::from_cents(3).unwrap()));
+    }
+}
@@ -0,0 +1,232 @@
+//! Download the candlesticks and trades of every market of a series into
+//! JSONL files, for backtesting:
+//!
+//! - `{dir}/{series}/candlesticks.jsonl`
+//! - `{dir}/{series}/trades.jsonl`
+//! - `{dir}/{series}/completed.jsonl`: markets fully downloaded, skipped when
+//!   the backfill runs again
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use futures::{StreamExt, TryStreamExt};
+use kalshi_api_spec::{
+    history::{Candlestick, CandlestickInterval, GetCandlesticksParams, GetTradesParams, Trade},
+    market::{GetMarketsParams, Market},
+    ticker::{MarketTicker, SeriesTicker},
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::{markets::MarketsApiClient, store::JsonlStore};
+
+/// Kalshi returns at most this many candlesticks per request
+const MAX_CANDLESTICKS: i64 = 5000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandlestickRecord {
+    pub ticker: MarketTicker,
+    pub interval: CandlestickInterval,
+    #[serde(flatten)]
+    pub candlestick: Candlestick,
+}
+
+impl CandlestickRecord {
+    fn key(&self) -> String {
+        format!(
+            "{}:{}:{}",
+            self.ticker,
+            self.interval.minutes(),
+            self.candlestick.end_period_ts()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CompletedMarket {
+    ticker: MarketTicker,
+    interval: CandlestickInterval,
+    candlesticks: usize,
+    trades: usize,
+}
+
+impl CompletedMarket {
+    fn key_of(ticker: &MarketTicker, interval: CandlestickInterval) -> String {
+        format!("{}:{}", ticker, interval.minutes())
+    }
+
+    fn key(&self) -> String {
+        Self::key_of(&self.ticker, self.interval)
+    }
+}
+
+struct Stores {
+    candlesticks: JsonlStore<CandlestickRecord>,
+    trades: JsonlStore<Trade>,
+    completed: JsonlStore<CompletedMarket>,
+}
+
+impl Stores {
+    async fn open(dir: &Path) -> Result<Self> {
+        Ok(Self {
+            candlesticks: JsonlStore::open(dir.join("candlesticks.jsonl"), CandlestickRecord::key)
+                .await?,
+            trades: JsonlStore::open(dir.join("trades.jsonl"), |trade: &Trade| {
+                trade.trade_id().to_string()
+            })
+            .await?,
+            completed: JsonlStore::open(dir.join("completed.jsonl"), CompletedMarket::key).await?,
+        })
+    }
+}
+
+/// Download every market of `series` whose event date is between `from` and
+/// `to` inclusive, resuming where a previous run stopped
+pub async fn backfill_series(
+    client: &MarketsApiClient,
+    series: &SeriesTicker,
+    from: NaiveDate,
+    to: NaiveDate,
+    interval: CandlestickInterval,
+    dir: &Path,
+) -> Result<()> {
+    let dir = dir.join(series.to_string());
+    let mut stores = Stores::open(&dir).await?;
+
+    // Markets close on or shortly after their event date
+    let params = GetMarketsParams {
+        series_ticker: Some(series.clone()),
+        ..Default::default()
+    }
+    .with_close_time_range(
+        from.and_time(Default::default()).and_utc(),
+        (to + TimeDelta::days(2))
+            .and_time(Default::default())
+            .and_utc(),
+    );
+    let markets: Vec<Market> = client.markets(params).try_collect().await?;
+
+    let now = Utc::now();
+    let mut skipped = 0;
+    for market in &markets {
+        let date = market.event_ticker().date()?;
+        if date < from || date > to {
+            continue;
+        }
+        if market.close_time() > now {
+            eprintln!("Skipping {}, which hasn't closed yet", market.ticker());
+            continue;
+        }
+        if stores
+            .completed
+            .contains(&CompletedMarket::key_of(market.ticker(), interval))
+        {
+            skipped += 1;
+            continue;
+        }
+
+        let completed = backfill_market(client, series, market, interval, &mut stores)
+            .await
+            .with_context(|| format!("Downloading {}", market.ticker()))?;
+        eprintln!(
+            "{}: {} new candlesticks, {} new trades",
+            market.ticker(),
+            completed.candlesticks,
+            completed.trades
+        );
+        stores.completed.append(&completed).await?;
+        stores.completed.flush().await?;
+    }
+
+    eprintln!(
+        "{} markets already downloaded, {} candlesticks and {} trades stored in {}",
+        skipped,
+        stores.candlesticks.len(),
+        stores.trades.len(),
+        dir.display()
+    );
+    Ok(())
+}
+
+async fn backfill_market(
+    client: &MarketsApiClient,
+    series: &SeriesTicker,
+    market: &Market,
+    interval: CandlestickInterval,
+    stores: &mut Stores,
+) -> Result<CompletedMarket> {
+    let ticker = market.ticker();
+    let mut candlesticks = 0;
+    for (start, end) in chunks(market.open_time(), market.close_time(), interval) {
+        let params = GetCandlesticksParams {
+            start_ts: start.timestamp(),
+            end_ts: end.timestamp(),
+            period_interval: interval,
+        };
+        for candlestick in client.get_candlesticks(series, ticker, &params).await? {
+            let record = CandlestickRecord {
+                ticker: ticker.clone(),
+                interval,
+                candlestick,
+            };
+            if stores.candlesticks.append(&record).await? {
+                candlesticks += 1;
+            }
+        }
+    }
+    stores.candlesticks.flush().await?;
+
+    let params = GetTradesParams {
+        ticker: Some(ticker.clone()),
+        min_ts: Some(market.open_time().timestamp()),
+        max_ts: Some(market.close_time().timestamp()),
+        ..Default::default()
+    };
+    let mut trades = 0;
+    let mut stream = std::pin::pin!(client.trades(params));
+    while let Some(trade) = stream.next().await {
+        if stores.trades.append(&trade?).await? {
+            trades += 1;
+        }
+    }
+    stores.trades.flush().await?;
+
+    Ok(CompletedMarket {
+        ticker: ticker.clone(),
+        interval,
+        candlesticks,
+        trades,
+    })
+}
+
+/// Split `[from, to]` into ranges of at most `MAX_CANDLESTICKS` periods
+fn chunks(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    interval: CandlestickInterval,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let step = TimeDelta::minutes(interval.minutes() as i64 * MAX_CANDLESTICKS);
+    let mut chunks = vec![];
+    let mut start = from;
+    while start < to {
+        let end = (start + step).min(to);
+        chunks.push((start, end));
+        start = end;
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_cover_range() {
+        let from = DateTime::from_timestamp(0, 0).unwrap();
+        let to = from + TimeDelta::days(8);
+        let minutes = chunks(from, to, CandlestickInterval::Minute);
+        assert_eq!(minutes.len(), 3);
+        assert_eq!(minutes[0], (from, from + TimeDelta::minutes(5000)));
+        assert_eq!(minutes[2].1, to);
+        assert_eq!(chunks(from, to, CandlestickInterval::Hour).len(), 1);
+    }
+}
@@ -1,73 +1,353 @@
-use anyhow::Result;
+use anyhow::{Context, Error, Result, bail};
 use async_trait::async_trait;
-use chrono::Local;
+use chrono::{DateTime, Utc};
+use rand::Rng;
 use reqwest::{
-    Client, Method, RequestBuilder,
-    header::{CONTENT_TYPE, HeaderMap},
+    Client, Method,
+    header::{CONTENT_TYPE, DATE, HeaderMap},
 };
-use serde::de::DeserializeOwned;
+use serde::{Serialize, de::DeserializeOwned};
+use std::{fmt::Display, str::FromStr, sync::Arc, time::Duration};
+use tokio::time::sleep;
 
-use crate::keys::{ApiKey, PrivateKey};
+use crate::{
+    error::KalshiApiError,
+    keys::{ApiKey, PrivateKey},
+    rate_limit::{RateLimitTier, RateLimiter},
+    signer::Signer,
+};
 
+#[derive(Debug, Clone)]
 pub enum BaseUrl {
     Prod,
     Demo,
+    /// Scheme and host of another server, e.g. `http://127.0.0.1:8080`
+    Custom(String),
 }
 
+const API_PATH: &str = "/trade-api/v2";
+pub(crate) const WEBSOCKET_PATH: &str = "/trade-api/ws/v2";
+
+/// How often the clock offset is measured again by default
+const CLOCK_SYNC_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// The `Date` header only has second precision, so smaller differences
+/// between two measurements are noise rather than skew
+const CLOCK_SKEW_TOLERANCE_MS: i64 = 1_500;
+
 impl BaseUrl {
     fn url(&self) -> String {
         match self {
-            BaseUrl::Prod => "https://api.elections.kalshi.com/trade-api/v2".into(),
-            BaseUrl::Demo => "https://demo-api.kalshi.co/trade-api/v2".into(),
+            BaseUrl::Prod => format!("https://api.elections.kalshi.com{API_PATH}"),
+            BaseUrl::Demo => format!("https://demo-api.kalshi.co{API_PATH}"),
+            BaseUrl::Custom(host) => format!("{}{API_PATH}", host.trim_end_matches('/')),
+        }
+    }
+
+    pub(crate) fn websocket_url(&self) -> String {
+        match self {
+            BaseUrl::Prod => format!("wss://api.elections.kalshi.com{WEBSOCKET_PATH}"),
+            BaseUrl::Demo => format!("wss://demo-api.kalshi.co{WEBSOCKET_PATH}"),
+            BaseUrl::Custom(host) => {
+                let host = host.trim_end_matches('/');
+                let host = match host.split_once("://") {
+                    Some(("https", rest)) => format!("wss://{rest}"),
+                    Some((_, rest)) => format!("ws://{rest}"),
+                    None => format!("ws://{host}"),
+                };
+                format!("{host}{WEBSOCKET_PATH}")
+            }
+        }
+    }
+}
+
+impl Display for BaseUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BaseUrl::Prod => write!(f, "prod"),
+            BaseUrl::Demo => write!(f, "demo"),
+            BaseUrl::Custom(host) => write!(f, "{}", host),
+        }
+    }
+}
+
+/// `prod`, `demo`, or the address of another server such as the mock exchange
+impl FromStr for BaseUrl {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "prod" => Ok(BaseUrl::Prod),
+            "demo" => Ok(BaseUrl::Demo),
+            _ if s.starts_with("http://") || s.starts_with("https://") => {
+                Ok(BaseUrl::Custom(s.to_string()))
+            }
+            _ => bail!("Expected prod, demo or an http(s) URL, got {}", s),
         }
     }
 }
 
+/// How failed GET requests are retried. Other methods are never retried since
+/// they may have taken effect on the exchange.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Exponential backoff with full jitter
+    fn delay(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        ceiling.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+    }
+}
+
+fn is_retryable(error: &Error) -> bool {
+    if let Some(error) = error.downcast_ref::<KalshiApiError>() {
+        return error.is_retryable();
+    }
+    if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+        return error.is_connect() || error.is_timeout();
+    }
+    false
+}
+
+/// Cloning is cheap and clones share the same rate limiter and signer, which
+/// is what we want since limits apply per account and the clock offset per
+/// machine.
+#[derive(Clone)]
 pub struct KalshiApiClient {
     client: Client,
-    api_key: ApiKey,
-    private_key: PrivateKey,
+    signer: Signer,
     base_url: BaseUrl,
+    rate_limiter: Arc<RateLimiter>,
+    retry_policy: RetryPolicy,
+    clock_sync_interval: Option<Duration>,
 }
 
 impl KalshiApiClient {
     pub fn new(api_key: ApiKey, private_key: PrivateKey, base_url: BaseUrl) -> Self {
         let client = Client::new();
         Self {
-            api_key,
-            private_key,
             client,
+            signer: Signer::new(api_key, private_key),
             base_url,
+            rate_limiter: Arc::new(RateLimiter::new(RateLimitTier::default())),
+            retry_policy: RetryPolicy::default(),
+            clock_sync_interval: Some(CLOCK_SYNC_INTERVAL),
         }
     }
 
-    fn headers(&self, method: &Method, path: &str) -> Result<HeaderMap> {
-        let now = Local::now().timestamp_millis();
-        let msg = format!("{now}{method}{path}");
-        let signed_msg = self.private_key.sign(&msg)?;
+    pub fn with_rate_limit_tier(mut self, tier: RateLimitTier) -> Self {
+        self.rate_limiter = Arc::new(RateLimiter::new(tier));
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// How often to measure the clock offset before sending a request. With
+    /// `None` it is only measured when a signature gets refused.
+    pub fn with_clock_sync_interval(mut self, interval: Option<Duration>) -> Self {
+        self.clock_sync_interval = interval;
+        self
+    }
+
+    /// Shared with the websocket client so both sign with the same offset
+    pub fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    /// Measure how far the exchange's clock is ahead of ours from the `Date`
+    /// header of the exchange status endpoint, and sign with it from now on
+    pub async fn sync_clock(&self) -> Result<i64> {
+        self.rate_limiter.acquire(&Method::GET).await;
 
-        let mut headers = HeaderMap::new();
+        let sent_at = Utc::now();
+        let response = self
+            .client
+            .get(format!("{}/exchange/status", self.base_url.url()))
+            .send()
+            .await?;
+        let received_at = Utc::now();
+
+        let date = response
+            .headers()
+            .get(DATE)
+            .context("Exchange status response has no Date header")?
+            .to_str()?;
+        // The header is truncated to the second, assume we're halfway through it
+        let server_ms = DateTime::parse_from_rfc2822(date)
+            .with_context(|| format!("Invalid Date header {}", date))?
+            .timestamp_millis()
+            + 500;
+        let local_ms = sent_at.timestamp_millis() + (received_at - sent_at).num_milliseconds() / 2;
+
+        let offset_ms = server_ms - local_ms;
+        self.signer.set_offset_ms(offset_ms);
+        Ok(offset_ms)
+    }
+
+    async fn sync_clock_if_stale(&self) {
+        let Some(interval) = self.clock_sync_interval else {
+            return;
+        };
+        if self.signer.is_stale(interval)
+            && let Err(e) = self.sync_clock().await
+        {
+            eprintln!("Failed to sync clock with Kalshi: {:?}", e);
+        }
+    }
+
+    /// Tell refused timestamps apart from other authentication failures by
+    /// measuring the clock offset again
+    async fn check_timestamp_skew(&self, error: KalshiApiError) -> KalshiApiError {
+        let KalshiApiError::Unauthorized { message, .. } = &error else {
+            return error;
+        };
+        let previous_ms = self.signer.offset_ms();
+        match self.sync_clock().await {
+            Ok(offset_ms) if (offset_ms - previous_ms).abs() > CLOCK_SKEW_TOLERANCE_MS => {
+                KalshiApiError::TimestampSkew {
+                    offset_ms,
+                    message: message.clone(),
+                }
+            }
+            Ok(_) => error,
+            Err(e) => {
+                eprintln!("Failed to sync clock with Kalshi: {:?}", e);
+                error
+            }
+        }
+    }
+
+    fn headers(&self, method: &Method, path: &str) -> Result<HeaderMap> {
+        let full_path = format!("{API_PATH}{path}");
+        let mut headers = self.signer.headers(method, &full_path)?;
         let _ = headers.insert(CONTENT_TYPE, "application/json".parse()?);
-        let _ = headers.insert("KALSHI-ACCESS-KEY", self.api_key.to_string().parse()?);
-        let _ = headers.insert("KALSHI-ACCESS-SIGNATURE", signed_msg.parse()?);
-        let _ = headers.insert("KALSHI-ACCESS-TIMESTAMP", now.to_string().parse()?);
         Ok(headers)
     }
 
-    fn request(&self, method: Method, path: &str) -> Result<RequestBuilder> {
-        let url = format!("{}{}", self.base_url.url(), path);
-        let headers = self.headers(&method, path)?;
-        let request = self.client.request(method, url).headers(headers);
-        Ok(request)
+    fn request(&self, method: Method, path: &str) -> Result<KalshiRequest<'_>> {
+        Ok(KalshiRequest {
+            client: self,
+            method,
+            path: path.to_string(),
+            query: None,
+            body: None,
+            error: None,
+        })
     }
 
-    pub fn get(&self, path: &str) -> Result<RequestBuilder> {
+    pub fn get(&self, path: &str) -> Result<KalshiRequest<'_>> {
         self.request(Method::GET, path)
     }
 
-    pub fn post(&self, path: &str) -> Result<RequestBuilder> {
+    pub fn post(&self, path: &str) -> Result<KalshiRequest<'_>> {
         self.request(Method::POST, path)
     }
+
+    pub fn delete(&self, path: &str) -> Result<KalshiRequest<'_>> {
+        self.request(Method::DELETE, path)
+    }
+}
+
+/// A request that is signed and rate limited each time it is sent, so that it
+/// can be retried
+pub struct KalshiRequest<'a> {
+    client: &'a KalshiApiClient,
+    method: Method,
+    path: String,
+    query: Option<String>,
+    body: Option<Vec<u8>>,
+    /// Serialization error reported when sending, like reqwest does
+    error: Option<Error>,
+}
+
+impl KalshiRequest<'_> {
+    pub fn query<T: Serialize + ?Sized>(mut self, query: &T) -> Self {
+        match serde_urlencoded::to_string(query) {
+            Ok(query) if query.is_empty() => {}
+            Ok(query) => self.query = Some(query),
+            Err(e) => self.error = Some(e.into()),
+        }
+        self
+    }
+
+    pub fn json<T: Serialize + ?Sized>(mut self, body: &T) -> Self {
+        match serde_json::to_vec(body) {
+            Ok(body) => self.body = Some(body),
+            Err(e) => self.error = Some(e.into()),
+        }
+        self
+    }
+
+    async fn send_once<T: DeserializeOwned>(&self) -> Result<T> {
+        self.client.sync_clock_if_stale().await;
+        self.client.rate_limiter.acquire(&self.method).await;
+
+        let mut url = format!("{}{}", self.client.base_url.url(), self.path);
+        if let Some(query) = &self.query {
+            url = format!("{url}?{query}");
+        }
+        let headers = self.client.headers(&self.method, &self.path)?;
+        let mut request = self
+            .client
+            .client
+            .request(self.method.clone(), url)
+            .headers(headers);
+        if let Some(body) = &self.body {
+            request = request.body(body.clone());
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let headers = response.headers().clone();
+            let body = response.text().await.unwrap_or_default();
+            let error = KalshiApiError::from_response(status, &headers, &body);
+            return Err(self.client.check_timestamp_skew(error).await.into());
+        }
+        Ok(response.json().await?)
+    }
 }
 
 #[async_trait]
@@ -76,9 +356,297 @@ pub trait SafeSend {
 }
 
 #[async_trait]
-impl SafeSend for RequestBuilder {
-    async fn safe_send<T: DeserializeOwned>(self) -> Result<T> {
-        let result: T = self.send().await?.error_for_status()?.json().await?;
-        Ok(result)
+impl SafeSend for KalshiRequest<'_> {
+    async fn safe_send<T: DeserializeOwned>(mut self) -> Result<T> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        let policy = &self.client.retry_policy;
+        let max_retries = if self.method == Method::GET {
+            policy.max_retries
+        } else {
+            0
+        };
+
+        let mut attempt = 0;
+        loop {
+            let error = match self.send_once().await {
+                Ok(result) => return Ok(result),
+                Err(error) if attempt < max_retries && is_retryable(&error) => error,
+                Err(error) => return Err(error),
+            };
+
+            let mut delay = policy.delay(attempt);
+            if let Some(KalshiApiError::RateLimited {
+                retry_after: Some(retry_after),
+                ..
+            }) = error.downcast_ref::<KalshiApiError>()
+            {
+                delay = delay.max(*retry_after);
+            }
+            eprintln!(
+                "{} {} failed, retrying in {:?}: {}",
+                self.method, self.path, delay, error
+            );
+            sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+    use std::sync::Mutex;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    pub(crate) struct StubResponse {
+        status: u16,
+        body: String,
+        date: Option<DateTime<Utc>>,
+    }
+
+    pub(crate) fn reply(status: u16, body: String) -> StubResponse {
+        StubResponse {
+            status,
+            body,
+            date: None,
+        }
+    }
+
+    /// Exchange status response from a server whose clock is `offset` ahead
+    fn status_reply(offset: chrono::TimeDelta) -> StubResponse {
+        StubResponse {
+            date: Some(Utc::now() + offset),
+            ..reply(200, json!({"exchange_active": true}).to_string())
+        }
+    }
+
+    /// Answers each connection with the next scripted response and records
+    /// the requests it received. Shared with the tests of the endpoints.
+    pub(crate) async fn stub_server(
+        responses: Vec<StubResponse>,
+    ) -> (BaseUrl, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(vec![]));
+
+        let received = requests.clone();
+        tokio::spawn(async move {
+            for StubResponse { status, body, date } in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0; 8192];
+                let n = socket.read(&mut buffer).await.unwrap();
+                let request = String::from_utf8_lossy(&buffer[..n]).to_string();
+                received.lock().unwrap().push(request);
+
+                let date = date
+                    .map(|date| format!("date: {}\r\n", date.format("%a, %d %b %Y %H:%M:%S GMT")))
+                    .unwrap_or_default();
+                let response = format!(
+                    "HTTP/1.1 {status} Stub\r\ncontent-type: application/json\r\ncontent-length: {}\r\n{date}connection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        (BaseUrl::Custom(format!("http://{address}")), requests)
+    }
+
+    pub(crate) fn request_line(request: &str) -> &str {
+        request.lines().next().unwrap_or_default()
+    }
+
+    fn timestamp_header(request: &str) -> i64 {
+        request
+            .lines()
+            .find_map(|line| line.strip_prefix("kalshi-access-timestamp: "))
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    pub(crate) fn client(base_url: BaseUrl) -> KalshiApiClient {
+        let private_key = PrivateKey::from_pem(include_str!("../fixtures/test_key.pem")).unwrap();
+        KalshiApiClient::new(ApiKey::new("test-key"), private_key, base_url)
+            .with_retry_policy(RetryPolicy::default().with_base_delay(Duration::from_millis(1)))
+            .with_clock_sync_interval(None)
+    }
+
+    pub(crate) fn error_body(code: &str, message: &str) -> String {
+        json!({"error": {"code": code, "message": message}}).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_get_is_retried_on_server_error() {
+        let (base_url, requests) = stub_server(vec![
+            reply(503, error_body("service_unavailable", "try again")),
+            reply(200, json!({"balance": 1000}).to_string()),
+        ])
+        .await;
+
+        let response: Value = client(base_url)
+            .get("/portfolio/balance")
+            .unwrap()
+            .query(&[("limit", 5)])
+            .safe_send()
+            .await
+            .unwrap();
+        assert_eq!(response["balance"], 1000);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            request_line(&requests[1]),
+            "GET /trade-api/v2/portfolio/balance?limit=5 HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_post_is_not_retried() {
+        let (base_url, requests) = stub_server(vec![reply(
+            503,
+            error_body("service_unavailable", "try again"),
+        )])
+        .await;
+
+        let error = client(base_url)
+            .post("/portfolio/orders")
+            .unwrap()
+            .json(&json!({"ticker": "KXHIGHNY-25OCT17-B61.5"}))
+            .safe_send::<Value>()
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<KalshiApiError>(),
+            Some(KalshiApiError::Server { status: 503, .. })
+        ));
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_order_keeps_error_code() {
+        let (base_url, _) = stub_server(vec![reply(
+            400,
+            error_body("insufficient_balance", "Not enough funds"),
+        )])
+        .await;
+
+        let error = client(base_url)
+            .post("/portfolio/orders")
+            .unwrap()
+            .json(&json!({}))
+            .safe_send::<Value>()
+            .await
+            .unwrap_err();
+
+        let error = error.downcast_ref::<KalshiApiError>().unwrap();
+        assert_eq!(error.code(), Some("insufficient_balance"));
+        assert_eq!(error.message(), "Not enough funds");
+        assert!(!error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_get_gives_up_after_max_retries() {
+        let responses = (0..3)
+            .map(|_| reply(429, error_body("too_many_requests", "slow down")))
+            .collect();
+        let (base_url, requests) = stub_server(responses).await;
+
+        let client = client(base_url).with_retry_policy(
+            RetryPolicy::default()
+                .with_max_retries(2)
+                .with_base_delay(Duration::from_millis(1)),
+        );
+        let error = client
+            .get("/markets")
+            .unwrap()
+            .safe_send::<Value>()
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<KalshiApiError>(),
+            Some(KalshiApiError::RateLimited { .. })
+        ));
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_is_resigned_after_timestamp_skew() {
+        let (base_url, requests) = stub_server(vec![
+            reply(
+                401,
+                error_body("authentication_error", "timestamp too far off"),
+            ),
+            status_reply(chrono::TimeDelta::hours(1)),
+            reply(200, json!({"balance": 1000}).to_string()),
+        ])
+        .await;
+
+        let response: Value = client(base_url)
+            .get("/portfolio/balance")
+            .unwrap()
+            .safe_send()
+            .await
+            .unwrap();
+        assert_eq!(response["balance"], 1000);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            request_line(&requests[1]),
+            "GET /trade-api/v2/exchange/status HTTP/1.1"
+        );
+        let resigned_at = timestamp_header(&requests[2]);
+        let expected = Utc::now().timestamp_millis() + 3_600_000;
+        assert!((resigned_at - expected).abs() < 5_000);
+    }
+
+    #[tokio::test]
+    async fn test_post_reports_timestamp_skew() {
+        let (base_url, _) = stub_server(vec![
+            reply(
+                401,
+                error_body("authentication_error", "timestamp too far off"),
+            ),
+            status_reply(chrono::TimeDelta::minutes(-2)),
+            reply(401, error_body("authentication_error", "invalid signature")),
+            status_reply(chrono::TimeDelta::minutes(-2)),
+        ])
+        .await;
+        let client = client(base_url);
+
+        let error = client
+            .post("/portfolio/orders")
+            .unwrap()
+            .json(&json!({}))
+            .safe_send::<Value>()
+            .await
+            .unwrap_err();
+        let Some(KalshiApiError::TimestampSkew { offset_ms, .. }) = error.downcast_ref() else {
+            panic!("Expected a timestamp skew, got {:?}", error);
+        };
+        assert!((offset_ms + 120_000).abs() < 5_000);
+        assert_eq!(client.signer().offset_ms(), *offset_ms);
+
+        // Once the clock is corrected, refusals are reported as they are
+        let error = client
+            .post("/portfolio/orders")
+            .unwrap()
+            .json(&json!({}))
+            .safe_send::<Value>()
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(KalshiApiError::Unauthorized { .. })
+        ));
     }
 }
@@ -0,0 +1,267 @@
+//! Credentials are read from environment variables, or a `.env` file, so the
+//! same setup works locally and in containers. A profile prefixes every
+//! variable: profile `demo` reads `KALSHI_DEMO_API_KEY` and so on, while no
+//! profile reads `KALSHI_API_KEY`.
+//!
+//! - `API_KEY`: the key id
+//! - `PRIVATE_KEY`: PEM content of the private key, or
+//! - `PRIVATE_KEY_PATH`: path of a PEM file
+//! - `PRIVATE_KEY_PASSPHRASE`: passphrase of an encrypted key
+//! - `BASE_URL`: prod, demo or the URL of a mock exchange, prod by default
+
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use std::{env, path::PathBuf};
+
+use crate::{
+    client::{BaseUrl, KalshiApiClient},
+    keys::{ApiKey, PrivateKey},
+};
+
+pub struct Credentials {
+    profile: Option<String>,
+    api_key: ApiKey,
+    private_key: PrivateKey,
+    base_url: BaseUrl,
+}
+
+impl Credentials {
+    /// Load and validate the credentials of a profile from the environment
+    pub fn load(profile: Option<&str>) -> Result<Self> {
+        Self::load_with(profile, |name| env::var(name).ok())
+    }
+
+    /// Like `load`, reading variables with `var` instead of the environment
+    pub fn load_with(profile: Option<&str>, var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let profile_name = profile.unwrap_or("default");
+        let prefix = match profile {
+            Some(profile) => format!("KALSHI_{}_", profile.to_uppercase().replace('-', "_")),
+            None => "KALSHI_".to_string(),
+        };
+        let var = |name: &str| var(&format!("{prefix}{name}")).filter(|v| !v.trim().is_empty());
+
+        let api_key = var("API_KEY")
+            .map(ApiKey::new)
+            .with_context(|| format!("{prefix}API_KEY is not set for profile {profile_name}"))?;
+
+        let pem = match (var("PRIVATE_KEY"), var("PRIVATE_KEY_PATH")) {
+            (Some(_), Some(_)) => bail!(
+                "Both {prefix}PRIVATE_KEY and {prefix}PRIVATE_KEY_PATH are set for profile {profile_name}"
+            ),
+            // Env files and container secrets often flatten newlines
+            (Some(pem), None) => pem.replace("\\n", "\n"),
+            (None, Some(path)) => std::fs::read_to_string(&path)
+                .with_context(|| format!("Reading private key {}", path))?,
+            (None, None) => bail!(
+                "Neither {prefix}PRIVATE_KEY nor {prefix}PRIVATE_KEY_PATH is set for profile {profile_name}"
+            ),
+        };
+        let passphrase = var("PRIVATE_KEY_PASSPHRASE");
+        let private_key = PrivateKey::from_pem_with_passphrase(&pem, passphrase.as_deref())
+            .with_context(|| format!("Loading private key of profile {profile_name}"))?;
+
+        let base_url = match var("BASE_URL") {
+            Some(base_url) => base_url.parse()?,
+            None => BaseUrl::Prod,
+        };
+
+        let credentials = Self {
+            profile: profile.map(str::to_string),
+            api_key,
+            private_key,
+            base_url,
+        };
+        credentials.validate()?;
+        Ok(credentials)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.api_key.is_empty() {
+            bail!("API key of profile {} is empty", self.profile_name());
+        }
+        self.private_key
+            .validate()
+            .with_context(|| format!("Invalid private key for profile {}", self.profile_name()))
+    }
+
+    pub fn profile_name(&self) -> &str {
+        self.profile.as_deref().unwrap_or("default")
+    }
+
+    pub fn api_key(&self) -> &ApiKey {
+        &self.api_key
+    }
+
+    pub fn private_key(&self) -> &PrivateKey {
+        &self.private_key
+    }
+
+    pub fn base_url(&self) -> &BaseUrl {
+        &self.base_url
+    }
+
+    pub fn client(&self) -> KalshiApiClient {
+        KalshiApiClient::new(
+            self.api_key.clone(),
+            self.private_key.clone(),
+            self.base_url.clone(),
+        )
+    }
+}
+
+/// Command line flags choosing the credentials, to `#[command(flatten)]` into
+/// any binary talking to Kalshi
+#[derive(Args, Debug, Clone)]
+pub struct CredentialArgs {
+    /// Credentials profile, e.g. `demo` reads `KALSHI_DEMO_API_KEY`
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Private key file, instead of the one set for the profile
+    #[arg(long, global = true)]
+    pub private_key: Option<PathBuf>,
+
+    /// prod, demo or the URL of a mock exchange, instead of the one set for
+    /// the profile
+    #[arg(long, global = true)]
+    pub base_url: Option<BaseUrl>,
+}
+
+impl CredentialArgs {
+    pub fn load(&self) -> Result<Credentials> {
+        let key_path = self
+            .private_key
+            .as_ref()
+            .map(|path| path.display().to_string());
+        let mut credentials = Credentials::load_with(self.profile.as_deref(), |name| {
+            // The flag replaces whichever key is set for the profile
+            if let Some(key_path) = &key_path {
+                if name.ends_with("_PRIVATE_KEY_PATH") {
+                    return Some(key_path.clone());
+                }
+                if name.ends_with("_PRIVATE_KEY") {
+                    return None;
+                }
+            }
+            env::var(name).ok()
+        })?;
+        if let Some(base_url) = &self.base_url {
+            credentials.base_url = base_url.clone();
+        }
+        Ok(credentials)
+    }
+
+    pub fn client(&self) -> Result<KalshiApiClient> {
+        Ok(self.load()?.client())
+    }
+
+    /// The flags back as command line arguments, to hand them to a subprocess
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(profile) = &self.profile {
+            args.extend(["--profile".into(), profile.clone()]);
+        }
+        if let Some(private_key) = &self.private_key {
+            args.extend(["--private-key".into(), private_key.display().to_string()]);
+        }
+        if let Some(base_url) = &self.base_url {
+            args.extend(["--base-url".into(), base_url.to_string()]);
+        }
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::collections::HashMap;
+
+    const KEYS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures");
+
+    fn load(profile: Option<&str>, vars: &[(&str, String)]) -> Result<Credentials> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect();
+        Credentials::load_with(profile, |name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_default_profile_reads_key_file() {
+        let credentials = load(
+            None,
+            &[
+                ("KALSHI_API_KEY", "key".to_string()),
+                ("KALSHI_PRIVATE_KEY_PATH", format!("{KEYS}/test_key.pem")),
+            ],
+        )
+        .unwrap();
+        assert_eq!(credentials.api_key().to_string(), "key");
+        assert!(matches!(credentials.base_url(), BaseUrl::Prod));
+    }
+
+    #[test]
+    fn test_named_profile_reads_pem_content() {
+        let pem = std::fs::read_to_string(format!("{KEYS}/test_key_pkcs8.pem")).unwrap();
+        let credentials = load(
+            Some("demo-2"),
+            &[
+                ("KALSHI_DEMO_2_API_KEY", "demo-key".to_string()),
+                ("KALSHI_DEMO_2_PRIVATE_KEY", pem.replace('\n', "\\n")),
+                ("KALSHI_DEMO_2_BASE_URL", "demo".to_string()),
+                ("KALSHI_API_KEY", "prod-key".to_string()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(credentials.profile_name(), "demo-2");
+        assert_eq!(credentials.api_key().to_string(), "demo-key");
+        assert!(matches!(credentials.base_url(), BaseUrl::Demo));
+    }
+
+    #[test]
+    fn test_encrypted_key_needs_passphrase() {
+        let vars = |passphrase: Option<&str>| {
+            let mut vars = vec![
+                ("KALSHI_API_KEY", "key".to_string()),
+                (
+                    "KALSHI_PRIVATE_KEY_PATH",
+                    format!("{KEYS}/test_key_encrypted.pem"),
+                ),
+            ];
+            if let Some(passphrase) = passphrase {
+                vars.push(("KALSHI_PRIVATE_KEY_PASSPHRASE", passphrase.to_string()));
+            }
+            vars
+        };
+
+        assert!(load(None, &vars(Some("hunter2"))).is_ok());
+        let error = load(None, &vars(None)).err().unwrap();
+        assert!(format!("{:#}", error).contains("passphrase is required"));
+        let error = load(None, &vars(Some("wrong"))).err().unwrap();
+        assert!(format!("{:#}", error).contains("passphrase"));
+    }
+
+    #[test]
+    fn test_missing_key_names_the_variables() {
+        let error = load(Some("prod"), &[("KALSHI_PROD_API_KEY", "key".to_string())])
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("KALSHI_PROD_PRIVATE_KEY_PATH"));
+    }
+
+    #[test]
+    fn test_args_round_trip() {
+        #[derive(clap::Parser)]
+        struct Command {
+            #[command(flatten)]
+            credentials: CredentialArgs,
+        }
+
+        let args = ["--profile", "demo", "--base-url", "http://127.0.0.1:8080"];
+        let command = Command::parse_from(std::iter::once("bot").chain(args));
+        assert_eq!(command.credentials.to_args(), args);
+        let command = Command::parse_from(["bot"]);
+        assert!(command.credentials.to_args().is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+use reqwest::{
+    StatusCode,
+    header::{HeaderMap, RETRY_AFTER},
+};
+use serde::Deserialize;
+use std::{fmt::Display, time::Duration};
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: ErrorDetails,
+}
+
+#[derive(Deserialize)]
+struct ErrorDetails {
+    #[serde(default)]
+    code: String,
+    #[serde(default)]
+    message: String,
+}
+
+/// Error returned by the Kalshi REST API. Callers can get it back from an
+/// `anyhow::Error` with `downcast_ref`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KalshiApiError {
+    /// The request was refused, e.g. an order exceeding the available balance
+    Rejected {
+        status: u16,
+        code: String,
+        message: String,
+    },
+    Unauthorized {
+        code: String,
+        message: String,
+    },
+    NotFound {
+        code: String,
+        message: String,
+    },
+    /// The signature was refused because the exchange's clock is `offset_ms`
+    /// ahead of ours. The signer is corrected by the time this is returned, so
+    /// sending the request again should succeed.
+    TimestampSkew {
+        offset_ms: i64,
+        message: String,
+    },
+    /// Too many requests for the account's rate limit tier
+    RateLimited {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    /// The exchange failed to handle the request
+    Server {
+        status: u16,
+        message: String,
+    },
+}
+
+impl KalshiApiError {
+    pub fn from_response(status: StatusCode, headers: &HeaderMap, body: &str) -> Self {
+        let (code, message) = match serde_json::from_str::<ErrorBody>(body) {
+            Ok(body) => (body.error.code, body.error.message),
+            Err(_) => (String::new(), body.trim().to_string()),
+        };
+
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => {
+                let retry_after = headers
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse().ok())
+                    .map(Duration::from_secs);
+                Self::RateLimited {
+                    message,
+                    retry_after,
+                }
+            }
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                Self::Unauthorized { code, message }
+            }
+            StatusCode::NOT_FOUND => Self::NotFound { code, message },
+            status if status.is_server_error() => Self::Server {
+                status: status.as_u16(),
+                message,
+            },
+            status => Self::Rejected {
+                status: status.as_u16(),
+                code,
+                message,
+            },
+        }
+    }
+
+    /// Kalshi's error code, e.g. `insufficient_balance`
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Self::Rejected { code, .. }
+            | Self::Unauthorized { code, .. }
+            | Self::NotFound { code, .. } => Some(code.as_str()).filter(|code| !code.is_empty()),
+            Self::TimestampSkew { .. } | Self::RateLimited { .. } | Self::Server { .. } => None,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Rejected { message, .. }
+            | Self::Unauthorized { message, .. }
+            | Self::NotFound { message, .. }
+            | Self::TimestampSkew { message, .. }
+            | Self::RateLimited { message, .. }
+            | Self::Server { message, .. } => message,
+        }
+    }
+
+    /// Whether sending the same request again later may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::TimestampSkew { .. } | Self::RateLimited { .. } | Self::Server { .. }
+        )
+    }
+}
+
+impl Display for KalshiApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rejected {
+                status,
+                code,
+                message,
+            } => write!(f, "Request rejected ({status} {code}): {message}"),
+            Self::Unauthorized { code, message } => write!(f, "Unauthorized ({code}): {message}"),
+            Self::NotFound { code, message } => write!(f, "Not found ({code}): {message}"),
+            Self::TimestampSkew { offset_ms, message } => write!(
+                f,
+                "Request timestamp refused, exchange clock is {offset_ms}ms ahead of ours: {message}"
+            ),
+            Self::RateLimited { message, .. } => write!(f, "Rate limited: {message}"),
+            Self::Server { status, message } => write!(f, "Server error ({status}): {message}"),
+        }
+    }
+}
+
+impl std::error::Error for KalshiApiError {}
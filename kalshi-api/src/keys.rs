@@ -1,9 +1,9 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use base64::{Engine as _, engine::general_purpose};
 use rand::rngs::OsRng;
 use rsa::Pss;
-use rsa::traits::SignatureScheme;
-use rsa::{RsaPrivateKey, pkcs1::DecodeRsaPrivateKey};
+use rsa::traits::{PublicKeyParts, SignatureScheme};
+use rsa::{RsaPrivateKey, pkcs1::DecodeRsaPrivateKey, pkcs8::DecodePrivateKey};
 use sha2::{Digest, Sha256};
 use std::fmt::Display;
 use std::{env, path::PathBuf};
@@ -14,10 +14,18 @@ use tokio::fs;
 pub struct ApiKey(String);
 
 impl ApiKey {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(key.into())
+    }
+
     pub fn from_env() -> Result<Self> {
         let key = env::var("KALSHI_API_KEY").context("Kalshi API key is not set")?;
         Ok(Self(key))
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.trim().is_empty()
+    }
 }
 
 impl Display for ApiKey {
@@ -32,11 +40,68 @@ pub struct PrivateKey(RsaPrivateKey);
 impl PrivateKey {
     pub async fn from_file(path: PathBuf) -> Result<Self> {
         let content = fs::read_to_string(path).await?;
+        Self::from_pem(&content)
+    }
+
+    /// Key in PKCS#1 (`BEGIN RSA PRIVATE KEY`) or PKCS#8 (`BEGIN PRIVATE KEY`)
+    /// format, the former being what Kalshi hands out
+    pub fn from_pem(content: &str) -> Result<Self> {
+        if is_encrypted(content) {
+            bail!("Private key is encrypted, a passphrase is required");
+        }
+        let private_key = RsaPrivateKey::from_pkcs1_pem(content)
+            .or_else(|_| RsaPrivateKey::from_pkcs8_pem(content))
+            .context("Error decoding private RSA key")?;
+        Ok(Self(private_key))
+    }
+
+    /// Key encrypted with a passphrase, either as PKCS#8 (`BEGIN ENCRYPTED
+    /// PRIVATE KEY`) or as a legacy PKCS#1 PEM with a `Proc-Type` header
+    pub fn from_encrypted_pem(content: &str, passphrase: &str) -> Result<Self> {
+        // The rsa crate can't decrypt keys without a pile of extra crates,
+        // while native-tls already links OpenSSL
+        let key = openssl::pkey::PKey::private_key_from_pem_passphrase(
+            content.as_bytes(),
+            passphrase.as_bytes(),
+        )
+        .context("Error decrypting private key, is the passphrase right?")?;
+        let der = key
+            .private_key_to_pkcs8()
+            .context("Error decoding private key")?;
         let private_key =
-            RsaPrivateKey::from_pkcs1_pem(&content).context("Error decoding private RSA key")?;
+            RsaPrivateKey::from_pkcs8_der(&der).context("Error decoding private RSA key")?;
         Ok(Self(private_key))
     }
 
+    /// Decrypt the key if a passphrase is given, and complain if one is
+    /// needed but missing
+    pub fn from_pem_with_passphrase(content: &str, passphrase: Option<&str>) -> Result<Self> {
+        match passphrase {
+            Some(passphrase) if is_encrypted(content) => {
+                Self::from_encrypted_pem(content, passphrase)
+            }
+            _ => Self::from_pem(content),
+        }
+    }
+
+    /// Sign and verify a message to catch a broken key at startup rather than
+    /// on the first request
+    pub fn validate(&self) -> Result<()> {
+        let bits = self.0.size() * 8;
+        if bits < 2048 {
+            bail!("Private key has {} bits, at least 2048 are required", bits);
+        }
+        self.0.validate().context("Private key is invalid")?;
+
+        let digest = Sha256::digest(b"kalshi key check");
+        let signature = Pss::new_with_salt::<Sha256>(32)
+            .sign(Some(&mut OsRng), &self.0, &digest)
+            .context("Private key can't sign")?;
+        Pss::new::<Sha256>()
+            .verify(&self.0.to_public_key(), &digest, &signature)
+            .context("Private key produces signatures its public key rejects")
+    }
+
     pub fn sign(&self, msg: &str) -> Result<String> {
         let padding = Pss::new_with_salt::<Sha256>(32);
 
@@ -50,3 +115,7 @@ impl PrivateKey {
         Ok(general_purpose::STANDARD.encode(signature))
     }
 }
+
+fn is_encrypted(content: &str) -> bool {
+    content.contains("BEGIN ENCRYPTED PRIVATE KEY") || content.contains("Proc-Type: 4,ENCRYPTED")
+}
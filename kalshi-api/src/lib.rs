@@ -1,3 +1,14 @@
+pub mod backfill;
 pub mod client;
+pub mod credentials;
+pub mod error;
 pub mod keys;
 pub mod markets;
+pub mod orderbook;
+pub mod output;
+pub mod pagination;
+pub mod portfolio;
+pub mod rate_limit;
+pub mod signer;
+pub mod store;
+pub mod websocket;
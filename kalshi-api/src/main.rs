@@ -1,35 +1,566 @@
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Result, bail};
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
+use futures::TryStreamExt;
 use kalshi_api::{
+    backfill::backfill_series,
     client::BaseUrl,
-    keys::{ApiKey, PrivateKey},
+    credentials::CredentialArgs,
     markets::MarketsApiClient,
+    output::{OutputFormat, Table},
+    portfolio::PortfolioApiClient,
+};
+use kalshi_api_spec::{
+    event::{Event, EventResponse, GetEventsParams},
+    fee::{Liquidity, fee},
+    history::CandlestickInterval,
+    market::{GetMarketsParams, Market, StatusFilter},
+    money::{Money, Price},
+    order::{Action, ClientOrderId, CreateOrderRequest, Order, OrderId, Side},
+    portfolio::{GetFillsParams, GetPositionsParams, GetSettlementsParams},
+    ticker::{EventTicker, MarketTicker, SeriesTicker},
+};
+use serde::Serialize;
+use std::{
+    io::{BufRead, Write},
+    path::PathBuf,
 };
-use kalshi_api_spec::{event::EventResponse, ticker::EventTicker};
-use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Cli {
-    #[arg(long)]
-    private_key: PathBuf,
+    #[command(flatten)]
+    credentials: CredentialArgs,
+
+    #[arg(long, global = true, value_enum, default_value_t)]
+    output: OutputFormat,
 
+    #[command(subcommand)]
+    command: Commands,
+}
+
+/// Sending anything to the exchange needs one of these
+#[derive(clap::Args)]
+struct Confirmation {
+    /// Only show what would be sent
     #[arg(long)]
-    event_ticker: EventTicker,
+    dry_run: bool,
+
+    /// Send without asking for confirmation, e.g. from scripts
+    #[arg(long, conflicts_with = "dry_run")]
+    yes: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    Event {
+        #[arg(long, required_unless_present = "series_ticker")]
+        event_ticker: Option<EventTicker>,
+
+        /// Series of a daily event, along with --date
+        #[arg(long, requires = "date", conflicts_with = "event_ticker")]
+        series_ticker: Option<SeriesTicker>,
+
+        #[arg(long)]
+        date: Option<NaiveDate>,
+    },
+    Events {
+        #[arg(long)]
+        series_ticker: Option<SeriesTicker>,
+
+        #[arg(long)]
+        status: Option<StatusFilter>,
+    },
+    Market {
+        #[arg(long)]
+        ticker: MarketTicker,
+    },
+    Markets {
+        #[arg(long)]
+        series_ticker: Option<SeriesTicker>,
+
+        #[arg(long)]
+        event_ticker: Option<EventTicker>,
+
+        #[arg(long)]
+        status: Option<StatusFilter>,
+    },
+    Orderbook {
+        #[arg(long)]
+        ticker: MarketTicker,
+
+        /// Price levels per side
+        #[arg(long)]
+        depth: Option<u32>,
+    },
+    Balance,
+    Positions {
+        #[arg(long)]
+        ticker: Option<MarketTicker>,
+
+        #[arg(long)]
+        event_ticker: Option<EventTicker>,
+    },
+    Fills {
+        #[arg(long)]
+        ticker: Option<MarketTicker>,
+
+        #[arg(long, default_value_t = 100)]
+        limit: u32,
+    },
+    Settlements {
+        #[arg(long)]
+        event_ticker: Option<EventTicker>,
+
+        #[arg(long, default_value_t = 100)]
+        limit: u32,
+    },
+    /// Resting orders
+    Orders {
+        #[arg(long)]
+        ticker: Option<MarketTicker>,
+    },
+    /// Place a limit order, or a market order without --price
+    Place {
+        #[arg(long)]
+        ticker: MarketTicker,
+
+        #[arg(long)]
+        side: Side,
+
+        #[arg(long)]
+        action: Action,
+
+        #[arg(long)]
+        count: u32,
+
+        /// Price of the contract on --side, in dollars
+        #[arg(long)]
+        price: Option<Price>,
+
+        /// Most a market buy may spend in dollars, required by Kalshi
+        #[arg(long, conflicts_with = "price")]
+        max_cost: Option<Money>,
+
+        /// Reuse the id of an earlier attempt so the exchange ignores a
+        /// duplicate
+        #[arg(long)]
+        client_order_id: Option<String>,
+
+        #[command(flatten)]
+        confirmation: Confirmation,
+    },
+    Cancel {
+        #[arg(long)]
+        order_id: OrderId,
+
+        #[command(flatten)]
+        confirmation: Confirmation,
+    },
+    /// Download candlesticks and trades of every market of a series for
+    /// backtesting, resuming an earlier download into the same directory
+    Backfill {
+        #[arg(long)]
+        series_ticker: SeriesTicker,
+
+        /// First event date
+        #[arg(long)]
+        from: NaiveDate,
+
+        /// Last event date
+        #[arg(long)]
+        to: NaiveDate,
+
+        /// Candlestick period in minutes: 1, 60 or 1440
+        #[arg(long, default_value_t = 1)]
+        interval: u32,
+
+        #[arg(long, default_value = "data/history")]
+        dir: PathBuf,
+    },
+}
+
+#[derive(Serialize)]
+struct OrderbookOutput {
+    ticker: MarketTicker,
+    /// Bids as (price, contracts), best first
+    yes: Vec<(Price, i64)>,
+    no: Vec<(Price, i64)>,
+}
+
+fn quote(price: Option<Price>) -> String {
+    price.map(|p| p.to_string()).unwrap_or_default()
+}
+
+fn cents(cents: i64) -> String {
+    Money::from_cents(cents).to_string()
+}
+
+fn markets_table(markets: &[Market]) -> Table {
+    let mut table = Table::new(&[
+        "Ticker", "Yes bid", "Yes ask", "No bid", "No ask", "Closes", "Title",
+    ]);
+    for market in markets {
+        table.push(vec![
+            market.ticker().to_string(),
+            quote(market.yes_bid()),
+            quote(market.yes_ask()),
+            quote(market.no_bid()),
+            quote(market.no_ask()),
+            market.close_time().format("%Y-%m-%d %H:%M").to_string(),
+            market.title().to_string(),
+        ]);
+    }
+    table
+}
+
+fn events_table(events: &[Event]) -> Table {
+    let mut table = Table::new(&["Ticker", "Strike date", "Title"]);
+    for event in events {
+        table.push(vec![
+            event.event_ticker().to_string(),
+            event
+                .strike_date()
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .or_else(|| event.strike_period().map(str::to_string))
+                .unwrap_or_default(),
+            event.title().to_string(),
+        ]);
+    }
+    table
+}
+
+fn orders_table(orders: &[Order]) -> Table {
+    let mut table = Table::new(&[
+        "Order id",
+        "Ticker",
+        "Action",
+        "Side",
+        "Price",
+        "Filled",
+        "Remaining",
+        "Status",
+    ]);
+    for order in orders {
+        table.push(vec![
+            order.order_id().to_string(),
+            order.ticker().to_string(),
+            order.action().to_string(),
+            order.side().to_string(),
+            order.price().to_string(),
+            order.fill_count().to_string(),
+            order.remaining_count().to_string(),
+            order.status().to_string(),
+        ]);
+    }
+    table
+}
+
+fn orderbook_table(book: &OrderbookOutput) -> Table {
+    let mut table = Table::new(&["Yes bid", "Size", "No bid", "Size"]);
+    for i in 0..book.yes.len().max(book.no.len()) {
+        let level = |levels: &[(Price, i64)]| match levels.get(i) {
+            Some((price, count)) => [price.to_string(), count.to_string()],
+            None => Default::default(),
+        };
+        let [yes_price, yes_count] = level(&book.yes);
+        let [no_price, no_count] = level(&book.no);
+        table.push(vec![yes_price, yes_count, no_price, no_count]);
+    }
+    table
+}
+
+/// Ask on the terminal, anything but `yes` declines
+fn confirm(prompt: &str) -> Result<bool> {
+    eprint!("{} Type yes to continue: ", prompt);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("yes"))
+}
+
+/// Whether to go ahead and send, after showing what would be sent
+fn proceed(confirmation: &Confirmation, preview: &str, base_url: &BaseUrl) -> Result<bool> {
+    eprintln!("{}", preview);
+    if confirmation.dry_run {
+        eprintln!("Dry run, nothing was sent to {}", base_url);
+        return Ok(false);
+    }
+    if confirmation.yes || confirm(&format!("This will be sent to {}.", base_url))? {
+        return Ok(true);
+    }
+    eprintln!("Aborted");
+    Ok(false)
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
-    dotenvy::dotenv()?;
-
-    let api_key = ApiKey::from_env()?;
-    let private_key = PrivateKey::from_file(cli.private_key).await?;
-    let client = MarketsApiClient::new(api_key, private_key, BaseUrl::Prod);
-    let response: EventResponse = client.get_event(&cli.event_ticker).await?;
-    let as_json = serde_json::to_string_pretty(&response)?;
-    eprintln!("Response:");
-    println!("\n{}", as_json);
+    // Containers pass credentials through the environment without a .env file
+    let _ = dotenvy::dotenv();
+
+    let credentials = cli.credentials.load()?;
+    let base_url = credentials.base_url().clone();
+    eprintln!(
+        "Using profile {} on {}",
+        credentials.profile_name(),
+        base_url
+    );
+    // Both clients share the rate limiter since limits apply per account
+    let client = credentials.client();
+    let markets = MarketsApiClient::from(client.clone());
+    let portfolio = PortfolioApiClient::from(client);
+    let output = cli.output;
+
+    match cli.command {
+        Commands::Event {
+            event_ticker,
+            series_ticker,
+            date,
+        } => {
+            let event_ticker = match (event_ticker, series_ticker, date) {
+                (Some(event_ticker), _, _) => event_ticker,
+                (None, Some(series_ticker), Some(date)) => EventTicker::new(&series_ticker, date),
+                _ => bail!("Either --event-ticker or --series-ticker and --date are required"),
+            };
+            let response: EventResponse = markets.get_event(&event_ticker).await?;
+            output.print(&response, |response| markets_table(response.markets()))?
+        }
+        Commands::Events {
+            series_ticker,
+            status,
+        } => {
+            let params = GetEventsParams {
+                series_ticker,
+                status,
+                ..Default::default()
+            };
+            let events: Vec<Event> = markets.events(params).try_collect().await?;
+            output.print(&events, |events| events_table(events))?
+        }
+        Commands::Market { ticker } => {
+            let response = markets.get_market(&ticker).await?;
+            output.print(&response, |response| {
+                markets_table(std::slice::from_ref(response.market()))
+            })?
+        }
+        Commands::Markets {
+            series_ticker,
+            event_ticker,
+            status,
+        } => {
+            let params = GetMarketsParams {
+                series_ticker,
+                event_ticker,
+                status,
+                ..Default::default()
+            };
+            let response: Vec<Market> = markets.markets(params).try_collect().await?;
+            output.print(&response, |markets| markets_table(markets))?
+        }
+        Commands::Orderbook { ticker, depth } => {
+            let book = markets.get_orderbook(&ticker, depth).await?;
+            let book = OrderbookOutput {
+                ticker,
+                yes: book.bids(Side::Yes),
+                no: book.bids(Side::No),
+            };
+            output.print(&book, orderbook_table)?
+        }
+        Commands::Balance => {
+            let balance = portfolio.get_balance().await?;
+            output.print(&balance, |balance| {
+                let mut table = Table::new(&["Balance", "Portfolio value"]);
+                table.push(vec![
+                    cents(balance.balance_cents()),
+                    cents(balance.portfolio_value_cents()),
+                ]);
+                table
+            })?
+        }
+        Commands::Positions {
+            ticker,
+            event_ticker,
+        } => {
+            let params = GetPositionsParams {
+                ticker,
+                event_ticker,
+                ..Default::default()
+            };
+            let positions = portfolio.get_positions(&params).await?;
+            output.print(&positions, |positions| {
+                let mut table = Table::new(&[
+                    "Ticker",
+                    "Position",
+                    "Exposure",
+                    "Realized PnL",
+                    "Fees",
+                    "Resting orders",
+                ]);
+                for position in &positions.market_positions {
+                    let holding = match position.holding() {
+                        Some((side, count)) => format!("{} {}", count, side),
+                        None => "-".to_string(),
+                    };
+                    table.push(vec![
+                        position.ticker().to_string(),
+                        holding,
+                        position.market_exposure().to_string(),
+                        position.realized_pnl().to_string(),
+                        position.fees_paid().to_string(),
+                        position.resting_orders_count().to_string(),
+                    ]);
+                }
+                table
+            })?
+        }
+        Commands::Fills { ticker, limit } => {
+            let params = GetFillsParams {
+                ticker,
+                limit: Some(limit),
+                ..Default::default()
+            };
+            let fills = portfolio.get_fills(&params).await?;
+            output.print(&fills, |fills| {
+                let mut table = Table::new(&[
+                    "Time", "Ticker", "Action", "Side", "Count", "Price", "Taker",
+                ]);
+                for fill in &fills.fills {
+                    table.push(vec![
+                        fill.created_time().format("%Y-%m-%d %H:%M:%S").to_string(),
+                        fill.ticker().to_string(),
+                        fill.action().to_string(),
+                        fill.side().to_string(),
+                        fill.count().to_string(),
+                        fill.price().to_string(),
+                        fill.is_taker().to_string(),
+                    ]);
+                }
+                table
+            })?
+        }
+        Commands::Settlements {
+            event_ticker,
+            limit,
+        } => {
+            let params = GetSettlementsParams {
+                event_ticker,
+                limit: Some(limit),
+                ..Default::default()
+            };
+            let settlements = portfolio.get_settlements(&params).await?;
+            output.print(&settlements, |settlements| {
+                let mut table =
+                    Table::new(&["Settled", "Ticker", "Result", "Yes", "No", "Revenue"]);
+                for settlement in &settlements.settlements {
+                    table.push(vec![
+                        settlement.settled_time().format("%Y-%m-%d").to_string(),
+                        settlement.ticker().to_string(),
+                        format!("{:?}", settlement.market_result()).to_lowercase(),
+                        settlement.yes_count().to_string(),
+                        settlement.no_count().to_string(),
+                        cents(settlement.revenue_cents()),
+                    ]);
+                }
+                table
+            })?
+        }
+        Commands::Orders { ticker } => {
+            let orders = portfolio.open_orders(ticker.as_ref()).await?;
+            output.print(&orders, |orders| orders_table(orders))?
+        }
+        Commands::Place {
+            ticker,
+            side,
+            action,
+            count,
+            price,
+            max_cost,
+            client_order_id,
+            confirmation,
+        } => {
+            let (request, preview) = match price {
+                Some(price) => {
+                    let fee = fee(Liquidity::Taker, count, price);
+                    let preview = format!(
+                        "{} {} {} {} at {}: {} plus up to {} in fees",
+                        action,
+                        count,
+                        side,
+                        ticker,
+                        price,
+                        price.cost(count),
+                        fee
+                    );
+                    let request = CreateOrderRequest::limit(ticker, side, action, count, price);
+                    (request, preview)
+                }
+                None => {
+                    let mut request =
+                        CreateOrderRequest::market(ticker.clone(), side, action, count);
+                    let mut preview = format!("{} {} {} {} at market", action, count, side, ticker);
+                    match (action, max_cost) {
+                        (Action::Buy, Some(max_cost)) => {
+                            request = request.with_buy_max_cost(max_cost.cents());
+                            preview = format!("{} spending at most {}", preview, max_cost);
+                        }
+                        (Action::Buy, None) => bail!("Market buys need --max-cost"),
+                        (Action::Sell, _) => {}
+                    }
+                    (request, preview)
+                }
+            };
+            let request = match client_order_id {
+                Some(id) => request.with_client_order_id(ClientOrderId::from(id)),
+                None => request,
+            };
+            let preview = format!(
+                "{} (client order id {})",
+                preview,
+                request.client_order_id()
+            );
+
+            if proceed(&confirmation, &preview, &base_url)? {
+                let order = portfolio.create_order(&request).await?;
+                output.print(&order, |order| orders_table(std::slice::from_ref(order)))?
+            }
+        }
+        Commands::Cancel {
+            order_id,
+            confirmation,
+        } => {
+            let order = portfolio.get_order(&order_id).await?;
+            let preview = format!(
+                "Cancel {} {} {} {} at {}, {} of {} still resting",
+                order.action(),
+                order.initial_count(),
+                order.side(),
+                order.ticker(),
+                order.price(),
+                order.remaining_count(),
+                order.initial_count()
+            );
+            if proceed(&confirmation, &preview, &base_url)? {
+                let response = portfolio.cancel_order(&order_id).await?;
+                output.print(&response, |response| {
+                    orders_table(std::slice::from_ref(&response.order))
+                })?
+            }
+        }
+        Commands::Backfill {
+            series_ticker,
+            from,
+            to,
+            interval,
+            dir,
+        } => {
+            if from > to {
+                bail!("--from {} is after --to {}", from, to);
+            }
+            let interval = CandlestickInterval::try_from(interval).map_err(anyhow::Error::msg)?;
+            backfill_series(&markets, &series_ticker, from, to, interval, &dir).await?
+        }
+    }
 
     Ok(())
 }
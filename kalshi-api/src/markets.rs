@@ -1,17 +1,31 @@
 use anyhow::Result;
+use futures::Stream;
 use kalshi_api_spec::{
-    event::EventResponse,
-    market::MarketResponse,
-    ticker::{EventTicker, MarketTicker},
+    event::{Event, EventResponse, GetEventsParams, GetEventsResponse},
+    history::{
+        Candlestick, GetCandlesticksParams, GetCandlesticksResponse, GetTradesParams,
+        GetTradesResponse, Trade,
+    },
+    market::{GetMarketsParams, GetMarketsResponse, Market, MarketResponse},
+    orderbook::{GetOrderbookResponse, OrderBook},
+    series::{GetSeriesListParams, GetSeriesListResponse, Series, SeriesResponse},
+    ticker::{EventTicker, MarketTicker, SeriesTicker},
 };
 
 use crate::{
     client::{BaseUrl, KalshiApiClient, SafeSend},
     keys::{ApiKey, PrivateKey},
+    pagination::paginate,
 };
 
 pub struct MarketsApiClient(KalshiApiClient);
 
+impl From<KalshiApiClient> for MarketsApiClient {
+    fn from(client: KalshiApiClient) -> Self {
+        Self(client)
+    }
+}
+
 impl MarketsApiClient {
     pub fn new(api_key: ApiKey, private_key: PrivateKey, base_url: BaseUrl) -> Self {
         let client = KalshiApiClient::new(api_key, private_key, base_url);
@@ -25,4 +39,80 @@ impl MarketsApiClient {
     pub async fn get_market(&self, ticker: &MarketTicker) -> Result<MarketResponse> {
         self.0.get(&format!("/markets/{ticker}"))?.safe_send().await
     }
+
+    /// Resting bids of a market, `depth` price levels per side at most
+    pub async fn get_orderbook(
+        &self,
+        ticker: &MarketTicker,
+        depth: Option<u32>,
+    ) -> Result<OrderBook> {
+        let response: GetOrderbookResponse = self
+            .0
+            .get(&format!("/markets/{ticker}/orderbook"))?
+            .query(&[("depth", depth)])
+            .safe_send()
+            .await?;
+        Ok(response.book(ticker.clone()))
+    }
+
+    pub async fn get_events(&self, params: &GetEventsParams) -> Result<GetEventsResponse> {
+        self.0.get("/events")?.query(params).safe_send().await
+    }
+
+    /// Every event matching `params`, fetching pages lazily
+    pub fn events(&self, params: GetEventsParams) -> impl Stream<Item = Result<Event>> + '_ {
+        paginate::<_, GetEventsResponse>(&self.0, "/events", params)
+    }
+
+    pub async fn get_markets(&self, params: &GetMarketsParams) -> Result<GetMarketsResponse> {
+        self.0.get("/markets")?.query(params).safe_send().await
+    }
+
+    /// Every market matching `params`, fetching pages lazily
+    pub fn markets(&self, params: GetMarketsParams) -> impl Stream<Item = Result<Market>> + '_ {
+        paginate::<_, GetMarketsResponse>(&self.0, "/markets", params)
+    }
+
+    pub async fn get_series(&self, ticker: &SeriesTicker) -> Result<Series> {
+        let response: SeriesResponse = self
+            .0
+            .get(&format!("/series/{ticker}"))?
+            .safe_send()
+            .await?;
+        Ok(response.series)
+    }
+
+    pub async fn get_series_list(&self, params: &GetSeriesListParams) -> Result<Vec<Series>> {
+        let response: GetSeriesListResponse =
+            self.0.get("/series")?.query(params).safe_send().await?;
+        Ok(response.series)
+    }
+
+    pub async fn get_candlesticks(
+        &self,
+        series: &SeriesTicker,
+        ticker: &MarketTicker,
+        params: &GetCandlesticksParams,
+    ) -> Result<Vec<Candlestick>> {
+        let response: GetCandlesticksResponse = self
+            .0
+            .get(&format!("/series/{series}/markets/{ticker}/candlesticks"))?
+            .query(params)
+            .safe_send()
+            .await?;
+        Ok(response.candlesticks)
+    }
+
+    pub async fn get_trades(&self, params: &GetTradesParams) -> Result<GetTradesResponse> {
+        self.0
+            .get("/markets/trades")?
+            .query(params)
+            .safe_send()
+            .await
+    }
+
+    /// Every public trade matching `params`, newest first, fetching pages lazily
+    pub fn trades(&self, params: GetTradesParams) -> impl Stream<Item = Result<Trade>> + '_ {
+        paginate::<_, GetTradesResponse>(&self.0, "/markets/trades", params)
+    }
 }
@@ -0,0 +1,54 @@
+use anyhow::Result;
+use kalshi_api_spec::{
+    orderbook::{OrderBook, OrderBooks},
+    ticker::MarketTicker,
+    websocket::Channel,
+};
+
+use crate::websocket::{KalshiWebsocketClient, WebsocketEvent};
+
+/// Keeps `OrderBooks` in sync with a websocket connection, asking for a new
+/// snapshot whenever a sequence gap is detected.
+pub struct OrderBookFeed {
+    client: KalshiWebsocketClient,
+    books: OrderBooks,
+}
+
+impl OrderBookFeed {
+    pub fn new(client: KalshiWebsocketClient) -> Self {
+        Self {
+            client,
+            books: OrderBooks::default(),
+        }
+    }
+
+    pub fn subscribe(&self, tickers: &[MarketTicker]) -> Result<()> {
+        self.client.subscribe(Channel::OrderbookDelta, tickers)
+    }
+
+    pub fn unsubscribe(&self, tickers: &[MarketTicker]) -> Result<()> {
+        self.client.unsubscribe(Channel::OrderbookDelta, tickers)
+    }
+
+    pub fn handle(&mut self, event: &WebsocketEvent) -> Result<()> {
+        match event {
+            WebsocketEvent::Connected => self.books.clear(),
+            WebsocketEvent::Message(message) => {
+                if let Err(gap) = self.books.apply(message) {
+                    eprintln!("{}, requesting a new snapshot", gap);
+                    self.client
+                        .resubscribe(Channel::OrderbookDelta, &gap.tickers)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn book(&self, ticker: &MarketTicker) -> Option<&OrderBook> {
+        self.books.get(ticker)
+    }
+
+    pub fn books(&self) -> &OrderBooks {
+        &self.books
+    }
+}
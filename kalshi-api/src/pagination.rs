@@ -0,0 +1,128 @@
+use anyhow::Result;
+use async_stream::try_stream;
+use futures::Stream;
+use kalshi_api_spec::pagination::{CursorParams, Paginated};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::client::{KalshiApiClient, SafeSend};
+
+/// Stream every item of a list endpoint. Pages are only fetched once the
+/// previous one has been consumed.
+pub fn paginate<'a, P, R>(
+    client: &'a KalshiApiClient,
+    path: &'a str,
+    mut params: P,
+) -> impl Stream<Item = Result<R::Item>> + 'a
+where
+    P: CursorParams + Serialize + Send + 'a,
+    R: Paginated + DeserializeOwned + 'a,
+{
+    try_stream! {
+        loop {
+            let page: R = client.get(path)?.query(&params).safe_send().await?;
+            let (items, cursor) = page.into_page();
+            for item in items {
+                yield item;
+            }
+            match cursor {
+                Some(cursor) => params.set_cursor(cursor),
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::tests::{client, error_body, reply, request_line, stub_server};
+    use futures::StreamExt;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Default, Serialize)]
+    struct Params {
+        cursor: Option<String>,
+    }
+
+    impl CursorParams for Params {
+        fn set_cursor(&mut self, cursor: String) {
+            self.cursor = Some(cursor);
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Page {
+        items: Vec<u32>,
+        cursor: Option<String>,
+    }
+
+    impl Paginated for Page {
+        type Item = u32;
+
+        fn into_page(self) -> (Vec<u32>, Option<String>) {
+            (self.items, self.cursor.filter(|cursor| !cursor.is_empty()))
+        }
+    }
+
+    fn page(items: &[u32], cursor: &str) -> String {
+        json!({"items": items, "cursor": cursor}).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_walks_every_page() {
+        let (base_url, requests) = stub_server(vec![
+            reply(200, page(&[1, 2], "next")),
+            reply(200, page(&[], "last")),
+            reply(200, page(&[3], "")),
+        ])
+        .await;
+        let client = client(base_url);
+
+        let items: Vec<u32> = paginate::<_, Page>(&client, "/items", Params::default())
+            .map(|item| item.unwrap())
+            .collect()
+            .await;
+        assert_eq!(items, vec![1, 2, 3]);
+
+        let requests = requests.lock().unwrap();
+        let lines: Vec<&str> = requests.iter().map(|r| request_line(r)).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "GET /trade-api/v2/items HTTP/1.1",
+                "GET /trade-api/v2/items?cursor=next HTTP/1.1",
+                "GET /trade-api/v2/items?cursor=last HTTP/1.1",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_cursor_ends_the_walk() {
+        let (base_url, requests) = stub_server(vec![reply(200, page(&[], ""))]).await;
+        let client = client(base_url);
+
+        let items: Vec<Result<u32>> = paginate::<_, Page>(&client, "/items", Params::default())
+            .collect()
+            .await;
+        assert!(items.is_empty());
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_error_on_a_later_page() {
+        let (base_url, _) = stub_server(vec![
+            reply(200, page(&[1], "next")),
+            reply(400, error_body("bad_request", "invalid cursor")),
+        ])
+        .await;
+        let client = client(base_url);
+
+        let items: Vec<Result<u32>> = paginate::<_, Page>(&client, "/items", Params::default())
+            .collect()
+            .await;
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].as_ref().unwrap(), &1);
+        assert!(items[1].is_err());
+    }
+}
@@ -0,0 +1,336 @@
+use anyhow::Result;
+use futures::{Stream, TryStreamExt};
+use kalshi_api_spec::{
+    order::{
+        AmendOrderRequest, AmendOrderResponse, CancelOrderResponse, CreateOrderRequest,
+        DecreaseOrderRequest, GetOrdersParams, GetOrdersResponse, Order, OrderId, OrderResponse,
+        OrderStatus,
+    },
+    portfolio::{
+        Balance, Fill, GetFillsParams, GetFillsResponse, GetPositionsParams, GetPositionsResponse,
+        GetSettlementsParams, GetSettlementsResponse, Settlement,
+    },
+    ticker::MarketTicker,
+};
+
+use crate::{
+    client::{BaseUrl, KalshiApiClient, SafeSend},
+    keys::{ApiKey, PrivateKey},
+    pagination::paginate,
+};
+
+pub struct PortfolioApiClient(KalshiApiClient);
+
+impl From<KalshiApiClient> for PortfolioApiClient {
+    fn from(client: KalshiApiClient) -> Self {
+        Self(client)
+    }
+}
+
+impl PortfolioApiClient {
+    pub fn new(api_key: ApiKey, private_key: PrivateKey, base_url: BaseUrl) -> Self {
+        let client = KalshiApiClient::new(api_key, private_key, base_url);
+        Self(client)
+    }
+
+    pub async fn create_order(&self, request: &CreateOrderRequest) -> Result<Order> {
+        let response: OrderResponse = self
+            .0
+            .post("/portfolio/orders")?
+            .json(request)
+            .safe_send()
+            .await?;
+        Ok(response.order)
+    }
+
+    pub async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+        let response: OrderResponse = self
+            .0
+            .get(&format!("/portfolio/orders/{order_id}"))?
+            .safe_send()
+            .await?;
+        Ok(response.order)
+    }
+
+    pub async fn amend_order(
+        &self,
+        order_id: &OrderId,
+        request: &AmendOrderRequest,
+    ) -> Result<AmendOrderResponse> {
+        self.0
+            .post(&format!("/portfolio/orders/{order_id}/amend"))?
+            .json(request)
+            .safe_send()
+            .await
+    }
+
+    pub async fn decrease_order(
+        &self,
+        order_id: &OrderId,
+        request: &DecreaseOrderRequest,
+    ) -> Result<Order> {
+        let response: OrderResponse = self
+            .0
+            .post(&format!("/portfolio/orders/{order_id}/decrease"))?
+            .json(request)
+            .safe_send()
+            .await?;
+        Ok(response.order)
+    }
+
+    pub async fn cancel_order(&self, order_id: &OrderId) -> Result<CancelOrderResponse> {
+        self.0
+            .delete(&format!("/portfolio/orders/{order_id}"))?
+            .safe_send()
+            .await
+    }
+
+    pub async fn get_orders(&self, params: &GetOrdersParams) -> Result<GetOrdersResponse> {
+        self.0
+            .get("/portfolio/orders")?
+            .query(params)
+            .safe_send()
+            .await
+    }
+
+    /// Every order matching `params`, fetching pages lazily
+    pub fn orders(&self, params: GetOrdersParams) -> impl Stream<Item = Result<Order>> + '_ {
+        paginate::<_, GetOrdersResponse>(&self.0, "/portfolio/orders", params)
+    }
+
+    /// All resting orders, optionally only for one market
+    pub async fn open_orders(&self, ticker: Option<&MarketTicker>) -> Result<Vec<Order>> {
+        let params = GetOrdersParams {
+            ticker: ticker.cloned(),
+            status: Some(OrderStatus::Resting),
+            ..Default::default()
+        };
+        self.orders(params).try_collect().await
+    }
+
+    pub async fn get_balance(&self) -> Result<Balance> {
+        self.0.get("/portfolio/balance")?.safe_send().await
+    }
+
+    pub async fn get_positions(&self, params: &GetPositionsParams) -> Result<GetPositionsResponse> {
+        self.0
+            .get("/portfolio/positions")?
+            .query(params)
+            .safe_send()
+            .await
+    }
+
+    pub async fn get_fills(&self, params: &GetFillsParams) -> Result<GetFillsResponse> {
+        self.0
+            .get("/portfolio/fills")?
+            .query(params)
+            .safe_send()
+            .await
+    }
+
+    /// Every fill matching `params`, fetching pages lazily
+    pub fn fills(&self, params: GetFillsParams) -> impl Stream<Item = Result<Fill>> + '_ {
+        paginate::<_, GetFillsResponse>(&self.0, "/portfolio/fills", params)
+    }
+
+    pub async fn get_settlements(
+        &self,
+        params: &GetSettlementsParams,
+    ) -> Result<GetSettlementsResponse> {
+        self.0
+            .get("/portfolio/settlements")?
+            .query(params)
+            .safe_send()
+            .await
+    }
+
+    /// Every settlement matching `params`, fetching pages lazily
+    pub fn settlements(
+        &self,
+        params: GetSettlementsParams,
+    ) -> impl Stream<Item = Result<Settlement>> + '_ {
+        paginate::<_, GetSettlementsResponse>(&self.0, "/portfolio/settlements", params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::tests::{client, reply, request_line, stub_server};
+    use kalshi_api_spec::{
+        money::Price,
+        order::{Action, Side},
+    };
+    use serde_json::{Value, json};
+
+    const ORDERS_PAGE: &str = include_str!("../../kalshi-api-spec/fixtures/order/orders_page.json");
+
+    fn orders_page() -> Value {
+        serde_json::from_str(ORDERS_PAGE).unwrap()
+    }
+
+    fn order_response() -> String {
+        json!({"order": orders_page()["orders"][0]}).to_string()
+    }
+
+    fn request_body(request: &str) -> Value {
+        let (_, body) = request.split_once("\r\n\r\n").unwrap();
+        serde_json::from_str(body).unwrap()
+    }
+
+    fn portfolio(base_url: BaseUrl) -> PortfolioApiClient {
+        PortfolioApiClient::from(client(base_url))
+    }
+
+    #[tokio::test]
+    async fn test_create_order() {
+        let (base_url, requests) = stub_server(vec![reply(200, order_response())]).await;
+        let request = CreateOrderRequest::limit(
+            "KXHIGHNY-25OCT17-B61.5".parse().unwrap(),
+            Side::Yes,
+            Action::Buy,
+            10,
+            Price::from_cents(22).unwrap(),
+        );
+
+        let order = portfolio(base_url).create_order(&request).await.unwrap();
+        assert_eq!(order.status(), OrderStatus::Resting);
+        assert_eq!(order.remaining_count(), 6);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            request_line(&requests[0]),
+            "POST /trade-api/v2/portfolio/orders HTTP/1.1"
+        );
+        let body = request_body(&requests[0]);
+        assert_eq!(body["client_order_id"], json!(request.client_order_id()));
+        assert_eq!(body["yes_price_dollars"], "0.22");
+        assert_eq!(body["type"], "limit");
+    }
+
+    #[tokio::test]
+    async fn test_amend_decrease_and_cancel() {
+        let (base_url, requests) = stub_server(vec![
+            reply(
+                200,
+                include_str!("../../kalshi-api-spec/fixtures/order/amend_response.json").into(),
+            ),
+            reply(200, order_response()),
+            reply(
+                200,
+                include_str!("../../kalshi-api-spec/fixtures/order/cancel_response.json").into(),
+            ),
+        ])
+        .await;
+        let portfolio = portfolio(base_url);
+        let order: Order = serde_json::from_value(orders_page()["orders"][0].clone()).unwrap();
+        let order_id = order.order_id();
+
+        let request = AmendOrderRequest::new(&order, 12, Price::from_cents(24).unwrap());
+        let amended = portfolio.amend_order(order_id, &request).await.unwrap();
+        assert_eq!(amended.order.remaining_count(), 8);
+        portfolio
+            .decrease_order(order_id, &DecreaseOrderRequest::ReduceTo(2))
+            .await
+            .unwrap();
+        let canceled = portfolio.cancel_order(order_id).await.unwrap();
+        assert_eq!(canceled.reduced_by, 8);
+
+        let requests = requests.lock().unwrap();
+        let lines: Vec<&str> = requests.iter().map(|r| request_line(r)).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "POST /trade-api/v2/portfolio/orders/0f5e7ed4-1c4a-4be5-9d7e-7a0c5a3c2e11/amend HTTP/1.1",
+                "POST /trade-api/v2/portfolio/orders/0f5e7ed4-1c4a-4be5-9d7e-7a0c5a3c2e11/decrease HTTP/1.1",
+                "DELETE /trade-api/v2/portfolio/orders/0f5e7ed4-1c4a-4be5-9d7e-7a0c5a3c2e11 HTTP/1.1",
+            ]
+        );
+        assert_eq!(request_body(&requests[0])["count"], 12);
+        assert_eq!(request_body(&requests[1]), json!({"reduce_to": 2}));
+    }
+
+    #[tokio::test]
+    async fn test_open_orders_walk_every_page() {
+        let mut last_page = orders_page();
+        last_page["cursor"] = json!("");
+        let (base_url, requests) = stub_server(vec![
+            reply(200, ORDERS_PAGE.into()),
+            reply(200, last_page.to_string()),
+        ])
+        .await;
+
+        let ticker: MarketTicker = "KXHIGHNY-25OCT17-B61.5".parse().unwrap();
+        let orders = portfolio(base_url)
+            .open_orders(Some(&ticker))
+            .await
+            .unwrap();
+        assert_eq!(orders.len(), 4);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            request_line(&requests[0]),
+            "GET /trade-api/v2/portfolio/orders?ticker=KXHIGHNY-25OCT17-B61.5&status=resting HTTP/1.1"
+        );
+        assert_eq!(
+            request_line(&requests[1]),
+            "GET /trade-api/v2/portfolio/orders?ticker=KXHIGHNY-25OCT17-B61.5&status=resting&cursor=CgwI-pm8xwYQ4KWgjQMSJDBmNWU3ZWQ0 HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_account_endpoints() {
+        let (base_url, requests) = stub_server(vec![
+            reply(
+                200,
+                include_str!("../../kalshi-api-spec/fixtures/portfolio/balance.json").into(),
+            ),
+            reply(
+                200,
+                include_str!("../../kalshi-api-spec/fixtures/portfolio/positions.json").into(),
+            ),
+            reply(
+                200,
+                include_str!("../../kalshi-api-spec/fixtures/portfolio/fills.json").into(),
+            ),
+            reply(
+                200,
+                include_str!("../../kalshi-api-spec/fixtures/portfolio/settlements.json").into(),
+            ),
+        ])
+        .await;
+        let portfolio = portfolio(base_url);
+
+        let balance = portfolio.get_balance().await.unwrap();
+        assert_eq!(balance.balance_cents(), 48213);
+        let positions = portfolio
+            .get_positions(&GetPositionsParams::default())
+            .await
+            .unwrap();
+        assert_eq!(positions.market_positions.len(), 2);
+        let params = GetFillsParams {
+            min_ts: Some(1760700000),
+            ..Default::default()
+        };
+        let fills: Vec<Fill> = portfolio.fills(params).try_collect().await.unwrap();
+        assert_eq!(fills.len(), 2);
+        let settlements: Vec<Settlement> = portfolio
+            .settlements(GetSettlementsParams::default())
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(settlements.len(), 3);
+
+        let requests = requests.lock().unwrap();
+        let lines: Vec<&str> = requests.iter().map(|r| request_line(r)).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "GET /trade-api/v2/portfolio/balance HTTP/1.1",
+                "GET /trade-api/v2/portfolio/positions HTTP/1.1",
+                "GET /trade-api/v2/portfolio/fills?min_ts=1760700000 HTTP/1.1",
+                "GET /trade-api/v2/portfolio/settlements HTTP/1.1",
+            ]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use reqwest::Method;
+use std::{sync::Mutex, time::Duration};
+use tokio::time::{Instant, sleep};
+
+/// Kalshi API usage tier, which decides how many requests are allowed per second
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RateLimitTier {
+    #[default]
+    Basic,
+    Advanced,
+    Premier,
+    Prime,
+}
+
+impl RateLimitTier {
+    /// Reads and writes allowed per second
+    pub fn limits(&self) -> (u32, u32) {
+        match self {
+            RateLimitTier::Basic => (20, 10),
+            RateLimitTier::Advanced => (30, 30),
+            RateLimitTier::Premier => (100, 100),
+            RateLimitTier::Prime => (400, 400),
+        }
+    }
+}
+
+struct TokenBucket {
+    capacity: f64,
+    /// Tokens regained per second
+    rate: f64,
+    /// Available tokens and when they were last counted
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    fn new(per_second: u32) -> Self {
+        let capacity = per_second as f64;
+        Self {
+            capacity,
+            rate: capacity,
+            state: Mutex::new((capacity, Instant::now())),
+        }
+    }
+
+    /// Take a token, or tell how long to wait for one
+    fn try_take(&self) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap();
+        let (tokens, last) = *state;
+        let now = Instant::now();
+        let tokens =
+            (tokens + now.duration_since(last).as_secs_f64() * self.rate).min(self.capacity);
+
+        if tokens >= 1.0 {
+            *state = (tokens - 1.0, now);
+            None
+        } else {
+            *state = (tokens, now);
+            Some(Duration::from_secs_f64((1.0 - tokens) / self.rate))
+        }
+    }
+
+    async fn acquire(&self) {
+        while let Some(wait) = self.try_take() {
+            sleep(wait).await;
+        }
+    }
+}
+
+/// Client-side limiter keeping requests within the tier's limits. GET
+/// requests count as reads, everything else as writes.
+pub struct RateLimiter {
+    reads: TokenBucket,
+    writes: TokenBucket,
+}
+
+impl RateLimiter {
+    pub fn new(tier: RateLimitTier) -> Self {
+        let (reads, writes) = tier.limits();
+        Self {
+            reads: TokenBucket::new(reads),
+            writes: TokenBucket::new(writes),
+        }
+    }
+
+    /// Wait until a request with `method` may be sent
+    pub async fn acquire(&self, method: &Method) {
+        if *method == Method::GET {
+            self.reads.acquire().await
+        } else {
+            self.writes.acquire().await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_writes_are_throttled_separately_from_reads() {
+        let limiter = RateLimiter::new(RateLimitTier::Basic);
+        let start = Instant::now();
+
+        // The whole burst is available right away
+        for _ in 0..20 {
+            limiter.acquire(&Method::GET).await;
+        }
+        for _ in 0..10 {
+            limiter.acquire(&Method::POST).await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        // Beyond it, writes come back at 10 per second
+        for _ in 0..3 {
+            limiter.acquire(&Method::DELETE).await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(250));
+    }
+}
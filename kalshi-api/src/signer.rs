@@ -0,0 +1,71 @@
+use anyhow::Result;
+use chrono::Utc;
+use reqwest::{Method, header::HeaderMap};
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicI64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::keys::{ApiKey, PrivateKey};
+
+/// Signs requests with timestamps corrected by the offset between our clock
+/// and the exchange's. Clones share the offset, so the REST and websocket
+/// clients stay in agreement.
+#[derive(Clone)]
+pub struct Signer {
+    api_key: ApiKey,
+    private_key: PrivateKey,
+    /// Exchange clock minus ours, in milliseconds
+    offset_ms: Arc<AtomicI64>,
+    synced_at: Arc<Mutex<Option<Instant>>>,
+}
+
+impl Signer {
+    pub fn new(api_key: ApiKey, private_key: PrivateKey) -> Self {
+        Self {
+            api_key,
+            private_key,
+            offset_ms: Arc::new(AtomicI64::new(0)),
+            synced_at: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn offset_ms(&self) -> i64 {
+        self.offset_ms.load(Ordering::Relaxed)
+    }
+
+    pub fn set_offset_ms(&self, offset_ms: i64) {
+        self.offset_ms.store(offset_ms, Ordering::Relaxed);
+        *self.synced_at.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Whether the offset was never measured or is older than `max_age`
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.synced_at
+            .lock()
+            .unwrap()
+            .is_none_or(|synced_at| synced_at.elapsed() > max_age)
+    }
+
+    /// Current time on the exchange's clock, in milliseconds
+    pub fn now_ms(&self) -> i64 {
+        Utc::now().timestamp_millis() + self.offset_ms()
+    }
+
+    /// Headers authenticating a request to `full_path`, which Kalshi signs
+    /// without the query string
+    pub fn headers(&self, method: &Method, full_path: &str) -> Result<HeaderMap> {
+        let now = self.now_ms();
+        let msg = format!("{now}{method}{full_path}");
+        let signed_msg = self.private_key.sign(&msg)?;
+
+        let mut headers = HeaderMap::new();
+        let _ = headers.insert("KALSHI-ACCESS-KEY", self.api_key.to_string().parse()?);
+        let _ = headers.insert("KALSHI-ACCESS-SIGNATURE", signed_msg.parse()?);
+        let _ = headers.insert("KALSHI-ACCESS-TIMESTAMP", now.to_string().parse()?);
+        Ok(headers)
+    }
+}
@@ -0,0 +1,171 @@
+use anyhow::{Context, Result};
+use serde::{Serialize, de::DeserializeOwned};
+use std::{
+    collections::HashSet,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::AsyncWriteExt,
+};
+
+async fn read_lines(path: &Path) -> Result<String> {
+    match fs::read_to_string(path).await {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e).with_context(|| format!("Reading {}", path.display())),
+    }
+}
+
+/// Records of a JSONL file, skipping lines that don't parse such as one cut
+/// short by an interrupted run
+fn parse_lines<T: DeserializeOwned>(path: &Path, content: &str) -> Vec<T> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(i, line)| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                eprintln!("Skipping line {} of {}: {}", i + 1, path.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Append-only JSONL file that never stores two records with the same key,
+/// so a download can be run again after being interrupted
+pub struct JsonlStore<T> {
+    path: PathBuf,
+    file: File,
+    key: fn(&T) -> String,
+    keys: HashSet<String>,
+}
+
+impl<T: Serialize + DeserializeOwned> JsonlStore<T> {
+    pub async fn open(path: impl Into<PathBuf>, key: fn(&T) -> String) -> Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        let content = read_lines(&path).await?;
+        let keys = parse_lines(&path, &content).iter().map(key).collect();
+
+        let mut file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&path)
+            .await
+            .with_context(|| format!("Opening {}", path.display()))?;
+        // Don't glue the next record onto a line cut short
+        if !content.is_empty() && !content.ends_with('\n') {
+            file.write_all(b"\n").await?;
+        }
+
+        Ok(Self {
+            path,
+            file,
+            key,
+            keys,
+        })
+    }
+
+    /// Every record stored at `path`
+    pub async fn load(path: impl Into<PathBuf>) -> Result<Vec<T>> {
+        let path = path.into();
+        let content = read_lines(&path).await?;
+        Ok(parse_lines(&path, &content))
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.keys.contains(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Store `record` unless one with the same key already is. Returns whether
+    /// it was written.
+    pub async fn append(&mut self, record: &T) -> Result<bool> {
+        let key = (self.key)(record);
+        if self.keys.contains(&key) {
+            return Ok(false);
+        }
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        self.file
+            .write_all(line.as_bytes())
+            .await
+            .with_context(|| format!("Writing to {}", self.path.display()))?;
+        self.keys.insert(key);
+        Ok(true)
+    }
+
+    pub async fn flush(&mut self) -> Result<()> {
+        self.file.flush().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        id: u32,
+        value: String,
+    }
+
+    fn key(record: &Record) -> String {
+        record.id.to_string()
+    }
+
+    fn record(id: u32, value: &str) -> Record {
+        Record {
+            id,
+            value: value.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resumes_without_duplicates() {
+        let dir = std::env::temp_dir().join(format!("jsonl-store-{}", std::process::id()));
+        let path = dir.join("records.jsonl");
+        let _ = fs::remove_file(&path).await;
+
+        let mut store = JsonlStore::open(&path, key).await.unwrap();
+        assert!(store.append(&record(1, "a")).await.unwrap());
+        assert!(!store.append(&record(1, "b")).await.unwrap());
+        store.flush().await.unwrap();
+        drop(store);
+
+        // An interrupted run left half a record behind
+        let mut file = OpenOptions::new().append(true).open(&path).await.unwrap();
+        file.write_all(br#"{"id": 2, "val"#).await.unwrap();
+        drop(file);
+
+        let mut store = JsonlStore::open(&path, key).await.unwrap();
+        assert!(store.contains("1"));
+        assert!(!store.append(&record(1, "c")).await.unwrap());
+        assert!(store.append(&record(2, "d")).await.unwrap());
+        store.flush().await.unwrap();
+
+        let records = JsonlStore::<Record>::load(&path).await.unwrap();
+        assert_eq!(records, vec![record(1, "a"), record(2, "d")]);
+        fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
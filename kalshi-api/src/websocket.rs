@@ -0,0 +1,473 @@
+use anyhow::{Result, anyhow, bail};
+use futures::{
+    Sink, SinkExt, Stream, StreamExt,
+    channel::mpsc::{self, UnboundedReceiver, UnboundedSender},
+};
+use kalshi_api_spec::{
+    ticker::MarketTicker,
+    websocket::{Channel, UpdateAction, WebsocketCommand, WebsocketMessage, WebsocketRequest},
+};
+use reqwest::Method;
+use std::{
+    collections::{BTreeSet, HashMap},
+    time::Duration,
+};
+use tokio::time::sleep;
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{self, Message, client::IntoClientRequest},
+};
+
+use crate::{
+    client::{BaseUrl, WEBSOCKET_PATH},
+    signer::Signer,
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum WebsocketEvent {
+    /// A connection was (re)established. Subscriptions are re-sent right after,
+    /// so order books will receive a fresh snapshot.
+    Connected,
+    Message(WebsocketMessage),
+}
+
+enum Command {
+    Subscribe(Channel, Vec<MarketTicker>),
+    Unsubscribe(Channel, Vec<MarketTicker>),
+    Resubscribe(Channel, Vec<MarketTicker>),
+}
+
+/// Handle to a websocket connection that is kept alive in the background.
+/// Dropping every handle and the event stream shuts the connection down.
+#[derive(Clone)]
+pub struct KalshiWebsocketClient {
+    commands: UnboundedSender<Command>,
+}
+
+impl KalshiWebsocketClient {
+    /// Pass the signer of the REST client, e.g. `client.signer().clone()`, so
+    /// that the handshake uses the clock offset it measured
+    pub fn connect(
+        signer: Signer,
+        base_url: BaseUrl,
+    ) -> (Self, impl Stream<Item = WebsocketEvent> + Send) {
+        let (command_tx, command_rx) = mpsc::unbounded();
+        let (event_tx, event_rx) = mpsc::unbounded();
+        let connection = Connection {
+            signer,
+            url: base_url.websocket_url(),
+            subscriptions: Subscriptions::default(),
+        };
+        tokio::spawn(connection.run(command_rx, event_tx));
+        (
+            Self {
+                commands: command_tx,
+            },
+            event_rx,
+        )
+    }
+
+    pub fn subscribe(&self, channel: Channel, tickers: &[MarketTicker]) -> Result<()> {
+        self.send(Command::Subscribe(channel, tickers.to_vec()))
+    }
+
+    pub fn unsubscribe(&self, channel: Channel, tickers: &[MarketTicker]) -> Result<()> {
+        self.send(Command::Unsubscribe(channel, tickers.to_vec()))
+    }
+
+    /// Drop and re-add markets to their subscription, which makes the exchange
+    /// send a fresh order book snapshot for each of them
+    pub fn resubscribe(&self, channel: Channel, tickers: &[MarketTicker]) -> Result<()> {
+        self.send(Command::Resubscribe(channel, tickers.to_vec()))
+    }
+
+    pub fn subscribe_fills(&self) -> Result<()> {
+        self.subscribe(Channel::Fill, &[])
+    }
+
+    fn send(&self, command: Command) -> Result<()> {
+        self.commands
+            .unbounded_send(command)
+            .map_err(|_| anyhow!("Kalshi websocket task has stopped"))
+    }
+}
+
+/// Bookkeeping of what we want to be subscribed to versus what the exchange
+/// has acknowledged. There is at most one subscription (sid) per channel.
+#[derive(Default)]
+struct Subscriptions {
+    /// Markets we want per channel. Survives reconnects.
+    desired: HashMap<Channel, BTreeSet<MarketTicker>>,
+    /// Markets covered by a live or in-flight subscription per channel
+    requested: HashMap<Channel, BTreeSet<MarketTicker>>,
+    sids: HashMap<Channel, u64>,
+    /// Subscribe requests waiting for an acknowledgement, by request id
+    pending: HashMap<u64, Channel>,
+    next_id: u64,
+}
+
+impl Subscriptions {
+    fn request(&mut self, command: WebsocketCommand) -> WebsocketRequest {
+        self.next_id += 1;
+        WebsocketRequest {
+            id: self.next_id,
+            command,
+        }
+    }
+
+    fn subscribe(&mut self, channel: Channel, tickers: Vec<MarketTicker>) -> Vec<WebsocketRequest> {
+        if channel.is_market_scoped() && tickers.is_empty() {
+            return vec![];
+        }
+        self.desired.entry(channel).or_default().extend(tickers);
+        self.sync(channel)
+    }
+
+    fn unsubscribe(
+        &mut self,
+        channel: Channel,
+        tickers: Vec<MarketTicker>,
+    ) -> Vec<WebsocketRequest> {
+        if let Some(markets) = self.desired.get_mut(&channel) {
+            for ticker in tickers.iter() {
+                markets.remove(ticker);
+            }
+            if markets.is_empty() || !channel.is_market_scoped() {
+                self.desired.remove(&channel);
+            }
+        }
+        self.sync(channel)
+    }
+
+    fn resubscribe(
+        &mut self,
+        channel: Channel,
+        tickers: Vec<MarketTicker>,
+    ) -> Vec<WebsocketRequest> {
+        let Some(sid) = self.sids.get(&channel).copied() else {
+            // Not acknowledged yet, a snapshot is on its way anyway
+            return vec![];
+        };
+        let requested = self.requested.get(&channel).cloned().unwrap_or_default();
+        let market_tickers: Vec<_> = tickers
+            .into_iter()
+            .filter(|ticker| requested.contains(ticker))
+            .collect();
+        if market_tickers.is_empty() {
+            return vec![];
+        }
+
+        [UpdateAction::DeleteMarkets, UpdateAction::AddMarkets]
+            .into_iter()
+            .map(|action| {
+                self.request(WebsocketCommand::UpdateSubscription {
+                    sids: vec![sid],
+                    market_tickers: market_tickers.clone(),
+                    action,
+                })
+            })
+            .collect()
+    }
+
+    fn on_subscribed(
+        &mut self,
+        id: Option<u64>,
+        channel: Channel,
+        sid: u64,
+    ) -> Vec<WebsocketRequest> {
+        if let Some(id) = id {
+            self.pending.remove(&id);
+        }
+        self.sids.insert(channel, sid);
+        self.sync(channel)
+    }
+
+    fn on_error(&mut self, id: Option<u64>) {
+        // Forget the failed subscription instead of retrying it in a loop
+        if let Some(channel) = id.and_then(|id| self.pending.remove(&id)) {
+            self.requested.remove(&channel);
+            self.desired.remove(&channel);
+        }
+    }
+
+    /// Forget all server-side state and re-subscribe everything we want
+    fn reset(&mut self) -> Vec<WebsocketRequest> {
+        self.requested.clear();
+        self.sids.clear();
+        self.pending.clear();
+        let channels: Vec<Channel> = self.desired.keys().copied().collect();
+        channels
+            .into_iter()
+            .flat_map(|channel| self.sync(channel))
+            .collect()
+    }
+
+    /// Requests needed to go from the requested markets to the desired ones
+    fn sync(&mut self, channel: Channel) -> Vec<WebsocketRequest> {
+        if self.pending.values().any(|c| *c == channel) {
+            // Re-synced once the subscription is acknowledged
+            return vec![];
+        }
+
+        let desired = self.desired.get(&channel).cloned();
+        match (desired, self.sids.get(&channel).copied()) {
+            (None, None) => vec![],
+            (Some(markets), None) => {
+                let request = self.request(WebsocketCommand::Subscribe {
+                    channels: vec![channel],
+                    market_tickers: markets.iter().cloned().collect(),
+                });
+                self.pending.insert(request.id, channel);
+                self.requested.insert(channel, markets);
+                vec![request]
+            }
+            (None, Some(sid)) => {
+                self.sids.remove(&channel);
+                self.requested.remove(&channel);
+                vec![self.request(WebsocketCommand::Unsubscribe { sids: vec![sid] })]
+            }
+            (Some(markets), Some(sid)) => {
+                let requested = self.requested.remove(&channel).unwrap_or_default();
+                let added: Vec<_> = markets.difference(&requested).cloned().collect();
+                let removed: Vec<_> = requested.difference(&markets).cloned().collect();
+                self.requested.insert(channel, markets);
+
+                let mut requests = vec![];
+                for (market_tickers, action) in [
+                    (added, UpdateAction::AddMarkets),
+                    (removed, UpdateAction::DeleteMarkets),
+                ] {
+                    if !market_tickers.is_empty() {
+                        requests.push(self.request(WebsocketCommand::UpdateSubscription {
+                            sids: vec![sid],
+                            market_tickers,
+                            action,
+                        }));
+                    }
+                }
+                requests
+            }
+        }
+    }
+}
+
+struct Connection {
+    signer: Signer,
+    url: String,
+    subscriptions: Subscriptions,
+}
+
+async fn send_request<S>(sink: &mut S, request: WebsocketRequest) -> Result<()>
+where
+    S: Sink<Message, Error = tungstenite::Error> + Unpin,
+{
+    let text = serde_json::to_string(&request)?;
+    sink.send(Message::Text(text.into())).await?;
+    Ok(())
+}
+
+impl Connection {
+    async fn run(
+        mut self,
+        mut commands: UnboundedReceiver<Command>,
+        events: UnboundedSender<WebsocketEvent>,
+    ) {
+        let mut backoff = INITIAL_BACKOFF;
+        loop {
+            match self.serve(&mut commands, &events, &mut backoff).await {
+                Ok(()) => return,
+                Err(e) => eprintln!("Kalshi websocket disconnected: {:?}", e),
+            }
+            if events.is_closed() {
+                return;
+            }
+            sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    /// Returns `Ok` once nobody is listening anymore and `Err` when the
+    /// connection should be re-established. `backoff` starts over once
+    /// connected, so that a drop after a healthy period reconnects quickly.
+    async fn serve(
+        &mut self,
+        commands: &mut UnboundedReceiver<Command>,
+        events: &UnboundedSender<WebsocketEvent>,
+        backoff: &mut Duration,
+    ) -> Result<()> {
+        let mut request = self.url.as_str().into_client_request()?;
+        let headers = self.signer.headers(&Method::GET, WEBSOCKET_PATH)?;
+        request.headers_mut().extend(headers);
+        let (socket, _) = connect_async(request).await?;
+        let (mut write, mut read) = socket.split();
+
+        if events.unbounded_send(WebsocketEvent::Connected).is_err() {
+            return Ok(());
+        }
+        for request in self.subscriptions.reset() {
+            send_request(&mut write, request).await?;
+        }
+        *backoff = INITIAL_BACKOFF;
+
+        let mut commands_open = true;
+        loop {
+            tokio::select! {
+                command = commands.next(), if commands_open => {
+                    let requests = match command {
+                        Some(Command::Subscribe(channel, tickers)) => {
+                            self.subscriptions.subscribe(channel, tickers)
+                        }
+                        Some(Command::Unsubscribe(channel, tickers)) => {
+                            self.subscriptions.unsubscribe(channel, tickers)
+                        }
+                        Some(Command::Resubscribe(channel, tickers)) => {
+                            self.subscriptions.resubscribe(channel, tickers)
+                        }
+                        None => {
+                            commands_open = false;
+                            vec![]
+                        }
+                    };
+                    for request in requests {
+                        send_request(&mut write, request).await?;
+                    }
+                }
+                message = read.next() => {
+                    let text = match message {
+                        None => bail!("Connection closed"),
+                        Some(Err(e)) => return Err(e.into()),
+                        Some(Ok(Message::Close(frame))) => bail!("Connection closed by server: {:?}", frame),
+                        Some(Ok(Message::Text(text))) => text,
+                        // Pings are answered by tungstenite
+                        Some(Ok(_)) => continue,
+                    };
+
+                    let message: WebsocketMessage = match serde_json::from_str(&text) {
+                        Ok(message) => message,
+                        Err(e) => {
+                            eprintln!("Unrecognized websocket message {}: {}", text.as_str(), e);
+                            continue;
+                        }
+                    };
+
+                    let requests = match &message {
+                        WebsocketMessage::Subscribed { id, msg } => {
+                            self.subscriptions.on_subscribed(*id, msg.channel, msg.sid)
+                        }
+                        WebsocketMessage::Error { id, msg } => {
+                            eprintln!("Kalshi websocket error {}: {}", msg.code, msg.msg);
+                            self.subscriptions.on_error(*id);
+                            vec![]
+                        }
+                        _ => vec![],
+                    };
+                    for request in requests {
+                        send_request(&mut write, request).await?;
+                    }
+
+                    if events.unbounded_send(WebsocketEvent::Message(message)).is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn tickers(tickers: &[&str]) -> Vec<MarketTicker> {
+        tickers
+            .iter()
+            .map(|ticker| ticker.parse().unwrap())
+            .collect()
+    }
+
+    fn wire(requests: Vec<WebsocketRequest>) -> Vec<Value> {
+        requests
+            .iter()
+            .map(|request| serde_json::to_value(request).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_subscriptions() {
+        let mut subscriptions = Subscriptions::default();
+        let requests = subscriptions.subscribe(Channel::OrderbookDelta, tickers(&["A-1"]));
+        assert_eq!(
+            wire(requests),
+            vec![json!({
+                "id": 1,
+                "cmd": "subscribe",
+                "params": {"channels": ["orderbook_delta"], "market_tickers": ["A-1"]},
+            })]
+        );
+
+        // Waits for the acknowledgement before adding markets
+        assert!(
+            subscriptions
+                .subscribe(Channel::OrderbookDelta, tickers(&["B-1"]))
+                .is_empty()
+        );
+        // Nothing to resubscribe before the exchange gave a sid
+        assert!(
+            subscriptions
+                .resubscribe(Channel::OrderbookDelta, tickers(&["A-1"]))
+                .is_empty()
+        );
+        assert_eq!(
+            wire(subscriptions.on_subscribed(Some(1), Channel::OrderbookDelta, 7)),
+            vec![json!({
+                "id": 2,
+                "cmd": "update_subscription",
+                "params": {"sids": [7], "market_tickers": ["B-1"], "action": "add_markets"},
+            })]
+        );
+
+        // Drops and adds back the markets with a sequence gap, only if subscribed
+        assert_eq!(
+            wire(subscriptions.resubscribe(Channel::OrderbookDelta, tickers(&["A-1", "C-1"]))),
+            vec![
+                json!({
+                    "id": 3,
+                    "cmd": "update_subscription",
+                    "params": {"sids": [7], "market_tickers": ["A-1"], "action": "delete_markets"},
+                }),
+                json!({
+                    "id": 4,
+                    "cmd": "update_subscription",
+                    "params": {"sids": [7], "market_tickers": ["A-1"], "action": "add_markets"},
+                }),
+            ]
+        );
+
+        // A reconnect subscribes to every desired market again
+        assert_eq!(
+            wire(subscriptions.reset()),
+            vec![json!({
+                "id": 5,
+                "cmd": "subscribe",
+                "params": {"channels": ["orderbook_delta"], "market_tickers": ["A-1", "B-1"]},
+            })]
+        );
+        subscriptions.on_subscribed(Some(5), Channel::OrderbookDelta, 8);
+        assert_eq!(
+            wire(subscriptions.unsubscribe(Channel::OrderbookDelta, tickers(&["A-1", "B-1"]))),
+            vec![json!({"id": 6, "cmd": "unsubscribe", "params": {"sids": [8]}})]
+        );
+    }
+
+    #[test]
+    fn test_refused_subscription_is_forgotten() {
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.subscribe(Channel::Fill, vec![]);
+        subscriptions.on_error(Some(1));
+        assert!(subscriptions.reset().is_empty());
+    }
+}
@@ -0,0 +1,22 @@
+use chrono::NaiveDate;
+use kalshi_api_spec::ticker::{EventTicker, SeriesTicker};
+use weather::station::Station;
+
+/// Kalshi series settled on a station's observations
+pub trait StationSeries {
+    /// Series of the daily highest temperature markets
+    fn high_temperature_series(&self) -> SeriesTicker;
+
+    /// Highest temperature event of `date`, in the station's local time
+    fn high_temperature_event(&self, date: &NaiveDate) -> EventTicker {
+        EventTicker::new(&self.high_temperature_series(), *date)
+    }
+}
+
+impl StationSeries for Station {
+    fn high_temperature_series(&self) -> SeriesTicker {
+        match self {
+            Station::KNYC => "KXHIGHNY".parse().unwrap(),
+        }
+    }
+}
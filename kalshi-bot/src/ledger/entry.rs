@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use kalshi_api_spec::{
+    fee::{Liquidity, fee},
+    market::MarketResult,
+    money::{Money, Price},
+    order::{Action, Side},
+    portfolio::{Fill, Settlement},
+    ticker::MarketTicker,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::order_engine::paper::PaperFill;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerFill {
+    pub fill_id: String,
+    pub ticker: MarketTicker,
+    pub side: Side,
+    pub action: Action,
+    pub count: u32,
+    /// Price of the contract on `side`
+    pub price: Price,
+    pub fee: Money,
+    pub time: DateTime<Utc>,
+}
+
+impl LedgerFill {
+    /// Side and price of the contracts bought: selling YES at 0.60 is buying
+    /// NO at 0.40
+    pub fn bought(&self) -> (Side, Price) {
+        match self.action {
+            Action::Buy => (self.side, self.price),
+            Action::Sell => (self.side.opposite(), self.price.complement()),
+        }
+    }
+}
+
+/// Kalshi doesn't report fees per fill, so they are computed from the fee
+/// schedule
+impl From<&Fill> for LedgerFill {
+    fn from(fill: &Fill) -> Self {
+        let liquidity = Liquidity::from_is_taker(fill.is_taker());
+        Self {
+            fill_id: fill.fill_id().to_string(),
+            ticker: fill.ticker().clone(),
+            side: fill.side(),
+            action: fill.action(),
+            count: fill.count(),
+            price: *fill.price(),
+            fee: fee(liquidity, fill.count(), *fill.price()),
+            time: fill.created_time(),
+        }
+    }
+}
+
+impl From<&PaperFill> for LedgerFill {
+    fn from(fill: &PaperFill) -> Self {
+        Self {
+            fill_id: fill.fill_id.clone(),
+            ticker: fill.ticker.clone(),
+            side: fill.side,
+            action: fill.action,
+            count: fill.count,
+            price: fill.price,
+            fee: fill.fee,
+            time: fill.created_time,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerSettlement {
+    pub ticker: MarketTicker,
+    pub result: MarketResult,
+    pub yes_count: u32,
+    pub yes_cost: Money,
+    pub no_count: u32,
+    pub no_cost: Money,
+    /// Paid out for the contracts held
+    pub revenue: Money,
+    pub time: DateTime<Utc>,
+}
+
+impl From<&Settlement> for LedgerSettlement {
+    fn from(settlement: &Settlement) -> Self {
+        Self {
+            ticker: settlement.ticker().clone(),
+            result: settlement.market_result(),
+            yes_count: settlement.yes_count(),
+            yes_cost: Money::from_cents(settlement.yes_total_cost_cents()),
+            no_count: settlement.no_count(),
+            no_cost: Money::from_cents(settlement.no_total_cost_cents()),
+            revenue: Money::from_cents(settlement.revenue_cents()),
+            time: settlement.settled_time(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LedgerEntry {
+    Fill(LedgerFill),
+    Settlement(LedgerSettlement),
+}
+
+impl LedgerEntry {
+    /// Unique per entry, so that syncing twice doesn't record anything twice
+    pub fn key(&self) -> String {
+        match self {
+            LedgerEntry::Fill(fill) => format!("fill-{}", fill.fill_id),
+            LedgerEntry::Settlement(settlement) => format!("settlement-{}", settlement.ticker),
+        }
+    }
+
+    pub fn ticker(&self) -> &MarketTicker {
+        match self {
+            LedgerEntry::Fill(fill) => &fill.ticker,
+            LedgerEntry::Settlement(settlement) => &settlement.ticker,
+        }
+    }
+
+    pub fn time(&self) -> DateTime<Utc> {
+        match self {
+            LedgerEntry::Fill(fill) => fill.time,
+            LedgerEntry::Settlement(settlement) => settlement.time,
+        }
+    }
+}
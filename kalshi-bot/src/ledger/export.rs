@@ -0,0 +1,86 @@
+use chrono::Datelike;
+use kalshi_api_spec::money::Money;
+use std::fmt::Write;
+
+use crate::ledger::pnl::ClosedTrade;
+
+const HEADERS: [&str; 10] = [
+    "closed", "opened", "ticker", "side", "count", "cost", "proceeds", "fees", "pnl", "currency",
+];
+
+/// Quote a field when it holds a separator, a quote or a line break
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Dollars with cents, as accounting software expects
+fn amount(money: Money) -> String {
+    format!("{:.2}", money.value())
+}
+
+/// Trades closed during `year`, one row each, for accounting
+pub fn closed_trades_csv(trades: &[ClosedTrade], year: i32) -> String {
+    let mut csv = HEADERS.join(",");
+    csv.push('\n');
+    let mut trades: Vec<_> = trades
+        .iter()
+        .filter(|trade| trade.closed.year() == year)
+        .collect();
+    trades.sort_by_key(|trade| trade.closed);
+
+    for trade in trades {
+        let row = [
+            trade.closed.to_rfc3339(),
+            trade.opened.to_rfc3339(),
+            trade.ticker.to_string(),
+            trade.side.to_string(),
+            trade.count.to_string(),
+            amount(trade.cost),
+            amount(trade.proceeds),
+            amount(trade.fees),
+            amount(trade.pnl()),
+            "USD".to_string(),
+        ];
+        let row: Vec<String> = row.iter().map(|field| escape(field)).collect();
+        writeln!(csv, "{}", row.join(",")).unwrap();
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+    use kalshi_api_spec::{order::Side, usd};
+
+    fn trade(closed: &str) -> ClosedTrade {
+        let closed: DateTime<Utc> = closed.parse().unwrap();
+        ClosedTrade {
+            ticker: "KXHIGHNY-25DEC31-B61.5".parse().unwrap(),
+            side: Side::Yes,
+            count: 10,
+            opened: closed,
+            closed,
+            cost: usd!(3),
+            proceeds: usd!(10),
+            fees: usd!(0.15),
+        }
+    }
+
+    #[test]
+    fn test_closed_trades_csv() {
+        let trades = [trade("2026-01-01T00:00:00Z"), trade("2025-12-31T23:00:00Z")];
+        let csv = closed_trades_csv(&trades, 2025);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[1],
+            "2025-12-31T23:00:00+00:00,2025-12-31T23:00:00+00:00,KXHIGHNY-25DEC31-B61.5,yes,10,3.00,10.00,0.15,6.85,USD"
+        );
+        assert_eq!(escape("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+}
@@ -0,0 +1,126 @@
+pub mod entry;
+pub mod export;
+pub mod pnl;
+pub mod store;
+
+use crate::ledger::{export::closed_trades_csv, pnl::Pnl, store::LedgerStore};
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use kalshi_api::{
+    credentials::CredentialArgs, markets::MarketsApiClient, output::Table,
+    portfolio::PortfolioApiClient,
+};
+use std::{collections::BTreeMap, fmt::Display, path::PathBuf, time::Duration};
+
+/// Where the ledger is kept unless told otherwise
+pub const LEDGER_DIR: &str = "data/ledger";
+
+#[derive(Debug, Clone, Args)]
+pub struct LedgerCommand {
+    #[command(subcommand)]
+    command: LedgerSubcommand,
+
+    /// Directory keeping the ledger between runs
+    #[arg(long, default_value = LEDGER_DIR)]
+    dir: PathBuf,
+
+    #[command(flatten)]
+    credentials: CredentialArgs,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum LedgerSubcommand {
+    /// Record the fills and settlements of the account
+    Sync {
+        /// Keep syncing, every this many seconds
+        #[arg(long)]
+        every: Option<u64>,
+    },
+    /// Realized PnL per market, event and day, and unrealized PnL at the
+    /// current best bids
+    Pnl,
+    /// Write the trades closed during a year as CSV
+    Export {
+        #[arg(long)]
+        year: i32,
+
+        /// Output file, stdout if not given
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+fn pnl_table<K: Display>(header: &str, pnl: &BTreeMap<K, Pnl>) -> Table {
+    let mut table = Table::new(&[header, "gross", "fees", "net"]);
+    for (key, pnl) in pnl {
+        table.push(vec![
+            key.to_string(),
+            pnl.gross.to_string(),
+            pnl.fees.to_string(),
+            pnl.net().to_string(),
+        ]);
+    }
+    table
+}
+
+pub async fn run_ledger(command: &LedgerCommand) -> Result<()> {
+    let mut store = LedgerStore::open(&command.dir).await?;
+
+    match &command.command {
+        LedgerSubcommand::Sync { every } => {
+            let client = PortfolioApiClient::from(command.credentials.client()?);
+            loop {
+                match store.sync(&client).await {
+                    Ok(new) if new > 0 => {
+                        println!("Recorded {} entries in {}", new, store.path().display())
+                    }
+                    Ok(_) => {}
+                    Err(e) if every.is_some() => eprintln!("Failed to sync the ledger: {:?}", e),
+                    Err(e) => return Err(e),
+                }
+                let Some(every) = every else {
+                    break;
+                };
+                tokio::time::sleep(Duration::from_secs(*every)).await;
+            }
+        }
+        LedgerSubcommand::Pnl => {
+            let client = MarketsApiClient::from(command.credentials.client()?);
+            let positions = store.ledger().positions();
+            for ticker in positions.keys() {
+                let book = client.get_orderbook(ticker, None).await?;
+                store.ledger_mut().mark(&book);
+            }
+
+            let ledger = store.ledger();
+            print!("{}", pnl_table("market", &ledger.realized_by_market()));
+            println!();
+            print!("{}", pnl_table("event", &ledger.realized_by_event()));
+            println!();
+            print!("{}", pnl_table("day", &ledger.realized_by_day()));
+            println!();
+
+            let mut table = Table::new(&["market", "side", "count", "unrealized"]);
+            for (ticker, (side, count)) in &positions {
+                table.push(vec![
+                    ticker.to_string(),
+                    side.to_string(),
+                    count.to_string(),
+                    ledger.unrealized(ticker).to_string(),
+                ]);
+            }
+            print!("{}", table);
+        }
+        LedgerSubcommand::Export { year, output } => {
+            let csv = closed_trades_csv(store.ledger().closed_trades(), *year);
+            match output {
+                Some(path) => tokio::fs::write(path, csv)
+                    .await
+                    .with_context(|| format!("Writing {}", path.display()))?,
+                None => print!("{}", csv),
+            }
+        }
+    }
+
+    Ok(())
+}
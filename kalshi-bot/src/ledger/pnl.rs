@@ -0,0 +1,397 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use kalshi_api_spec::{
+    money::{Money, Price},
+    order::Side,
+    orderbook::OrderBook,
+    ticker::{EventTicker, MarketTicker},
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use crate::ledger::entry::{LedgerEntry, LedgerFill, LedgerSettlement};
+
+/// Contracts bought together, still held
+#[derive(Debug, Clone)]
+struct Lot {
+    side: Side,
+    count: u32,
+    price: Price,
+    /// Share of the fees of the fill that opened the lot
+    fee: Money,
+    opened: DateTime<Utc>,
+}
+
+/// Contracts bought, then sold or settled
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClosedTrade {
+    pub ticker: MarketTicker,
+    pub side: Side,
+    pub count: u32,
+    pub opened: DateTime<Utc>,
+    pub closed: DateTime<Utc>,
+    pub cost: Money,
+    pub proceeds: Money,
+    /// Share of the fees of both the opening and the closing fill
+    pub fees: Money,
+}
+
+impl ClosedTrade {
+    pub fn pnl(&self) -> Money {
+        self.proceeds - self.cost - self.fees
+    }
+}
+
+/// Realized PnL, before and after fees
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Pnl {
+    pub gross: Money,
+    pub fees: Money,
+}
+
+impl Pnl {
+    pub fn net(&self) -> Money {
+        self.gross - self.fees
+    }
+
+    fn add(&mut self, other: Pnl) {
+        self.gross = self.gross + other.gross;
+        self.fees = self.fees + other.fees;
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Realization {
+    ticker: MarketTicker,
+    time: DateTime<Utc>,
+    pnl: Pnl,
+}
+
+/// Splits an amount such as the fee of a fill across parts of its contracts
+/// in whole cents, the last part taking what is left
+struct CentSplit {
+    cents: i64,
+    count: u32,
+}
+
+impl CentSplit {
+    fn take(&mut self, count: u32) -> Money {
+        let cents = self.cents * count as i64 / self.count.max(1) as i64;
+        self.cents -= cents;
+        self.count -= count;
+        Money::from_cents(cents)
+    }
+}
+
+/// Positions, realized and unrealized PnL built from fills and settlements.
+/// Lots are closed first in, first out. Fees count against the day they were
+/// paid.
+#[derive(Debug, Default)]
+pub struct Ledger {
+    lots: HashMap<MarketTicker, VecDeque<Lot>>,
+    closed: Vec<ClosedTrade>,
+    realized: Vec<Realization>,
+    /// Best YES and NO bids of the latest book of each market
+    marks: HashMap<MarketTicker, (Option<Price>, Option<Price>)>,
+}
+
+impl Ledger {
+    pub fn record(&mut self, entry: &LedgerEntry) {
+        match entry {
+            LedgerEntry::Fill(fill) => self.record_fill(fill),
+            LedgerEntry::Settlement(settlement) => self.record_settlement(settlement),
+        }
+    }
+
+    fn record_fill(&mut self, fill: &LedgerFill) {
+        let (side, price) = fill.bought();
+        let mut fees = CentSplit {
+            cents: fill.fee.cents(),
+            count: fill.count,
+        };
+        let lots = self.lots.entry(fill.ticker.clone()).or_default();
+
+        // Buying the other side closes the contracts held
+        let mut remaining = fill.count;
+        while remaining > 0
+            && let Some(lot) = lots.front_mut()
+            && lot.side != side
+        {
+            let count = remaining.min(lot.count);
+            let lot_fee = CentSplit {
+                cents: lot.fee.cents(),
+                count: lot.count,
+            }
+            .take(count);
+            self.closed.push(ClosedTrade {
+                ticker: fill.ticker.clone(),
+                side: lot.side,
+                count,
+                opened: lot.opened,
+                closed: fill.time,
+                cost: lot.price.cost(count),
+                proceeds: price.complement().cost(count),
+                fees: lot_fee + fees.take(count),
+            });
+            self.realized.push(Realization {
+                ticker: fill.ticker.clone(),
+                time: fill.time,
+                pnl: Pnl {
+                    gross: price.complement().cost(count) - lot.price.cost(count),
+                    fees: Money::ZERO,
+                },
+            });
+
+            remaining -= count;
+            lot.count -= count;
+            lot.fee = lot.fee - lot_fee;
+            if lot.count == 0 {
+                lots.pop_front();
+            }
+        }
+
+        if remaining > 0 {
+            lots.push_back(Lot {
+                side,
+                count: remaining,
+                price,
+                fee: fees.take(remaining),
+                opened: fill.time,
+            });
+        }
+        self.realized.push(Realization {
+            ticker: fill.ticker.clone(),
+            time: fill.time,
+            pnl: Pnl {
+                gross: Money::ZERO,
+                fees: fill.fee,
+            },
+        });
+    }
+
+    fn record_settlement(&mut self, settlement: &LedgerSettlement) {
+        let lots = self.lots.remove(&settlement.ticker).unwrap_or_default();
+        let held: u32 = lots.iter().map(|lot| lot.count).sum();
+        let settled = settlement.yes_count + settlement.no_count;
+        if held != settled {
+            eprintln!(
+                "Ledger holds {} contracts of {} but {} were settled",
+                held, settlement.ticker, settled
+            );
+        }
+
+        let mut trades: Vec<ClosedTrade> = lots
+            .into_iter()
+            .map(|lot| ClosedTrade {
+                ticker: settlement.ticker.clone(),
+                side: lot.side,
+                count: lot.count,
+                opened: lot.opened,
+                closed: settlement.time,
+                cost: lot.price.cost(lot.count),
+                proceeds: Money::ZERO,
+                fees: lot.fee,
+            })
+            .collect();
+        if trades.is_empty() && settled > 0 {
+            // Bought before the ledger started
+            let (side, count, cost) = if settlement.yes_count > 0 {
+                (Side::Yes, settlement.yes_count, settlement.yes_cost)
+            } else {
+                (Side::No, settlement.no_count, settlement.no_cost)
+            };
+            trades.push(ClosedTrade {
+                ticker: settlement.ticker.clone(),
+                side,
+                count,
+                opened: settlement.time,
+                closed: settlement.time,
+                cost,
+                proceeds: Money::ZERO,
+                fees: Money::ZERO,
+            });
+        }
+
+        // Every contract pays the same, split the revenue by contracts
+        let mut revenue = CentSplit {
+            cents: settlement.revenue.cents(),
+            count: trades.iter().map(|trade| trade.count).sum(),
+        };
+        for mut trade in trades {
+            trade.proceeds = revenue.take(trade.count);
+            self.realized.push(Realization {
+                ticker: settlement.ticker.clone(),
+                time: settlement.time,
+                pnl: Pnl {
+                    gross: trade.proceeds - trade.cost,
+                    fees: Money::ZERO,
+                },
+            });
+            self.closed.push(trade);
+        }
+    }
+
+    /// Mark the positions of the book's market at its best bids
+    pub fn mark(&mut self, book: &OrderBook) {
+        let bid = |side| book.best_bid(side).map(|(price, _)| price);
+        self.marks
+            .insert(book.ticker().clone(), (bid(Side::Yes), bid(Side::No)));
+    }
+
+    /// Side and count held of every market with a position
+    pub fn positions(&self) -> BTreeMap<MarketTicker, (Side, u32)> {
+        self.lots
+            .iter()
+            .filter_map(|(ticker, lots)| {
+                let side = lots.front()?.side;
+                Some((
+                    ticker.clone(),
+                    (side, lots.iter().map(|lot| lot.count).sum()),
+                ))
+            })
+            .collect()
+    }
+
+    /// What the position would sell for at the best bid minus what it cost.
+    /// Zero for a market that was never marked, and the whole cost when
+    /// nobody bids.
+    pub fn unrealized(&self, ticker: &MarketTicker) -> Money {
+        let Some(lots) = self.lots.get(ticker) else {
+            return Money::ZERO;
+        };
+        let Some((yes_bid, no_bid)) = self.marks.get(ticker) else {
+            return Money::ZERO;
+        };
+        lots.iter()
+            .map(|lot| {
+                let bid = match lot.side {
+                    Side::Yes => yes_bid,
+                    Side::No => no_bid,
+                };
+                let value = bid.map(|bid| bid.cost(lot.count)).unwrap_or(Money::ZERO);
+                value - lot.price.cost(lot.count)
+            })
+            .sum()
+    }
+
+    pub fn unrealized_by_market(&self) -> BTreeMap<MarketTicker, Money> {
+        self.lots
+            .iter()
+            .filter(|(_, lots)| !lots.is_empty())
+            .map(|(ticker, _)| (ticker.clone(), self.unrealized(ticker)))
+            .collect()
+    }
+
+    fn realized_by<K: Ord>(&self, key: impl Fn(&Realization) -> K) -> BTreeMap<K, Pnl> {
+        let mut pnl: BTreeMap<K, Pnl> = BTreeMap::new();
+        for realization in &self.realized {
+            pnl.entry(key(realization))
+                .or_default()
+                .add(realization.pnl);
+        }
+        pnl
+    }
+
+    pub fn realized_by_market(&self) -> BTreeMap<MarketTicker, Pnl> {
+        self.realized_by(|realization| realization.ticker.clone())
+    }
+
+    pub fn realized_by_event(&self) -> BTreeMap<EventTicker, Pnl> {
+        self.realized_by(|realization| realization.ticker.event())
+    }
+
+    /// Realized PnL per UTC day
+    pub fn realized_by_day(&self) -> BTreeMap<NaiveDate, Pnl> {
+        self.realized_by(|realization| realization.time.date_naive())
+    }
+
+    /// Realized PnL of `date` after fees plus the unrealized PnL of every
+    /// open position, what the daily loss limit is checked against
+    pub fn daily_pnl(&self, date: NaiveDate) -> Money {
+        let realized = self
+            .realized_by_day()
+            .get(&date)
+            .map(Pnl::net)
+            .unwrap_or(Money::ZERO);
+        realized + self.lots.keys().map(|ticker| self.unrealized(ticker)).sum()
+    }
+
+    pub fn closed_trades(&self) -> &[ClosedTrade] {
+        &self.closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kalshi_api_spec::{market::MarketResult, order::Action, usd};
+
+    const TICKER: &str = "KXHIGHNY-25OCT17-B61.5";
+
+    fn time(hour: u32) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("2025-10-17T{:02}:00:00Z", hour))
+            .unwrap()
+            .to_utc()
+    }
+
+    fn fill(
+        id: &str,
+        side: Side,
+        action: Action,
+        count: u32,
+        cents: u32,
+        fee: Money,
+    ) -> LedgerEntry {
+        LedgerEntry::Fill(LedgerFill {
+            fill_id: id.to_string(),
+            ticker: TICKER.parse().unwrap(),
+            side,
+            action,
+            count,
+            price: Price::from_cents(cents).unwrap(),
+            fee,
+            time: time(14),
+        })
+    }
+
+    #[test]
+    fn test_fifo_and_settlement() {
+        let mut ledger = Ledger::default();
+        ledger.record(&fill("1", Side::Yes, Action::Buy, 10, 30, usd!(0.15)));
+        ledger.record(&fill("2", Side::Yes, Action::Buy, 10, 40, usd!(0.17)));
+        // Buying NO at 0.45 sells YES at 0.55
+        ledger.record(&fill("3", Side::No, Action::Buy, 15, 45, usd!(0.18)));
+
+        let ticker: MarketTicker = TICKER.parse().unwrap();
+        assert_eq!(ledger.positions()[&ticker], (Side::Yes, 5));
+        let closed = ledger.closed_trades();
+        assert_eq!(closed.len(), 2);
+        // 10 x (0.55 - 0.30) and 5 x (0.55 - 0.40)
+        assert_eq!(closed[0].proceeds - closed[0].cost, usd!(2.5));
+        assert_eq!(closed[1].proceeds - closed[1].cost, usd!(0.75));
+        assert_eq!(closed[0].fees, usd!(0.15) + usd!(0.12));
+
+        let book =
+            OrderBook::from_levels(ticker.clone(), &[(Price::from_cents(50).unwrap(), 10)], &[]);
+        ledger.mark(&book);
+        assert_eq!(ledger.unrealized(&ticker), usd!(0.5));
+        let realized = ledger.realized_by_market()[&ticker];
+        assert_eq!(realized.gross, usd!(3.25));
+        assert_eq!(realized.fees, usd!(0.5));
+        assert_eq!(ledger.daily_pnl(time(0).date_naive()), usd!(3.25));
+
+        ledger.record(&LedgerEntry::Settlement(LedgerSettlement {
+            ticker: ticker.clone(),
+            result: MarketResult::Yes,
+            yes_count: 5,
+            yes_cost: usd!(2),
+            no_count: 0,
+            no_cost: Money::ZERO,
+            revenue: usd!(5),
+            time: time(23),
+        }));
+        assert!(ledger.positions().is_empty());
+        assert_eq!(ledger.closed_trades()[2].pnl(), usd!(2.91));
+        let event = ticker.event();
+        assert_eq!(ledger.realized_by_event()[&event].net(), usd!(5.75));
+    }
+}
@@ -0,0 +1,223 @@
+use anyhow::{Result, bail};
+use chrono::Utc;
+use futures::TryStreamExt;
+use kalshi_api::{portfolio::PortfolioApiClient, store::JsonlStore};
+use kalshi_api_spec::portfolio::{Fill, GetFillsParams, GetSettlementsParams, Settlement};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    ledger::{
+        entry::{LedgerEntry, LedgerFill, LedgerSettlement},
+        pnl::Ledger,
+    },
+    order_engine::risk::RiskManager,
+};
+
+/// Least time between the reads feeding the daily loss limit
+const RISK_RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A ledger kept in `entries.jsonl` of a directory, rebuilt from it on
+/// restart
+pub struct LedgerStore {
+    path: PathBuf,
+    /// None when opened read-only
+    store: Option<JsonlStore<LedgerEntry>>,
+    ledger: Ledger,
+    /// Keys of the entries in `ledger`, the file may hold one twice
+    applied: HashSet<String>,
+    /// Latest entry of each kind, where the next sync starts from
+    last_fill: Option<i64>,
+    last_settlement: Option<i64>,
+    read_at: Option<Instant>,
+}
+
+impl LedgerStore {
+    /// Open the ledger to sync it. `ledger sync` should be the only writer.
+    pub async fn open(dir: &Path) -> Result<Self> {
+        let path = dir.join("entries.jsonl");
+        let store = JsonlStore::open(&path, LedgerEntry::key).await?;
+        let mut store = Self::new(path, Some(store));
+        store.reload().await?;
+        Ok(store)
+    }
+
+    /// Open the ledger to follow what `ledger sync` records, as strategies
+    /// do while it runs next to them
+    pub async fn open_read_only(dir: &Path) -> Result<Self> {
+        let mut store = Self::new(dir.join("entries.jsonl"), None);
+        store.reload().await?;
+        Ok(store)
+    }
+
+    fn new(path: PathBuf, store: Option<JsonlStore<LedgerEntry>>) -> Self {
+        Self {
+            path,
+            store,
+            ledger: Ledger::default(),
+            applied: HashSet::new(),
+            last_fill: None,
+            last_settlement: None,
+            read_at: None,
+        }
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    pub fn ledger_mut(&mut self) -> &mut Ledger {
+        &mut self.ledger
+    }
+
+    /// Apply the entries written since the last read. Returns how many
+    /// were new.
+    pub async fn reload(&mut self) -> Result<usize> {
+        let mut entries = JsonlStore::<LedgerEntry>::load(&self.path).await?;
+        entries.sort_by_key(LedgerEntry::time);
+        let mut new = 0;
+        for entry in &entries {
+            if self.apply(entry) {
+                new += 1;
+            }
+        }
+        self.read_at = Some(Instant::now());
+        Ok(new)
+    }
+
+    /// Apply `entry` unless it already was. Returns whether it was new.
+    fn apply(&mut self, entry: &LedgerEntry) -> bool {
+        if !self.applied.insert(entry.key()) {
+            return false;
+        }
+        let ts = Some(entry.time().timestamp());
+        match entry {
+            LedgerEntry::Fill(_) => self.last_fill = self.last_fill.max(ts),
+            LedgerEntry::Settlement(_) => self.last_settlement = self.last_settlement.max(ts),
+        }
+        self.ledger.record(entry);
+        true
+    }
+
+    /// Persist and apply `entry` unless it was already recorded. Returns
+    /// whether it was new.
+    pub async fn record(&mut self, entry: LedgerEntry) -> Result<bool> {
+        let Some(store) = &mut self.store else {
+            bail!("The ledger at {} is open read-only", self.path.display());
+        };
+        if !store.append(&entry).await? {
+            return Ok(false);
+        }
+        store.flush().await?;
+        Ok(self.apply(&entry))
+    }
+
+    /// Record the fills and settlements of the account since the last sync.
+    /// Returns how many entries were new.
+    pub async fn sync(&mut self, client: &PortfolioApiClient) -> Result<usize> {
+        // Timestamps are in seconds, entries of the last second are fetched
+        // again and skipped
+        let params = GetFillsParams {
+            min_ts: self.last_fill,
+            ..Default::default()
+        };
+        let fills: Vec<Fill> = client.fills(params).try_collect().await?;
+        let params = GetSettlementsParams {
+            min_ts: self.last_settlement,
+            ..Default::default()
+        };
+        let settlements: Vec<Settlement> = client.settlements(params).try_collect().await?;
+
+        let mut entries: Vec<LedgerEntry> = fills
+            .iter()
+            .map(|fill| LedgerEntry::Fill(LedgerFill::from(fill)))
+            .chain(
+                settlements
+                    .iter()
+                    .map(|settlement| LedgerEntry::Settlement(LedgerSettlement::from(settlement))),
+            )
+            .collect();
+        // Newest first from the API, the ledger needs them in order
+        entries.sort_by_key(LedgerEntry::time);
+
+        let mut new = 0;
+        for entry in entries {
+            if self.record(entry).await? {
+                new += 1;
+            }
+        }
+        Ok(new)
+    }
+
+    /// Hand today's PnL to `risk` for its daily loss limit, after reading
+    /// what `ledger sync` recorded when the last read is more than a minute
+    /// old
+    pub async fn update_risk(&mut self, risk: &mut RiskManager) -> Result<()> {
+        if self
+            .read_at
+            .is_none_or(|read_at| read_at.elapsed() >= RISK_RELOAD_INTERVAL)
+        {
+            self.reload().await?;
+        }
+        let today = Utc::now().date_naive();
+        risk.set_daily_pnl(today, self.ledger.daily_pnl(today));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::entry::LedgerFill;
+    use kalshi_api_spec::{
+        money::Price,
+        order::{Action, Side},
+        usd,
+    };
+    use tokio::fs;
+
+    fn fill(id: &str, cents: u32) -> LedgerEntry {
+        LedgerEntry::Fill(LedgerFill {
+            fill_id: id.to_string(),
+            ticker: "KXHIGHNY-25OCT17-B61.5".parse().unwrap(),
+            side: Side::Yes,
+            action: Action::Buy,
+            count: 10,
+            price: Price::from_cents(cents).unwrap(),
+            fee: usd!(0.1),
+            time: Utc::now(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_replays_each_entry_once() {
+        let dir = std::env::temp_dir().join(format!("ledger-store-{}", std::process::id()));
+        fs::create_dir_all(&dir).await.unwrap();
+        // Two writers that each only knew their own keys both appended it
+        let line = serde_json::to_string(&fill("1", 30)).unwrap();
+        fs::write(dir.join("entries.jsonl"), format!("{line}\n{line}\n"))
+            .await
+            .unwrap();
+
+        let mut reader = LedgerStore::open_read_only(&dir).await.unwrap();
+        let ticker = "KXHIGHNY-25OCT17-B61.5".parse().unwrap();
+        assert_eq!(reader.ledger().positions()[&ticker], (Side::Yes, 10));
+        assert!(reader.record(fill("2", 40)).await.is_err());
+
+        let mut writer = LedgerStore::open(&dir).await.unwrap();
+        assert_eq!(writer.ledger().positions()[&ticker], (Side::Yes, 10));
+        assert!(!writer.record(fill("1", 30)).await.unwrap());
+        assert!(writer.record(fill("2", 40)).await.unwrap());
+
+        assert_eq!(reader.reload().await.unwrap(), 1);
+        assert_eq!(reader.ledger().positions()[&ticker], (Side::Yes, 20));
+        fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
@@ -1,4 +1,6 @@
 pub mod datasource;
+pub mod kalshi;
+pub mod ledger;
 pub mod math;
 pub mod order_engine;
 pub mod strategy;
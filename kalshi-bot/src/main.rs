@@ -2,6 +2,7 @@ use anyhow::Result;
 use clap::{Parser, Subcommand};
 use kalshi_bot::{
     datasource::{DataSourceCommand, run_data_source},
+    ledger::{LedgerCommand, run_ledger},
     strategy::{StrategyCommand, run_strategy},
     system::{SystemCommand, start_system},
 };
@@ -17,6 +18,7 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     DataSource(DataSourceCommand),
+    Ledger(LedgerCommand),
     Strategy(StrategyCommand),
     System(SystemCommand),
     Telegram,
@@ -29,6 +31,7 @@ async fn main() -> Result<()> {
 
     match &cli.command {
         Commands::DataSource(subcommand) => run_data_source(subcommand).await?,
+        Commands::Ledger(subcommand) => run_ledger(subcommand).await?,
         Commands::Strategy(subcommand) => run_strategy(subcommand).await?,
         Commands::System(subcommand) => start_system(subcommand).await?,
         Commands::Telegram => {
@@ -0,0 +1,190 @@
+use kalshi_api_spec::{
+    fee::{Liquidity, total_cost},
+    money::{Money, Price},
+    order::Side,
+    orderbook::OrderBook,
+    ticker::MarketTicker,
+};
+
+use crate::math::{ladder::BucketLadder, stats::Bucket};
+
+/// Buying `count` contracts of `side` in every market of a mutually exclusive
+/// event at the best asks. Exactly one market settles YES, so a YES basket
+/// pays a dollar per contract, and a NO basket a dollar per contract for
+/// every market but one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Basket {
+    pub side: Side,
+    pub count: u32,
+    /// Best ask of each market
+    pub legs: Vec<(MarketTicker, Price)>,
+    /// What buying every leg costs, taker fees included
+    pub cost: Money,
+    pub payout: Money,
+}
+
+impl Basket {
+    /// Sum of the asks of one contract of every leg, before fees
+    pub fn asks(&self) -> Money {
+        self.legs.iter().map(|(_, price)| price.cost(1)).sum()
+    }
+
+    /// What the basket makes whatever the outcome
+    pub fn profit(&self) -> Money {
+        self.payout - self.cost
+    }
+}
+
+/// The basket of `side` at the best asks of `books`, which hold one book per
+/// market of the event. It is as big as the smallest ask, up to `max_count`,
+/// and there is none when a market has no ask.
+pub fn basket(books: &[OrderBook], side: Side, max_count: u32) -> Option<Basket> {
+    let asks = books
+        .iter()
+        .map(|book| Some((book.ticker().clone(), book.best_ask(side)?)))
+        .collect::<Option<Vec<_>>>()?;
+    let smallest = asks.iter().map(|(_, (_, size))| *size).min()?;
+    let count = smallest.clamp(0, max_count as i64) as u32;
+    if count == 0 {
+        return None;
+    }
+
+    let cost = asks
+        .iter()
+        .map(|(_, (price, _))| total_cost(Liquidity::Taker, count, *price))
+        .sum();
+    let winners = match side {
+        Side::Yes => 1,
+        Side::No => asks.len() as i64 - 1,
+    };
+    Some(Basket {
+        side,
+        count,
+        legs: asks
+            .into_iter()
+            .map(|(ticker, (price, _))| (ticker, price))
+            .collect(),
+        cost,
+        payout: Money::from_cents(100 * winners * count as i64),
+    })
+}
+
+/// A bucket that can be bought for less than buckets on both sides of it
+/// can be sold for, which no distribution with a single peak explains
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dip {
+    pub ticker: MarketTicker,
+    pub ask: Price,
+    /// Best YES bid among the colder buckets
+    pub colder: (MarketTicker, Price),
+    /// Best YES bid among the warmer buckets
+    pub warmer: (MarketTicker, Price),
+}
+
+/// Buckets whose YES ask is below a YES bid of a colder and of a warmer
+/// bucket. Only `Between` buckets are compared, the open ended ones gather
+/// whole tails and can be worth more than their neighbours.
+pub fn dips(ladder: &BucketLadder, books: &[OrderBook]) -> Vec<Dip> {
+    let rungs: Vec<&OrderBook> = ladder
+        .iter()
+        .filter(|(_, bucket)| matches!(bucket, Bucket::Between(..)))
+        .filter_map(|(ticker, _)| books.iter().find(|book| book.ticker() == ticker))
+        .collect();
+    let best_bid = |books: &[&OrderBook]| {
+        books
+            .iter()
+            .filter_map(|book| Some((book.ticker().clone(), book.best_bid(Side::Yes)?.0)))
+            .max_by_key(|(_, price)| *price)
+    };
+
+    let mut dips = vec![];
+    for i in 1..rungs.len().saturating_sub(1) {
+        let Some((ask, _)) = rungs[i].best_ask(Side::Yes) else {
+            continue;
+        };
+        if let (Some(colder), Some(warmer)) = (best_bid(&rungs[..i]), best_bid(&rungs[i + 1..]))
+            && colder.1 > ask
+            && warmer.1 > ask
+        {
+            dips.push(Dip {
+                ticker: rungs[i].ticker().clone(),
+                ask,
+                colder,
+                warmer,
+            });
+        }
+    }
+    dips
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ladder::test_utils::market;
+    use kalshi_api_spec::usd;
+
+    fn price(cents: u32) -> Price {
+        Price::from_cents(cents).unwrap()
+    }
+
+    /// Book of a market with a single YES bid and a single NO bid
+    fn book(suffix: &str, yes_bid: (u32, i64), no_bid: (u32, i64)) -> OrderBook {
+        OrderBook::from_levels(
+            format!("KXHIGHNY-25OCT17-{}", suffix).parse().unwrap(),
+            &[(price(yes_bid.0), yes_bid.1)],
+            &[(price(no_bid.0), no_bid.1)],
+        )
+    }
+
+    #[test]
+    fn test_baskets() {
+        // YES asks of 0.20, 0.30 and 0.40, NO asks of 0.85, 0.75 and 0.65
+        let books = [
+            book("T61", (15, 10), (80, 5)),
+            book("B61.5", (25, 10), (70, 10)),
+            book("T62", (35, 10), (60, 3)),
+        ];
+
+        let yes = basket(&books, Side::Yes, 10).unwrap();
+        assert_eq!(yes.count, 3);
+        assert_eq!(yes.asks(), usd!(0.9));
+        // Fees of 0.04, 0.05 and 0.06 for 3 contracts
+        assert_eq!(yes.cost, usd!(2.85));
+        assert_eq!(yes.profit(), usd!(0.15));
+
+        let no = basket(&books, Side::No, 5).unwrap();
+        assert_eq!(no.count, 5);
+        assert_eq!(no.payout, usd!(10));
+        assert!(no.profit() < Money::ZERO);
+
+        assert_eq!(basket(&books[..2], Side::Yes, 0), None);
+    }
+
+    #[test]
+    fn test_dips() {
+        let markets = [
+            market("T61", "less", None, Some(61.)),
+            market("B61.5", "between", Some(61.), Some(62.)),
+            market("B63.5", "between", Some(63.), Some(64.)),
+            market("B65.5", "between", Some(65.), Some(66.)),
+            market("T66", "greater", Some(66.), None),
+        ];
+        let ladder = BucketLadder::from_markets(&markets).unwrap();
+        let mut books = vec![
+            book("T61", (50, 10), (45, 10)),
+            book("B61.5", (30, 10), (65, 10)),
+            book("B63.5", (20, 10), (75, 10)),
+            book("B65.5", (28, 10), (70, 10)),
+            book("T66", (5, 10), (90, 10)),
+        ];
+        // B63.5 is asked at 0.25 while its neighbours are bid 0.30 and 0.28
+        let found = dips(&ladder, &books);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].ticker.to_string(), "KXHIGHNY-25OCT17-B63.5");
+        assert_eq!(found[0].ask, price(25));
+        assert_eq!(found[0].warmer.1, price(28));
+
+        books[2] = book("B63.5", (20, 10), (71, 10));
+        assert_eq!(dips(&ladder, &books), vec![]);
+    }
+}
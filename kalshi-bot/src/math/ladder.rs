@@ -0,0 +1,245 @@
+use anyhow::{Context, Result, bail};
+use kalshi_api_spec::{
+    event::EventResponse,
+    market::{Market, StrikeType},
+    ticker::MarketTicker,
+};
+
+use crate::math::stats::{Bucket, DailyMax};
+
+fn whole_degree(value: f64, ticker: &MarketTicker) -> Result<usize> {
+    if !value.is_finite() || value < 0.0 {
+        bail!("Strike {} of {} is out of range", value, ticker);
+    }
+    Ok(value as usize)
+}
+
+/// Bucket of whole degrees a market settles YES on. `Between` strikes are
+/// inclusive ("61° to 62°") while `Greater` and `Less` are strict ("above
+/// 62°") and their `OrEqual` variants are not. Rounding inwards handles
+/// half-degree strikes the same way.
+pub fn market_bucket(market: &Market) -> Result<Bucket> {
+    let ticker = market.ticker();
+    let floor = || {
+        market
+            .floor_strike()
+            .with_context(|| format!("{} has no floor strike", ticker))
+    };
+    let cap = || {
+        market
+            .cap_strike()
+            .with_context(|| format!("{} has no cap strike", ticker))
+    };
+
+    let bucket = match market.strike_type() {
+        StrikeType::Between => {
+            let start = whole_degree(floor()?.ceil(), ticker)?;
+            let stop = whole_degree(cap()?.floor(), ticker)?;
+            if start > stop {
+                bail!("{} covers no whole degree", ticker);
+            }
+            Bucket::Between(start, stop)
+        }
+        StrikeType::Greater => Bucket::Gte(whole_degree(floor()?.floor() + 1.0, ticker)?),
+        StrikeType::GreaterOrEqual => Bucket::Gte(whole_degree(floor()?.ceil(), ticker)?),
+        StrikeType::Less => {
+            let below = cap()?.ceil() - 1.0;
+            if below < 0.0 {
+                bail!("{} covers no whole degree", ticker);
+            }
+            Bucket::Lte(whole_degree(below, ticker)?)
+        }
+        StrikeType::LessOrEqual => Bucket::Lte(whole_degree(cap()?.floor(), ticker)?),
+        other => bail!("{} has unsupported strike type {:?}", ticker, other),
+    };
+    Ok(bucket)
+}
+
+/// Buckets of an event's markets, ordered from coldest to warmest. Exactly
+/// one market settles YES whatever the outcome.
+#[derive(Debug, Clone)]
+pub struct BucketLadder {
+    rungs: Vec<(MarketTicker, Bucket)>,
+}
+
+impl BucketLadder {
+    pub fn from_markets<'a>(markets: impl IntoIterator<Item = &'a Market>) -> Result<Self> {
+        let mut rungs = markets
+            .into_iter()
+            .map(|market| Ok((market.ticker().clone(), market_bucket(market)?)))
+            .collect::<Result<Vec<_>>>()?;
+        rungs.sort_by_key(|(_, bucket)| bucket.lower());
+
+        let ladder = Self { rungs };
+        ladder.validate()?;
+        Ok(ladder)
+    }
+
+    pub fn from_event(event: &EventResponse) -> Result<Self> {
+        Self::from_markets(event.markets())
+            .with_context(|| format!("Invalid ladder for {}", event.event().event_ticker()))
+    }
+
+    fn validate(&self) -> Result<()> {
+        let (Some((first, lowest)), Some((last, highest))) =
+            (self.rungs.first(), self.rungs.last())
+        else {
+            bail!("No markets");
+        };
+        if lowest.lower().is_some() {
+            bail!("Nothing covers temperatures below {}", first);
+        }
+        if highest.upper().is_some() {
+            bail!("Nothing covers temperatures above {}", last);
+        }
+
+        for pair in self.rungs.windows(2) {
+            let (ticker, bucket) = &pair[0];
+            let (next_ticker, next) = &pair[1];
+            match (bucket.upper(), next.lower()) {
+                (Some(upper), Some(lower)) if lower == upper + 1 => {}
+                (Some(upper), Some(lower)) if lower <= upper => {
+                    bail!("{} overlaps with {}", ticker, next_ticker)
+                }
+                (Some(_), Some(_)) => bail!("Gap between {} and {}", ticker, next_ticker),
+                _ => bail!("{} overlaps with {}", ticker, next_ticker),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(MarketTicker, Bucket)> {
+        self.rungs.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rungs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rungs.is_empty()
+    }
+
+    pub fn bucket(&self, ticker: &MarketTicker) -> Option<&Bucket> {
+        self.rungs
+            .iter()
+            .find(|(t, _)| t == ticker)
+            .map(|(_, bucket)| bucket)
+    }
+
+    /// Probability of each market settling YES given the distribution of the
+    /// day's highest temperature
+    pub fn probabilities(&self, max: &DailyMax) -> Vec<(MarketTicker, f64)> {
+        self.rungs
+            .iter()
+            .map(|(ticker, bucket)| (ticker.clone(), max.bucket_probability(bucket)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+pub mod test_utils {
+    use kalshi_api_spec::market::Market;
+    use serde_json::json;
+
+    /// Active market of the KXHIGHNY-25OCT17 event with the ticker ending in
+    /// `suffix`
+    pub fn market(suffix: &str, strike_type: &str, floor: Option<f64>, cap: Option<f64>) -> Market {
+        serde_json::from_value(json!({
+            "ticker": format!("KXHIGHNY-25OCT17-{}", suffix),
+            "event_ticker": "KXHIGHNY-25OCT17",
+            "title": "",
+            "status": "active",
+            "open_time": "2025-10-16T14:00:00Z",
+            "close_time": "2025-10-18T03:59:00Z",
+            "strike_type": strike_type,
+            "floor_strike": floor,
+            "cap_strike": cap,
+            "yes_bid_dollars": "0.10",
+            "yes_ask_dollars": "0.12",
+            "no_bid_dollars": "0.88",
+            "no_ask_dollars": "0.90",
+        }))
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ladder::test_utils::market;
+    use approx::assert_relative_eq;
+
+    fn event_markets() -> Vec<Market> {
+        // Shuffled on purpose, the ladder sorts them
+        vec![
+            market("B61.5", "between", Some(61.), Some(62.)),
+            market("T65", "greater", Some(64.), None),
+            market("T61", "less", None, Some(61.)),
+            market("B63.5", "between", Some(63.), Some(64.)),
+        ]
+    }
+
+    #[test]
+    fn test_strike_edges() {
+        let markets = event_markets();
+        let buckets: Vec<Bucket> = markets.iter().map(|m| market_bucket(m).unwrap()).collect();
+        assert_eq!(
+            buckets,
+            vec![
+                Bucket::Between(61, 62),
+                Bucket::Gte(65),
+                Bucket::Lte(60),
+                Bucket::Between(63, 64),
+            ]
+        );
+
+        let half = market("B61.5", "between", Some(60.5), Some(62.5));
+        assert_eq!(market_bucket(&half).unwrap(), Bucket::Between(61, 62));
+        let half = market("T62", "greater", Some(62.5), None);
+        assert_eq!(market_bucket(&half).unwrap(), Bucket::Gte(63));
+        let half = market("T60", "less", None, Some(60.5));
+        assert_eq!(market_bucket(&half).unwrap(), Bucket::Lte(60));
+    }
+
+    #[test]
+    fn test_ladder_is_exhaustive() {
+        let markets = event_markets();
+        let ladder = BucketLadder::from_markets(&markets).unwrap();
+        let tickers: Vec<String> = ladder.iter().map(|(t, _)| t.to_string()).collect();
+        assert_eq!(
+            tickers,
+            vec![
+                "KXHIGHNY-25OCT17-T61",
+                "KXHIGHNY-25OCT17-B61.5",
+                "KXHIGHNY-25OCT17-B63.5",
+                "KXHIGHNY-25OCT17-T65",
+            ]
+        );
+
+        let total: f64 = ladder
+            .probabilities(&DailyMax::forecast(62.3, 2.7))
+            .iter()
+            .map(|(_, p)| p)
+            .sum();
+        assert_relative_eq!(total, 1.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_invalid_ladders() {
+        let mut markets = event_markets();
+        markets.remove(1);
+        let error = BucketLadder::from_markets(&markets).unwrap_err();
+        assert!(error.to_string().contains("above"));
+
+        let mut markets = event_markets();
+        markets.remove(3);
+        let error = BucketLadder::from_markets(&markets).unwrap_err();
+        assert!(error.to_string().contains("Gap"));
+
+        let mut markets = event_markets();
+        markets.push(market("B62.5", "between", Some(62.), Some(63.)));
+        let error = BucketLadder::from_markets(&markets).unwrap_err();
+        assert!(error.to_string().contains("overlaps"));
+    }
+}
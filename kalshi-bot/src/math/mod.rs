@@ -1 +1,4 @@
+pub mod arbitrage;
+pub mod ladder;
+pub mod sizing;
 pub mod stats;
@@ -0,0 +1,299 @@
+use anyhow::{Result, bail};
+use kalshi_api_spec::{
+    fee::{Liquidity, fee},
+    money::{Money, Price},
+    order::Side,
+    ticker::MarketTicker,
+};
+
+use crate::order_engine::intent::Intent;
+
+/// A market of a ladder with the model's probability of it settling YES and
+/// the prices it can be bought at now
+#[derive(Debug, Clone)]
+pub struct LadderQuote {
+    pub ticker: MarketTicker,
+    pub probability: f64,
+    pub yes_ask: Option<Price>,
+    pub no_ask: Option<Price>,
+}
+
+fn dollars(price: Price) -> f64 {
+    price.cents() as f64 / 100.0
+}
+
+fn to_price(probability: f64) -> Price {
+    let cents = (probability * 100.0).round().clamp(1.0, 99.0);
+    Price::from_cents(cents as u32).unwrap()
+}
+
+/// A contract that can be bought, paying a dollar in the outcomes it wins
+struct Bet {
+    quote: usize,
+    side: Side,
+    price: Price,
+    /// Price plus fee per contract
+    cost: f64,
+    cap: f64,
+}
+
+impl Bet {
+    fn pays(&self, outcome: usize) -> f64 {
+        match self.side {
+            Side::Yes if outcome == self.quote => 1.0,
+            Side::No if outcome != self.quote => 1.0,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Sizes positions across all the markets of a ladder at once, maximizing
+/// the expected log of the bankroll over which bucket settles YES. Sizing
+/// each market on its own would ignore that exactly one of them wins, and
+/// overbet when several look cheap.
+#[derive(Debug, Clone)]
+pub struct KellySizer {
+    /// Share of the Kelly stakes to bet, half Kelly by default
+    fraction: f64,
+    max_contracts: u32,
+    liquidity: Liquidity,
+}
+
+impl Default for KellySizer {
+    fn default() -> Self {
+        Self {
+            fraction: 0.5,
+            max_contracts: 100,
+            liquidity: Liquidity::Taker,
+        }
+    }
+}
+
+impl KellySizer {
+    pub fn with_fraction(mut self, fraction: f64) -> Self {
+        self.fraction = fraction;
+        self
+    }
+
+    pub fn with_max_contracts(mut self, max_contracts: u32) -> Self {
+        self.max_contracts = max_contracts;
+        self
+    }
+
+    /// Fees of resting orders are lower than those of orders taking the asks
+    pub fn with_liquidity(mut self, liquidity: Liquidity) -> Self {
+        self.liquidity = liquidity;
+        self
+    }
+
+    /// Fee per contract, averaged over 100 contracts so that rounding up to
+    /// the cent doesn't dominate
+    fn fee_per_contract(&self, price: Price) -> f64 {
+        fee(self.liquidity, 100, price).cents() as f64 / 10_000.0
+    }
+
+    /// Contracts per dollar of bankroll to buy of each bet, at full Kelly
+    fn solve(&self, probabilities: &[f64], bets: &[Bet]) -> Vec<f64> {
+        let mut stakes = vec![0.0; bets.len()];
+        let wealth = |stakes: &[f64], outcome: usize| {
+            1.0 + bets
+                .iter()
+                .zip(stakes)
+                .map(|(bet, stake)| stake * (bet.pays(outcome) - bet.cost))
+                .sum::<f64>()
+        };
+
+        // Coordinate ascent, each step maximizing the concave objective along
+        // one bet by bisecting its derivative
+        for _ in 0..200 {
+            let mut moved = 0.0f64;
+            for k in 0..bets.len() {
+                let bet = &bets[k];
+                let mut others = stakes.clone();
+                others[k] = 0.0;
+                let base: Vec<f64> = (0..probabilities.len())
+                    .map(|outcome| wealth(&others, outcome))
+                    .collect();
+                let slope = |stake: f64| -> f64 {
+                    probabilities
+                        .iter()
+                        .enumerate()
+                        .map(|(outcome, p)| {
+                            let gain = bet.pays(outcome) - bet.cost;
+                            p * gain / (base[outcome] + stake * gain)
+                        })
+                        .sum()
+                };
+
+                // Never bet so much that an outcome leaves nothing
+                let mut high = bet.cap;
+                for (outcome, w) in base.iter().enumerate() {
+                    let gain = bet.pays(outcome) - bet.cost;
+                    if gain < 0.0 {
+                        high = high.min(0.999 * w / -gain);
+                    }
+                }
+
+                let stake = if slope(0.0) <= 0.0 {
+                    0.0
+                } else if slope(high) >= 0.0 {
+                    high
+                } else {
+                    let (mut low, mut high) = (0.0, high);
+                    for _ in 0..60 {
+                        let mid = (low + high) / 2.0;
+                        if slope(mid) > 0.0 {
+                            low = mid;
+                        } else {
+                            high = mid;
+                        }
+                    }
+                    low
+                };
+                moved = moved.max((stake - stakes[k]).abs());
+                stakes[k] = stake;
+            }
+            if moved < 1e-9 {
+                break;
+            }
+        }
+        stakes
+    }
+
+    /// Target positions for every market of the ladder. Markets not worth
+    /// holding get a target of zero, priced at the model's fair value, so
+    /// that the order engine exits what is held there.
+    pub fn size(&self, quotes: &[LadderQuote], bankroll: Money) -> Result<Vec<Intent>> {
+        let total: f64 = quotes.iter().map(|quote| quote.probability).sum();
+        if quotes.is_empty() || (total - 1.0).abs() > 0.01 {
+            bail!("Probabilities of the ladder add up to {}", total);
+        }
+        let bankroll = bankroll.cents() as f64 / 100.0;
+        if bankroll <= 0.0 {
+            bail!("No bankroll to bet");
+        }
+        let probabilities: Vec<f64> = quotes
+            .iter()
+            .map(|quote| quote.probability / total)
+            .collect();
+
+        // Full Kelly stakes are scaled down afterwards, so cap them higher
+        let cap = self.max_contracts as f64 / self.fraction / bankroll;
+        let bets: Vec<Bet> = quotes
+            .iter()
+            .enumerate()
+            .flat_map(|(i, quote)| {
+                [(Side::Yes, quote.yes_ask), (Side::No, quote.no_ask)]
+                    .into_iter()
+                    .filter_map(move |(side, ask)| Some((i, side, ask?)))
+            })
+            .map(|(quote, side, price)| Bet {
+                quote,
+                side,
+                price,
+                cost: dollars(price) + self.fee_per_contract(price),
+                cap,
+            })
+            .collect();
+        let stakes = self.solve(&probabilities, &bets);
+
+        let mut intents = vec![];
+        for (i, quote) in quotes.iter().enumerate() {
+            let bought = bets
+                .iter()
+                .zip(&stakes)
+                .filter(|(bet, _)| bet.quote == i)
+                .map(|(bet, stake)| {
+                    let count = (stake * self.fraction * bankroll).floor() as u32;
+                    (bet, count.min(self.max_contracts))
+                })
+                .filter(|(_, count)| *count > 0)
+                .max_by_key(|(_, count)| *count);
+
+            let yes_value = to_price(probabilities[i]);
+            let intent = match bought {
+                Some((bet, count)) => {
+                    let fair_value = match bet.side {
+                        Side::Yes => yes_value,
+                        Side::No => yes_value.complement(),
+                    };
+                    Intent::hold(quote.ticker.clone(), bet.side, count, bet.price)
+                        .with_fair_value(fair_value)
+                }
+                None => Intent::close(quote.ticker.clone(), Side::Yes, yes_value)
+                    .with_fair_value(yes_value),
+            };
+            intents.push(intent);
+        }
+        Ok(intents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kalshi_api_spec::usd;
+
+    fn quote(suffix: &str, probability: f64, yes_ask: u32, no_ask: u32) -> LadderQuote {
+        LadderQuote {
+            ticker: format!("KXHIGHNY-25OCT17-{}", suffix).parse().unwrap(),
+            probability,
+            yes_ask: Some(Price::from_cents(yes_ask).unwrap()),
+            no_ask: Some(Price::from_cents(no_ask).unwrap()),
+        }
+    }
+
+    fn targets(intents: &[Intent]) -> Vec<(Side, u32)> {
+        intents
+            .iter()
+            .map(|intent| (intent.side(), intent.target()))
+            .collect()
+    }
+
+    #[test]
+    fn test_no_edge_no_bet() {
+        let quotes = [
+            quote("T61", 0.2, 21, 81),
+            quote("B61.5", 0.5, 51, 51),
+            quote("T63", 0.3, 31, 71),
+        ];
+        let intents = KellySizer::default().size(&quotes, usd!(100)).unwrap();
+        assert_eq!(targets(&intents), vec![(Side::Yes, 0); 3]);
+        assert_eq!(intents[1].price(), Price::from_cents(50).unwrap());
+    }
+
+    #[test]
+    fn test_sizes_the_ladder_jointly() {
+        let quotes = [
+            quote("T61", 0.1, 20, 82),
+            quote("B61.5", 0.6, 40, 62),
+            quote("T63", 0.3, 42, 60),
+        ];
+        let sizer = KellySizer::default().with_fraction(1.0);
+        let full = sizer.clone().size(&quotes, usd!(100)).unwrap();
+        let full = targets(&full);
+        assert_eq!(full[0].0, Side::No);
+        assert_eq!(full[1].0, Side::Yes);
+        assert!(full[0].1 > 0 && full[1].1 > 0);
+        // NO of T63 costs 0.63 with fees for a 0.70 chance, but mostly pays
+        // when the favourite wins, which its YES contracts already bet on
+        assert_eq!(full[2].1, 0);
+
+        let half = targets(
+            &sizer
+                .clone()
+                .with_fraction(0.5)
+                .size(&quotes, usd!(100))
+                .unwrap(),
+        );
+        assert!(half[1].1 * 2 <= full[1].1 + 1);
+
+        let capped = targets(
+            &sizer
+                .with_max_contracts(10)
+                .size(&quotes, usd!(100))
+                .unwrap(),
+        );
+        assert!(capped.iter().all(|(_, count)| *count <= 10));
+    }
+}
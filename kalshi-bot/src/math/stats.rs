@@ -1,29 +1,114 @@
 use statrs::distribution::{ContinuousCDF, Normal};
 use std::f64;
 
+/// Range of whole degrees, inclusive on both ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Bucket {
     Lte(usize),
     Between(usize, usize),
     Gte(usize),
 }
 
-pub fn bucket_probability(bucket: &Bucket, mu: f64, sigma: f64) -> f64 {
-    let normal = Normal::new(mu, sigma).unwrap();
-    match bucket {
-        Bucket::Lte(lt) => normal.cdf(*lt as f64 + 1.0 - f64::EPSILON),
-        Bucket::Between(start, stop) => {
-            normal.cdf(*stop as f64 + 1.0 - f64::EPSILON) - normal.cdf(*start as f64)
+impl Bucket {
+    /// Lowest value in the bucket, `None` if unbounded
+    pub fn lower(&self) -> Option<usize> {
+        match self {
+            Bucket::Lte(_) => None,
+            Bucket::Between(start, _) => Some(*start),
+            Bucket::Gte(gt) => Some(*gt),
+        }
+    }
+
+    /// Highest value in the bucket, `None` if unbounded
+    pub fn upper(&self) -> Option<usize> {
+        match self {
+            Bucket::Lte(lt) => Some(*lt),
+            Bucket::Between(_, stop) => Some(*stop),
+            Bucket::Gte(_) => None,
         }
-        Bucket::Gte(gt) => 1.0 - normal.cdf(*gt as f64),
+    }
+
+    /// Whether the bucket can still settle YES once the day reached
+    /// `observed_max`
+    pub fn can_win(&self, observed_max: f64) -> bool {
+        self.upper()
+            .is_none_or(|upper| observed_max < upper as f64 + 1.0)
+    }
+
+    /// Whether the bucket settles YES whatever the rest of the day brings
+    pub fn is_certain(&self, observed_max: f64) -> bool {
+        self.upper().is_none()
+            && self
+                .lower()
+                .is_some_and(|lower| observed_max >= lower as f64)
     }
 }
 
-pub fn bucket_probabilities(buckets: Vec<Bucket>, mu: f64, sigma: f64) -> Vec<f64> {
-    let mut probs = Vec::with_capacity(buckets.len());
-    for bucket in buckets {
-        probs.push(bucket_probability(&bucket, mu, sigma));
+/// Highest temperature of a day: the highest of what was observed so far and
+/// of the rest of the day, forecast as normally distributed. Late in the day
+/// the observed maximum dominates and the buckets below it can't win.
+#[derive(Debug, Clone, Copy)]
+pub struct DailyMax {
+    remaining: Option<Normal>,
+    observed: Option<f64>,
+}
+
+impl DailyMax {
+    /// Forecast highest temperature of the rest of the day
+    pub fn forecast(mu: f64, sigma: f64) -> Self {
+        Self {
+            remaining: Some(Normal::new(mu, sigma).unwrap()),
+            observed: None,
+        }
+    }
+
+    /// Nothing left to forecast, the day's highest was observed
+    pub fn observed(observed: f64) -> Self {
+        Self {
+            remaining: None,
+            observed: Some(observed),
+        }
+    }
+
+    /// Highest temperature observed so far today
+    pub fn with_observed(mut self, observed: f64) -> Self {
+        self.observed = Some(self.observed.map_or(observed, |o| o.max(observed)));
+        self
     }
-    probs
+
+    /// Probability of the day's highest temperature being below `x`
+    pub fn cdf(&self, x: f64) -> f64 {
+        if self.observed.is_some_and(|observed| observed >= x) {
+            return 0.0;
+        }
+        self.remaining.map_or(1.0, |normal| normal.cdf(x))
+    }
+
+    pub fn bucket_probability(&self, bucket: &Bucket) -> f64 {
+        match bucket {
+            Bucket::Lte(lt) => self.cdf(*lt as f64 + 1.0 - f64::EPSILON),
+            Bucket::Between(start, stop) => {
+                self.cdf(*stop as f64 + 1.0 - f64::EPSILON) - self.cdf(*start as f64)
+            }
+            Bucket::Gte(gt) => 1.0 - self.cdf(*gt as f64),
+        }
+    }
+}
+
+pub fn bucket_probability(bucket: &Bucket, mu: f64, sigma: f64) -> f64 {
+    DailyMax::forecast(mu, sigma).bucket_probability(bucket)
+}
+
+pub fn bucket_probabilities(buckets: Vec<Bucket>, mu: f64, sigma: f64) -> Vec<f64> {
+    conditional_bucket_probabilities(buckets, &DailyMax::forecast(mu, sigma))
+}
+
+/// Probability of each bucket given what was observed so far, see `DailyMax`
+pub fn conditional_bucket_probabilities(buckets: Vec<Bucket>, max: &DailyMax) -> Vec<f64> {
+    buckets
+        .iter()
+        .map(|bucket| max.bucket_probability(bucket))
+        .collect()
 }
 
 #[cfg(test)]
@@ -90,4 +175,27 @@ mod tests {
         assert_relative_eq!(probs[3] * 100.0, 0., epsilon = 1e-2);
         assert_relative_eq!(probs[4] * 100.0, 0., epsilon = 1e-2);
     }
+
+    #[test]
+    fn test_observed_max() {
+        let buckets = vec![
+            Bucket::Lte(60),
+            Bucket::Between(61, 62),
+            Bucket::Between(63, 64),
+            Bucket::Between(65, 66),
+            Bucket::Gte(67),
+        ];
+        // The forecast for the rest of the day is outdated by a warmer
+        // observation
+        let max = DailyMax::forecast(62.0, 1.5).with_observed(63.0);
+        let probs = conditional_bucket_probabilities(buckets.clone(), &max);
+        assert_eq!(probs[0], 0.0);
+        assert_eq!(probs[1], 0.0);
+        // Whatever the rest of the day brings under 65F
+        assert_relative_eq!(probs[2], bucket_probability(&Bucket::Lte(64), 62.0, 1.5));
+        assert_relative_eq!(probs.iter().sum::<f64>(), 1.0, epsilon = 1e-6);
+
+        let probs = conditional_bucket_probabilities(buckets, &DailyMax::observed(65.0));
+        assert_eq!(probs, vec![0.0, 0.0, 0.0, 1.0, 0.0]);
+    }
 }
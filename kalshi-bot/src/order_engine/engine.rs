@@ -0,0 +1,762 @@
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use kalshi_api_spec::{
+    money::Price,
+    order::{Action, AmendOrderRequest, ClientOrderId, CreateOrderRequest, Order, OrderId, Side},
+    ticker::MarketTicker,
+};
+use protocol::protocol::{Event, ServiceName, ServicePublisher};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, time::Instant};
+
+use crate::order_engine::{
+    gateway::{OrderGateway, is_rejection},
+    intent::Intent,
+    order::{OrderState, TrackedOrder},
+    risk::RiskManager,
+};
+
+/// Contracts of an order filled since the previous update. The price is the
+/// order's limit price, the exchange may have filled at a better one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderFill {
+    pub client_order_id: ClientOrderId,
+    pub order_id: Option<OrderId>,
+    pub ticker: MarketTicker,
+    pub side: Side,
+    pub action: Action,
+    pub count: u32,
+    pub price: Price,
+}
+
+/// Published on `ServiceName::OrderEngine`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum OrderEngineEvent {
+    Order(TrackedOrder),
+    Fill(OrderFill),
+}
+
+/// Turns the intents of strategies into orders and follows those orders
+/// until they are filled, cancelled or rejected
+pub struct OrderEngine<G> {
+    gateway: G,
+    /// Starts the client order id of every order of this engine
+    prefix: String,
+    orders: HashMap<ClientOrderId, TrackedOrder>,
+    /// Net YES contracts per market, negative when holding NO
+    positions: HashMap<MarketTicker, i64>,
+    publisher: Option<ServicePublisher<OrderEngineEvent>>,
+    event_id: u32,
+    risk: Option<RiskManager>,
+    /// Whether the resting orders were cancelled after the kill switch
+    killed: bool,
+}
+
+impl<G: OrderGateway> OrderEngine<G> {
+    pub fn new(gateway: G, prefix: &str) -> Self {
+        Self {
+            gateway,
+            prefix: prefix.to_string(),
+            orders: HashMap::new(),
+            positions: HashMap::new(),
+            publisher: None,
+            event_id: 0,
+            risk: None,
+            killed: false,
+        }
+    }
+
+    /// Check every intent and order with `risk` first
+    pub fn with_risk(mut self, risk: RiskManager) -> Self {
+        self.risk = Some(risk);
+        self
+    }
+
+    pub fn risk_mut(&mut self) -> Option<&mut RiskManager> {
+        self.risk.as_mut()
+    }
+
+    /// Publish order and fill events for other services to subscribe to
+    pub async fn with_publisher(mut self) -> Result<Self> {
+        self.publisher = Some(ServicePublisher::new(ServiceName::OrderEngine).await?);
+        Ok(self)
+    }
+
+    pub fn gateway(&self) -> &G {
+        &self.gateway
+    }
+
+    pub fn order(&self, client_order_id: &ClientOrderId) -> Option<&TrackedOrder> {
+        self.orders.get(client_order_id)
+    }
+
+    pub fn orders(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders.values()
+    }
+
+    /// Contracts of `side` held in `ticker`, negative when holding the other
+    /// side
+    pub fn position(&self, ticker: &MarketTicker, side: Side) -> i64 {
+        let yes = self.positions.get(ticker).copied().unwrap_or_default();
+        match side {
+            Side::Yes => yes,
+            Side::No => -yes,
+        }
+    }
+
+    /// Rebuild the state of the engine from the exchange after a restart:
+    /// positions, and the resting orders with this engine's prefix
+    pub async fn reconcile(&mut self) -> Result<()> {
+        self.positions = self
+            .gateway
+            .positions()
+            .await?
+            .into_iter()
+            .map(|position| (position.ticker().clone(), position.position()))
+            .collect();
+
+        for order in self.gateway.open_orders().await? {
+            let client_order_id = order.client_order_id();
+            if !client_order_id.has_prefix(&self.prefix)
+                || self.orders.contains_key(client_order_id)
+            {
+                continue;
+            }
+            let tracked = TrackedOrder::adopt(order);
+            println!(
+                "Resuming order {} on {}",
+                tracked.client_order_id(),
+                tracked.ticker()
+            );
+            self.orders
+                .insert(tracked.client_order_id().clone(), tracked.clone());
+            self.publish(OrderEngineEvent::Order(tracked)).await;
+        }
+
+        self.refresh().await
+    }
+
+    /// Create, amend or cancel orders so that the position of the intent's
+    /// market and side moves to its target
+    pub async fn submit(&mut self, intent: &Intent) -> Result<()> {
+        self.enforce_kill_switch().await;
+        let held = self.position(intent.ticker(), intent.side());
+        if let Some(risk) = &mut self.risk {
+            risk.check(intent, held, Utc::now())
+                .with_context(|| format!("Risk check refused {:?}", intent))?;
+        }
+
+        let working: Vec<TrackedOrder> = self
+            .orders
+            .values()
+            .filter(|order| {
+                !order.state().is_terminal()
+                    && order.ticker() == intent.ticker()
+                    && order.side() == intent.side()
+            })
+            .cloned()
+            .collect();
+        if let Some(order) = working.iter().find(|order| order.order_id().is_none()) {
+            eprintln!(
+                "Order {} on {} has no answer from the exchange yet, ignoring {:?}",
+                order.client_order_id(),
+                order.ticker(),
+                intent
+            );
+            return Ok(());
+        }
+
+        let mut wanted = intent.order_for(held);
+        for order in working {
+            match wanted {
+                Some((action, count)) if order.action() == action => {
+                    if order.price() != intent.price() || order.remaining() != count {
+                        self.amend(order.client_order_id(), count, intent.price())
+                            .await?;
+                    }
+                    wanted = None;
+                }
+                _ => self.cancel(order.client_order_id()).await?,
+            }
+        }
+
+        if let Some((action, count)) = wanted {
+            self.create(
+                intent.ticker().clone(),
+                intent.side(),
+                action,
+                count,
+                intent.price(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Submit intents that only make sense together, like the legs of a
+    /// basket. They are all checked against the risk limits first, and if one
+    /// fails anyway the working orders of the ones submitted are cancelled.
+    pub async fn submit_all(&mut self, intents: &[Intent]) -> Result<()> {
+        self.enforce_kill_switch().await;
+        if let Some(risk) = &self.risk {
+            let checked: Vec<(&Intent, i64)> = intents
+                .iter()
+                .map(|intent| (intent, self.position(intent.ticker(), intent.side())))
+                .collect();
+            risk.check_all(&checked, Utc::now(), Instant::now())
+                .context("Risk check refused the intents")?;
+        }
+
+        for (i, intent) in intents.iter().enumerate() {
+            if let Err(e) = self.submit(intent).await {
+                for submitted in &intents[..=i] {
+                    self.cancel_working(submitted.ticker(), submitted.side())
+                        .await;
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Catch up with the exchange on every order still working, and forget
+    /// the ones that were done by the previous refresh
+    pub async fn refresh(&mut self) -> Result<()> {
+        self.enforce_kill_switch().await;
+        self.orders.retain(|_, order| !order.state().is_terminal());
+        let client_order_ids: Vec<ClientOrderId> = self.orders.keys().cloned().collect();
+        for client_order_id in client_order_ids {
+            if let Err(e) = self.refresh_one(&client_order_id).await {
+                eprintln!("Failed to refresh order {}: {:?}", client_order_id, e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn refresh_one(&mut self, client_order_id: &ClientOrderId) -> Result<()> {
+        let tracked = &self.orders[client_order_id];
+        let order = match tracked.order_id() {
+            Some(order_id) => self.gateway.get_order(order_id).await?,
+            None => match self
+                .gateway
+                .find_order(tracked.ticker(), client_order_id)
+                .await?
+            {
+                Some(order) => order,
+                None if self.killed => {
+                    let tracked = self
+                        .orders
+                        .get_mut(client_order_id)
+                        .expect("Refreshed order is tracked");
+                    tracked.reject("Kill switch is on".to_string())?;
+                    let tracked = tracked.clone();
+                    self.publish(OrderEngineEvent::Order(tracked)).await;
+                    return Ok(());
+                }
+                // The create request never made it, the same id makes sure
+                // sending it again can't place it twice
+                None => return self.send(client_order_id).await,
+            },
+        };
+        self.update(client_order_id, order).await
+    }
+
+    pub async fn cancel(&mut self, client_order_id: &ClientOrderId) -> Result<()> {
+        let order_id = self
+            .orders
+            .get(client_order_id)
+            .and_then(|order| order.order_id())
+            .cloned()
+            .with_context(|| format!("Order {} isn't on the exchange", client_order_id))?;
+        let order = self.gateway.cancel_order(&order_id).await?;
+        self.update(client_order_id, order).await
+    }
+
+    /// Cancel every working order, carrying on past failures
+    pub async fn cancel_all(&mut self) {
+        let working: Vec<ClientOrderId> = self
+            .orders
+            .values()
+            .filter(|order| !order.state().is_terminal() && order.order_id().is_some())
+            .map(|order| order.client_order_id().clone())
+            .collect();
+        for client_order_id in working {
+            if let Err(e) = self.cancel(&client_order_id).await {
+                eprintln!("Failed to cancel order {}: {:?}", client_order_id, e);
+            }
+        }
+    }
+
+    /// Cancel the working orders of a market and side, carrying on past
+    /// failures
+    async fn cancel_working(&mut self, ticker: &MarketTicker, side: Side) {
+        let working: Vec<ClientOrderId> = self
+            .orders
+            .values()
+            .filter(|order| {
+                !order.state().is_terminal()
+                    && order.order_id().is_some()
+                    && order.ticker() == ticker
+                    && order.side() == side
+            })
+            .map(|order| order.client_order_id().clone())
+            .collect();
+        for client_order_id in working {
+            if let Err(e) = self.cancel(&client_order_id).await {
+                eprintln!("Failed to cancel order {}: {:?}", client_order_id, e);
+            }
+        }
+    }
+
+    async fn enforce_kill_switch(&mut self) {
+        let triggered = self
+            .risk
+            .as_ref()
+            .is_some_and(|risk| risk.kill_switch().is_triggered());
+        if triggered && !self.killed {
+            println!("Kill switch is on, cancelling every order");
+            self.cancel_all().await;
+            self.killed = true;
+        }
+    }
+
+    fn take_order_slot(&mut self) -> Result<()> {
+        match &mut self.risk {
+            Some(risk) => risk.take_order_slot(Instant::now()),
+            None => Ok(()),
+        }
+    }
+
+    async fn create(
+        &mut self,
+        ticker: MarketTicker,
+        side: Side,
+        action: Action,
+        count: u32,
+        price: Price,
+    ) -> Result<()> {
+        self.take_order_slot()?;
+        let client_order_id = ClientOrderId::with_prefix(&self.prefix);
+        let tracked =
+            TrackedOrder::pending(client_order_id.clone(), ticker, side, action, count, price);
+        self.orders.insert(client_order_id.clone(), tracked.clone());
+        self.publish(OrderEngineEvent::Order(tracked)).await;
+        self.send(&client_order_id).await
+    }
+
+    async fn send(&mut self, client_order_id: &ClientOrderId) -> Result<()> {
+        let tracked = &self.orders[client_order_id];
+        let request = CreateOrderRequest::limit(
+            tracked.ticker().clone(),
+            tracked.side(),
+            tracked.action(),
+            tracked.count(),
+            tracked.price(),
+        )
+        .with_client_order_id(client_order_id.clone());
+
+        match self.gateway.create_order(&request).await {
+            Ok(order) => self.update(client_order_id, order).await,
+            Err(e) if is_rejection(&e) => {
+                let tracked = self
+                    .orders
+                    .get_mut(client_order_id)
+                    .expect("Order being sent is tracked");
+                tracked.reject(format!("{:#}", e))?;
+                let tracked = tracked.clone();
+                eprintln!("Order {} rejected: {:#}", client_order_id, e);
+                self.publish(OrderEngineEvent::Order(tracked)).await;
+                Ok(())
+            }
+            Err(e) => {
+                eprintln!(
+                    "No answer for order {}, checking again on the next refresh: {:?}",
+                    client_order_id, e
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Move a working order to `count` more contracts at `price`
+    async fn amend(
+        &mut self,
+        client_order_id: &ClientOrderId,
+        count: u32,
+        price: Price,
+    ) -> Result<()> {
+        self.take_order_slot()?;
+        let tracked = &self.orders[client_order_id];
+        let (Some(order_id), Some(order)) = (tracked.order_id(), tracked.exchange_order()) else {
+            bail!("Order {} isn't on the exchange", client_order_id);
+        };
+        let request = AmendOrderRequest::new(order, tracked.filled() + count, price)
+            .with_updated_client_order_id(ClientOrderId::with_prefix(&self.prefix));
+        let response = self.gateway.amend_order(order_id, &request).await?;
+        self.update(client_order_id, response.order).await
+    }
+
+    /// Apply the exchange's copy of an order, publishing what changed
+    async fn update(&mut self, client_order_id: &ClientOrderId, order: Order) -> Result<()> {
+        let tracked = self
+            .orders
+            .get_mut(client_order_id)
+            .with_context(|| format!("Unknown order {}", client_order_id))?;
+        let before = (tracked.state(), tracked.count(), tracked.price());
+        let new_fills = tracked.apply(order)?;
+        let tracked = tracked.clone();
+        if tracked.client_order_id() != client_order_id {
+            self.orders.remove(client_order_id);
+            self.orders
+                .insert(tracked.client_order_id().clone(), tracked.clone());
+        }
+
+        if new_fills > 0 {
+            let change = match (tracked.side(), tracked.action()) {
+                (Side::Yes, Action::Buy) | (Side::No, Action::Sell) => new_fills as i64,
+                (Side::Yes, Action::Sell) | (Side::No, Action::Buy) => -(new_fills as i64),
+            };
+            *self.positions.entry(tracked.ticker().clone()).or_default() += change;
+            let fill = OrderFill {
+                client_order_id: tracked.client_order_id().clone(),
+                order_id: tracked.order_id().cloned(),
+                ticker: tracked.ticker().clone(),
+                side: tracked.side(),
+                action: tracked.action(),
+                count: new_fills,
+                price: tracked.price(),
+            };
+            self.publish(OrderEngineEvent::Fill(fill)).await;
+        }
+
+        let after = (tracked.state(), tracked.count(), tracked.price());
+        if new_fills > 0 || before != after {
+            if before.0 != after.0 && after.0 != OrderState::PartiallyFilled {
+                println!(
+                    "Order {} to {} {} {} {} at {} is {}",
+                    tracked.client_order_id(),
+                    tracked.action(),
+                    tracked.count(),
+                    tracked.side(),
+                    tracked.ticker(),
+                    tracked.price(),
+                    tracked.state()
+                );
+            }
+            self.publish(OrderEngineEvent::Order(tracked)).await;
+        }
+        Ok(())
+    }
+
+    async fn publish(&mut self, event: OrderEngineEvent) {
+        if let Some(publisher) = &mut self.publisher {
+            if let Err(e) = publisher.publish(Event::new(self.event_id, event)).await {
+                eprintln!("Failed to publish order engine event: {:?}", e);
+            }
+            self.event_id = self.event_id.wrapping_add(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_engine::risk::RiskLimits;
+    use async_trait::async_trait;
+    use kalshi_api_spec::{money::Money, order::AmendOrderResponse, portfolio::MarketPosition};
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    const TICKER: &str = "KXHIGHNY-25OCT17-B61.5";
+
+    fn order(
+        order_id: &str,
+        client_order_id: &ClientOrderId,
+        action: Action,
+        status: &str,
+        count: u32,
+        filled: u32,
+        price: Price,
+    ) -> Order {
+        serde_json::from_value(json!({
+            "order_id": order_id,
+            "client_order_id": client_order_id,
+            "ticker": TICKER,
+            "side": "yes",
+            "action": action,
+            "type": "limit",
+            "status": status,
+            "yes_price_dollars": price,
+            "no_price_dollars": price.complement(),
+            "fill_count": filled,
+            "remaining_count": count - filled,
+            "initial_count": count,
+        }))
+        .unwrap()
+    }
+
+    /// Exchange where every order rests until the test fills it
+    #[derive(Default)]
+    struct FakeGateway {
+        orders: Mutex<Vec<Order>>,
+        positions: Mutex<Vec<MarketPosition>>,
+    }
+
+    impl FakeGateway {
+        fn replace(&self, order_id: &OrderId, f: impl Fn(&Order) -> Order) -> Order {
+            let mut orders = self.orders.lock().unwrap();
+            let existing = orders
+                .iter_mut()
+                .find(|order| order.order_id() == order_id)
+                .unwrap();
+            *existing = f(existing);
+            existing.clone()
+        }
+
+        fn fill(&self, order_id: &OrderId, filled: u32) {
+            self.replace(order_id, |o| {
+                let status = if filled == o.initial_count() {
+                    "executed"
+                } else {
+                    "resting"
+                };
+                order(
+                    &o.order_id().to_string(),
+                    o.client_order_id(),
+                    o.action(),
+                    status,
+                    o.initial_count(),
+                    filled,
+                    *o.price(),
+                )
+            });
+        }
+
+        fn set_position(&self, position: i64) {
+            *self.positions.lock().unwrap() = vec![
+                serde_json::from_value(json!({
+                    "ticker": TICKER,
+                    "position": position,
+                    "total_traded_dollars": "0",
+                    "market_exposure_dollars": "0",
+                    "realized_pnl_dollars": "0",
+                    "fees_paid_dollars": "0",
+                    "last_updated_ts": null,
+                }))
+                .unwrap(),
+            ];
+        }
+    }
+
+    #[async_trait]
+    impl OrderGateway for FakeGateway {
+        async fn create_order(&self, request: &CreateOrderRequest) -> Result<Order> {
+            let mut orders = self.orders.lock().unwrap();
+            let order = order(
+                &format!("o-{}", orders.len()),
+                request.client_order_id(),
+                request.action(),
+                "resting",
+                request.count(),
+                0,
+                *request.price().unwrap(),
+            );
+            orders.push(order.clone());
+            Ok(order)
+        }
+
+        async fn amend_order(
+            &self,
+            order_id: &OrderId,
+            request: &AmendOrderRequest,
+        ) -> Result<AmendOrderResponse> {
+            let old_order = self.get_order(order_id).await?;
+            let order = self.replace(order_id, |o| {
+                order(
+                    &o.order_id().to_string(),
+                    request.updated_client_order_id(),
+                    o.action(),
+                    "resting",
+                    request.count(),
+                    o.fill_count(),
+                    *request.price().unwrap(),
+                )
+            });
+            Ok(AmendOrderResponse { old_order, order })
+        }
+
+        async fn cancel_order(&self, order_id: &OrderId) -> Result<Order> {
+            Ok(self.replace(order_id, |o| {
+                order(
+                    &o.order_id().to_string(),
+                    o.client_order_id(),
+                    o.action(),
+                    "canceled",
+                    o.initial_count(),
+                    o.fill_count(),
+                    *o.price(),
+                )
+            }))
+        }
+
+        async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+            self.orders
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|order| order.order_id() == order_id)
+                .cloned()
+                .context("No such order")
+        }
+
+        async fn find_order(
+            &self,
+            _ticker: &MarketTicker,
+            client_order_id: &ClientOrderId,
+        ) -> Result<Option<Order>> {
+            let orders = self.orders.lock().unwrap();
+            Ok(orders
+                .iter()
+                .find(|order| order.client_order_id() == client_order_id)
+                .cloned())
+        }
+
+        async fn open_orders(&self) -> Result<Vec<Order>> {
+            let orders = self.orders.lock().unwrap();
+            Ok(orders
+                .iter()
+                .filter(|order| !OrderState::of(order).is_terminal())
+                .cloned()
+                .collect())
+        }
+
+        async fn positions(&self) -> Result<Vec<MarketPosition>> {
+            Ok(self.positions.lock().unwrap().clone())
+        }
+    }
+
+    fn cents(cents: u32) -> Price {
+        Price::from_cents(cents).unwrap()
+    }
+
+    fn working(engine: &OrderEngine<FakeGateway>) -> Vec<TrackedOrder> {
+        engine
+            .orders()
+            .filter(|order| !order.state().is_terminal())
+            .cloned()
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_intents_become_orders() {
+        let ticker: MarketTicker = TICKER.parse().unwrap();
+        let mut engine = OrderEngine::new(FakeGateway::default(), "bot");
+
+        engine
+            .submit(&Intent::hold(ticker.clone(), Side::Yes, 20, cents(42)))
+            .await
+            .unwrap();
+        let [order] = &working(&engine)[..] else {
+            panic!("Expected one order");
+        };
+        assert_eq!(order.state(), OrderState::Resting);
+        assert_eq!((order.action(), order.count()), (Action::Buy, 20));
+        assert!(order.client_order_id().has_prefix("bot"));
+
+        engine.gateway().fill(order.order_id().unwrap(), 5);
+        engine.refresh().await.unwrap();
+        assert_eq!(engine.position(&ticker, Side::Yes), 5);
+        assert_eq!(engine.position(&ticker, Side::No), -5);
+
+        // A new price amends the order for the 15 contracts still missing
+        engine
+            .submit(&Intent::hold(ticker.clone(), Side::Yes, 20, cents(40)))
+            .await
+            .unwrap();
+        let [amended] = &working(&engine)[..] else {
+            panic!("Expected one order");
+        };
+        assert_eq!(amended.state(), OrderState::PartiallyFilled);
+        assert_eq!(amended.order_id(), order.order_id());
+        assert_ne!(amended.client_order_id(), order.client_order_id());
+        assert_eq!((amended.remaining(), amended.price()), (15, cents(40)));
+    }
+
+    #[tokio::test]
+    async fn test_kill_switch_cancels_orders() {
+        let ticker: MarketTicker = TICKER.parse().unwrap();
+        let mut risk = RiskManager::new(RiskLimits::default());
+        risk.set_close_time(ticker.clone(), Utc::now() + chrono::TimeDelta::hours(1));
+        risk.set_daily_pnl(Utc::now().date_naive(), Money::ZERO);
+        let kill_switch = risk.kill_switch().clone();
+        let mut engine = OrderEngine::new(FakeGateway::default(), "bot").with_risk(risk);
+
+        let intent = Intent::hold(ticker.clone(), Side::Yes, 20, cents(42));
+        engine.submit(&intent).await.unwrap();
+        assert_eq!(working(&engine).len(), 1);
+
+        kill_switch.trigger("test");
+        engine.refresh().await.unwrap();
+        assert!(working(&engine).is_empty());
+        assert!(engine.submit(&intent).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_intents_submitted_together() {
+        let ticker: MarketTicker = TICKER.parse().unwrap();
+        let mut risk = RiskManager::new(RiskLimits::default());
+        risk.set_close_time(ticker.clone(), Utc::now() + chrono::TimeDelta::hours(1));
+        risk.set_daily_pnl(Utc::now().date_naive(), Money::ZERO);
+        let mut engine = OrderEngine::new(FakeGateway::default(), "bot").with_risk(risk);
+
+        // The second market has no close time, so neither is submitted
+        let unknown: MarketTicker = "KXHIGHNY-25OCT17-B63.5".parse().unwrap();
+        let intents = [
+            Intent::hold(ticker.clone(), Side::Yes, 20, cents(42)),
+            Intent::hold(unknown, Side::Yes, 20, cents(42)),
+        ];
+        assert!(engine.submit_all(&intents).await.is_err());
+        assert!(engine.orders().next().is_none());
+
+        engine.submit_all(&intents[..1]).await.unwrap();
+        assert_eq!(working(&engine).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_after_restart() {
+        let ticker: MarketTicker = TICKER.parse().unwrap();
+        let gateway = FakeGateway::default();
+        gateway.set_position(5);
+        let ours = ClientOrderId::with_prefix("bot");
+        let manual = ClientOrderId::with_prefix("manual");
+        for (order_id, client_order_id) in [("o-1", &ours), ("o-2", &manual)] {
+            gateway.orders.lock().unwrap().push(order(
+                order_id,
+                client_order_id,
+                Action::Buy,
+                "resting",
+                20,
+                5,
+                cents(42),
+            ));
+        }
+
+        let mut engine = OrderEngine::new(gateway, "bot");
+        engine.reconcile().await.unwrap();
+        assert_eq!(engine.position(&ticker, Side::Yes), 5);
+        assert!(engine.order(&ours).is_some());
+        assert!(engine.order(&manual).is_none());
+
+        // Closing cancels the resting buy and sells what we hold
+        engine
+            .submit(&Intent::close(ticker.clone(), Side::Yes, cents(50)))
+            .await
+            .unwrap();
+        assert_eq!(engine.order(&ours).unwrap().state(), OrderState::Cancelled);
+        let [sell] = &working(&engine)[..] else {
+            panic!("Expected one order");
+        };
+        assert_eq!((sell.action(), sell.count()), (Action::Sell, 5));
+        assert_eq!(sell.price(), cents(50));
+    }
+}
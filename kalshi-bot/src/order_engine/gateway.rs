@@ -0,0 +1,146 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use kalshi_api::{error::KalshiApiError, portfolio::PortfolioApiClient};
+use kalshi_api_spec::{
+    order::{
+        AmendOrderRequest, AmendOrderResponse, ClientOrderId, CreateOrderRequest, GetOrdersParams,
+        Order, OrderId,
+    },
+    portfolio::{GetPositionsParams, MarketPosition},
+    ticker::MarketTicker,
+};
+use std::sync::Arc;
+
+/// Where the order engine sends its orders: the exchange, or a simulation of it
+#[async_trait]
+pub trait OrderGateway: Send + Sync {
+    async fn create_order(&self, request: &CreateOrderRequest) -> Result<Order>;
+
+    async fn amend_order(
+        &self,
+        order_id: &OrderId,
+        request: &AmendOrderRequest,
+    ) -> Result<AmendOrderResponse>;
+
+    async fn cancel_order(&self, order_id: &OrderId) -> Result<Order>;
+
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order>;
+
+    /// The order created with `client_order_id`, whatever its status
+    async fn find_order(
+        &self,
+        ticker: &MarketTicker,
+        client_order_id: &ClientOrderId,
+    ) -> Result<Option<Order>>;
+
+    async fn open_orders(&self) -> Result<Vec<Order>>;
+
+    async fn positions(&self) -> Result<Vec<MarketPosition>>;
+}
+
+/// Lets the caller keep a handle on the gateway it gives the order engine,
+/// e.g. to feed market data to a `PaperGateway`
+#[async_trait]
+impl<G: OrderGateway + ?Sized> OrderGateway for Arc<G> {
+    async fn create_order(&self, request: &CreateOrderRequest) -> Result<Order> {
+        (**self).create_order(request).await
+    }
+
+    async fn amend_order(
+        &self,
+        order_id: &OrderId,
+        request: &AmendOrderRequest,
+    ) -> Result<AmendOrderResponse> {
+        (**self).amend_order(order_id, request).await
+    }
+
+    async fn cancel_order(&self, order_id: &OrderId) -> Result<Order> {
+        (**self).cancel_order(order_id).await
+    }
+
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+        (**self).get_order(order_id).await
+    }
+
+    async fn find_order(
+        &self,
+        ticker: &MarketTicker,
+        client_order_id: &ClientOrderId,
+    ) -> Result<Option<Order>> {
+        (**self).find_order(ticker, client_order_id).await
+    }
+
+    async fn open_orders(&self) -> Result<Vec<Order>> {
+        (**self).open_orders().await
+    }
+
+    async fn positions(&self) -> Result<Vec<MarketPosition>> {
+        (**self).positions().await
+    }
+}
+
+/// Whether the gateway refused a request, as opposed to failing to get an
+/// answer, in which case the request may or may not have gone through
+pub fn is_rejection(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<KalshiApiError>()
+        .is_some_and(|e| !e.is_retryable() && !matches!(e, KalshiApiError::Unauthorized { .. }))
+}
+
+#[async_trait]
+impl OrderGateway for PortfolioApiClient {
+    async fn create_order(&self, request: &CreateOrderRequest) -> Result<Order> {
+        PortfolioApiClient::create_order(self, request).await
+    }
+
+    async fn amend_order(
+        &self,
+        order_id: &OrderId,
+        request: &AmendOrderRequest,
+    ) -> Result<AmendOrderResponse> {
+        PortfolioApiClient::amend_order(self, order_id, request).await
+    }
+
+    async fn cancel_order(&self, order_id: &OrderId) -> Result<Order> {
+        Ok(PortfolioApiClient::cancel_order(self, order_id)
+            .await?
+            .order)
+    }
+
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+        PortfolioApiClient::get_order(self, order_id).await
+    }
+
+    async fn find_order(
+        &self,
+        ticker: &MarketTicker,
+        client_order_id: &ClientOrderId,
+    ) -> Result<Option<Order>> {
+        let params = GetOrdersParams {
+            ticker: Some(ticker.clone()),
+            ..Default::default()
+        };
+        let orders: Vec<Order> = self.orders(params).try_collect().await?;
+        Ok(orders
+            .into_iter()
+            .find(|order| order.client_order_id() == client_order_id))
+    }
+
+    async fn open_orders(&self) -> Result<Vec<Order>> {
+        PortfolioApiClient::open_orders(self, None).await
+    }
+
+    async fn positions(&self) -> Result<Vec<MarketPosition>> {
+        let mut params = GetPositionsParams::default();
+        let mut positions = vec![];
+        loop {
+            let response = self.get_positions(&params).await?;
+            positions.extend(response.market_positions);
+            params.cursor = response.cursor.filter(|cursor| !cursor.is_empty());
+            if params.cursor.is_none() {
+                return Ok(positions);
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use kalshi_api_spec::{
+    money::Price,
+    order::{Action, Side},
+    ticker::MarketTicker,
+};
+use serde::{Deserialize, Serialize};
+
+/// What a strategy wants to hold in a market, e.g. 20 YES contracts bought
+/// at 0.42 or less. The order engine works out the orders to get there.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Intent {
+    ticker: MarketTicker,
+    side: Side,
+    target: u32,
+    price: Price,
+    /// Model value of the contract, to catch prices far away from it
+    fair_value: Option<Price>,
+}
+
+impl Intent {
+    /// Hold `target` contracts of `side`, buying at `price` or less and
+    /// selling at `price` or more
+    pub fn hold(ticker: MarketTicker, side: Side, target: u32, price: Price) -> Self {
+        Self {
+            ticker,
+            side,
+            target,
+            price,
+            fair_value: None,
+        }
+    }
+
+    pub fn with_fair_value(mut self, fair_value: Price) -> Self {
+        self.fair_value = Some(fair_value);
+        self
+    }
+
+    /// Sell every contract of `side`, at `price` or more
+    pub fn close(ticker: MarketTicker, side: Side, price: Price) -> Self {
+        Self::hold(ticker, side, 0, price)
+    }
+
+    pub fn ticker(&self) -> &MarketTicker {
+        &self.ticker
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    pub fn target(&self) -> u32 {
+        self.target
+    }
+
+    pub fn price(&self) -> Price {
+        self.price
+    }
+
+    pub fn fair_value(&self) -> Option<Price> {
+        self.fair_value
+    }
+
+    /// The order needed to go from `held` contracts of the intent's side to
+    /// the target, if any. `held` is negative when holding the other side,
+    /// which buying this side nets out.
+    pub fn order_for(&self, held: i64) -> Option<(Action, u32)> {
+        let delta = self.target as i64 - held;
+        match delta {
+            0 => None,
+            delta if delta > 0 => Some((Action::Buy, delta as u32)),
+            delta => Some((Action::Sell, delta.unsigned_abs() as u32)),
+        }
+    }
+}
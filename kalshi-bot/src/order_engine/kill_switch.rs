@@ -0,0 +1,69 @@
+use anyhow::Result;
+use std::{
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use tokio::signal::unix::{SignalKind, signal};
+
+/// Once triggered, the order engine cancels every resting order and refuses
+/// new ones until the process restarts. Clones share the same switch.
+#[derive(Debug, Clone, Default)]
+pub struct KillSwitch {
+    triggered: Arc<AtomicBool>,
+    reason: Arc<Mutex<Option<String>>>,
+}
+
+impl KillSwitch {
+    pub fn trigger(&self, reason: &str) {
+        if !self.triggered.swap(true, Ordering::SeqCst) {
+            eprintln!("Kill switch triggered: {}", reason);
+            *self.reason.lock().unwrap() = Some(reason.to_string());
+        }
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        self.triggered.load(Ordering::SeqCst)
+    }
+
+    pub fn reason(&self) -> Option<String> {
+        self.reason.lock().unwrap().clone()
+    }
+
+    /// Trigger as soon as `path` exists, e.g. after `touch /tmp/kill_switch`
+    /// or the Telegram `/kill` command
+    pub fn watch_file(&self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        let switch = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(1));
+            while !switch.is_triggered() {
+                interval.tick().await;
+                if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+                    // The file may say why
+                    let content = tokio::fs::read_to_string(&path).await.unwrap_or_default();
+                    let reason = match content.trim() {
+                        "" => format!("{} exists", path.display()),
+                        reason => format!("{}: {}", path.display(), reason),
+                    };
+                    switch.trigger(&reason);
+                }
+            }
+        });
+    }
+
+    /// Trigger on SIGUSR1, e.g. `pkill -USR1 kalshi-bot`
+    pub fn watch_signal(&self) -> Result<()> {
+        let mut signal = signal(SignalKind::user_defined1())?;
+        let switch = self.clone();
+        tokio::spawn(async move {
+            if signal.recv().await.is_some() {
+                switch.trigger("SIGUSR1 received");
+            }
+        });
+        Ok(())
+    }
+}
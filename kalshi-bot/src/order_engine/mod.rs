@@ -1 +1,7 @@
-
+pub mod engine;
+pub mod gateway;
+pub mod intent;
+pub mod kill_switch;
+pub mod order;
+pub mod paper;
+pub mod risk;
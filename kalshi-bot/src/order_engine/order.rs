@@ -0,0 +1,252 @@
+use anyhow::{Result, bail};
+use kalshi_api_spec::{
+    money::Price,
+    order::{Action, ClientOrderId, Order, OrderId, OrderStatus, Side},
+    ticker::MarketTicker,
+};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum OrderState {
+    /// Sent, or about to be, without an answer from the exchange yet
+    Pending,
+    Resting,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+}
+
+impl OrderState {
+    /// State of the exchange's copy of an order
+    pub fn of(order: &Order) -> Self {
+        match order.status() {
+            OrderStatus::Pending => OrderState::Pending,
+            OrderStatus::Resting if order.fill_count() > 0 => OrderState::PartiallyFilled,
+            OrderStatus::Resting => OrderState::Resting,
+            OrderStatus::Executed => OrderState::Filled,
+            OrderStatus::Canceled => OrderState::Cancelled,
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderState::Filled | OrderState::Cancelled | OrderState::Rejected
+        )
+    }
+
+    pub fn can_become(&self, next: OrderState) -> bool {
+        use OrderState::*;
+        match (self, next) {
+            (current, next) if *current == next => true,
+            (Pending, _) => true,
+            (Resting, PartiallyFilled | Filled | Cancelled) => true,
+            (PartiallyFilled, Filled | Cancelled) => true,
+            _ => false,
+        }
+    }
+}
+
+/// An order placed by the engine, followed from creation until it is done
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedOrder {
+    client_order_id: ClientOrderId,
+    order_id: Option<OrderId>,
+    ticker: MarketTicker,
+    side: Side,
+    action: Action,
+    price: Price,
+    count: u32,
+    filled: u32,
+    state: OrderState,
+    reason: Option<String>,
+    /// Latest copy from the exchange, needed to amend the order
+    #[serde(skip)]
+    order: Option<Order>,
+}
+
+impl TrackedOrder {
+    pub fn pending(
+        client_order_id: ClientOrderId,
+        ticker: MarketTicker,
+        side: Side,
+        action: Action,
+        count: u32,
+        price: Price,
+    ) -> Self {
+        Self {
+            client_order_id,
+            order_id: None,
+            ticker,
+            side,
+            action,
+            price,
+            count,
+            filled: 0,
+            state: OrderState::Pending,
+            reason: None,
+            order: None,
+        }
+    }
+
+    /// Start tracking an order found on the exchange
+    pub fn adopt(order: Order) -> Self {
+        let mut tracked = Self::pending(
+            order.client_order_id().clone(),
+            order.ticker().clone(),
+            order.side(),
+            order.action(),
+            order.initial_count(),
+            *order.price(),
+        );
+        tracked.filled = order.fill_count();
+        tracked.state = OrderState::of(&order);
+        tracked.order_id = Some(order.order_id().clone());
+        tracked.order = Some(order);
+        tracked
+    }
+
+    pub fn client_order_id(&self) -> &ClientOrderId {
+        &self.client_order_id
+    }
+
+    pub fn order_id(&self) -> Option<&OrderId> {
+        self.order_id.as_ref()
+    }
+
+    pub fn ticker(&self) -> &MarketTicker {
+        &self.ticker
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    pub fn action(&self) -> Action {
+        self.action
+    }
+
+    pub fn price(&self) -> Price {
+        self.price
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn filled(&self) -> u32 {
+        self.filled
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.count.saturating_sub(self.filled)
+    }
+
+    pub fn state(&self) -> OrderState {
+        self.state
+    }
+
+    /// Why the exchange rejected the order
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
+    pub fn exchange_order(&self) -> Option<&Order> {
+        self.order.as_ref()
+    }
+
+    /// Catch up with the exchange's copy of the order. Returns how many
+    /// contracts were filled since the last update.
+    pub fn apply(&mut self, order: Order) -> Result<u32> {
+        let state = OrderState::of(&order);
+        if !self.state.can_become(state) {
+            bail!(
+                "Order {} can't go from {} to {}",
+                self.client_order_id,
+                self.state,
+                state
+            );
+        }
+        if order.fill_count() < self.filled {
+            bail!(
+                "Order {} went from {} to {} filled contracts",
+                self.client_order_id,
+                self.filled,
+                order.fill_count()
+            );
+        }
+
+        let new_fills = order.fill_count() - self.filled;
+        // Amending gives the order a new client order id
+        self.client_order_id = order.client_order_id().clone();
+        self.order_id = Some(order.order_id().clone());
+        self.price = *order.price();
+        self.count = order.initial_count().max(order.fill_count());
+        self.filled = order.fill_count();
+        self.state = state;
+        self.order = Some(order);
+        Ok(new_fills)
+    }
+
+    pub fn reject(&mut self, reason: String) -> Result<()> {
+        if self.state != OrderState::Pending {
+            bail!(
+                "Order {} is already {}, it can't be rejected",
+                self.client_order_id,
+                self.state
+            );
+        }
+        self.state = OrderState::Rejected;
+        self.reason = Some(reason);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn order(status: &str, fill_count: u32) -> Order {
+        serde_json::from_value(json!({
+            "order_id": "o-1",
+            "client_order_id": "bot-1",
+            "ticker": "KXHIGHNY-25OCT17-B61.5",
+            "side": "yes",
+            "action": "buy",
+            "type": "limit",
+            "status": status,
+            "yes_price_dollars": "0.4200",
+            "no_price_dollars": "0.5800",
+            "fill_count": fill_count,
+            "remaining_count": 20 - fill_count,
+            "initial_count": 20,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let mut tracked = TrackedOrder::pending(
+            ClientOrderId::from("bot-1".to_string()),
+            order("resting", 0).ticker().clone(),
+            Side::Yes,
+            Action::Buy,
+            20,
+            Price::from_cents(42).unwrap(),
+        );
+        assert_eq!(tracked.apply(order("resting", 0)).unwrap(), 0);
+        assert_eq!(tracked.state(), OrderState::Resting);
+        assert_eq!(tracked.apply(order("resting", 5)).unwrap(), 5);
+        assert_eq!(tracked.state(), OrderState::PartiallyFilled);
+        assert_eq!(tracked.remaining(), 15);
+        assert_eq!(tracked.apply(order("executed", 20)).unwrap(), 15);
+        assert!(tracked.state().is_terminal());
+
+        assert!(tracked.apply(order("resting", 20)).is_err());
+        assert!(tracked.reject("too late".to_string()).is_err());
+    }
+}
@@ -0,0 +1,600 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use kalshi_api::{error::KalshiApiError, markets::MarketsApiClient};
+use kalshi_api_spec::{
+    fee::{Liquidity, fee, total_cost},
+    history::{GetTradesParams, Trade},
+    money::{Money, Price},
+    order::{
+        Action, AmendOrderRequest, AmendOrderResponse, ClientOrderId, CreateOrderRequest, Order,
+        OrderId, OrderStatus, OrderType, Side,
+    },
+    orderbook::OrderBook,
+    portfolio::MarketPosition,
+    ticker::MarketTicker,
+    websocket::OrderbookDelta,
+};
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Mutex,
+};
+
+use crate::order_engine::gateway::OrderGateway;
+
+fn rejected(code: &str, message: &str) -> anyhow::Error {
+    KalshiApiError::Rejected {
+        status: 400,
+        code: code.to_string(),
+        message: message.to_string(),
+    }
+    .into()
+}
+
+fn not_found(order_id: &OrderId) -> anyhow::Error {
+    KalshiApiError::NotFound {
+        code: "order_not_found".to_string(),
+        message: format!("No paper order {}", order_id),
+    }
+    .into()
+}
+
+#[derive(Debug, Clone)]
+struct PaperOrder {
+    order_id: OrderId,
+    client_order_id: ClientOrderId,
+    ticker: MarketTicker,
+    side: Side,
+    action: Action,
+    status: OrderStatus,
+    /// Limit price of the contract on `side`
+    price: Price,
+    initial_count: u32,
+    fill_count: u32,
+    created_time: DateTime<Utc>,
+    last_update_time: DateTime<Utc>,
+    /// Contracts resting at the same price that were there first
+    queue_ahead: i64,
+}
+
+impl PaperOrder {
+    fn remaining_count(&self) -> u32 {
+        self.initial_count.saturating_sub(self.fill_count)
+    }
+
+    /// Kalshi only has bids: selling YES at 0.60 rests as a NO bid at 0.40
+    fn bid(&self) -> (Side, Price) {
+        match self.action {
+            Action::Buy => (self.side, self.price),
+            Action::Sell => (self.side.opposite(), self.price.complement()),
+        }
+    }
+
+    /// Price of this order's contract when its bid trades at `bid_price`
+    fn contract_price(&self, bid_price: Price) -> Price {
+        match self.action {
+            Action::Buy => bid_price,
+            Action::Sell => bid_price.complement(),
+        }
+    }
+
+    fn to_order(&self) -> Order {
+        let (yes_price, no_price) = match self.side {
+            Side::Yes => (self.price, self.price.complement()),
+            Side::No => (self.price.complement(), self.price),
+        };
+        serde_json::from_value(json!({
+            "order_id": self.order_id,
+            "client_order_id": self.client_order_id,
+            "ticker": self.ticker,
+            "side": self.side,
+            "action": self.action,
+            "type": OrderType::Limit,
+            "status": self.status,
+            "yes_price_dollars": yes_price,
+            "no_price_dollars": no_price,
+            "fill_count": self.fill_count,
+            "remaining_count": self.remaining_count(),
+            "initial_count": self.initial_count,
+            "created_time": self.created_time,
+            "last_update_time": self.last_update_time,
+            "expiration_time": null,
+        }))
+        .expect("Paper order is a valid order")
+    }
+}
+
+/// A simulated execution, with the fee Kalshi would have charged
+#[derive(Debug, Clone, Serialize)]
+pub struct PaperFill {
+    pub fill_id: String,
+    pub order_id: OrderId,
+    pub client_order_id: ClientOrderId,
+    pub ticker: MarketTicker,
+    pub side: Side,
+    pub action: Action,
+    pub count: u32,
+    pub price: Price,
+    pub fee: Money,
+    pub is_taker: bool,
+    pub created_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct PaperPosition {
+    /// Positive for YES contracts, negative for NO contracts
+    position: i64,
+    total_traded: Money,
+    /// Net amount paid for the contracts currently held
+    exposure: Money,
+    fees_paid: Money,
+}
+
+#[derive(Debug, Default)]
+struct PaperState {
+    balance: Money,
+    orders: Vec<PaperOrder>,
+    books: HashMap<MarketTicker, OrderBook>,
+    positions: BTreeMap<MarketTicker, PaperPosition>,
+    fills: Vec<PaperFill>,
+    seen_trades: HashSet<String>,
+    next_id: u64,
+}
+
+impl PaperState {
+    fn find(&self, order_id: &OrderId) -> Result<usize> {
+        self.orders
+            .iter()
+            .position(|order| order.order_id == *order_id)
+            .ok_or_else(|| not_found(order_id))
+    }
+
+    fn resting(&self, order_id: &OrderId) -> Result<usize> {
+        let i = self.find(order_id)?;
+        if self.orders[i].status != OrderStatus::Resting {
+            return Err(rejected("order_not_resting", "Order is not resting"));
+        }
+        Ok(i)
+    }
+
+    fn fill(&mut self, i: usize, count: u32, price: Price, is_taker: bool) {
+        let order = &mut self.orders[i];
+        let now = Utc::now();
+        let amount = price.cost(count);
+        let fee = fee(Liquidity::from_is_taker(is_taker), count, price);
+        let (signed, cash) = match (order.side, order.action) {
+            (Side::Yes, Action::Buy) => (count as i64, -amount),
+            (Side::No, Action::Buy) => (-(count as i64), -amount),
+            (Side::Yes, Action::Sell) => (-(count as i64), amount),
+            (Side::No, Action::Sell) => (count as i64, amount),
+        };
+
+        order.fill_count += count;
+        order.last_update_time = now;
+        if order.remaining_count() == 0 {
+            order.status = OrderStatus::Executed;
+        }
+
+        let position = self.positions.entry(order.ticker.clone()).or_default();
+        position.position += signed;
+        position.total_traded = position.total_traded + amount;
+        position.exposure = position.exposure - cash;
+        position.fees_paid = position.fees_paid + fee;
+        self.balance = self.balance + cash - fee;
+
+        let fill_id = format!("paper-fill-{:08}", self.fills.len() + 1);
+        self.fills.push(PaperFill {
+            fill_id,
+            order_id: order.order_id.clone(),
+            client_order_id: order.client_order_id.clone(),
+            ticker: order.ticker.clone(),
+            side: order.side,
+            action: order.action,
+            count,
+            price,
+            fee,
+            is_taker,
+            created_time: now,
+        });
+    }
+
+    /// Match order `i` against the asks at or below its bid, taking them off
+    /// the book. Takers pay the ask, a resting order crossed by the book is
+    /// filled at its own price.
+    fn cross(&mut self, i: usize, is_taker: bool) {
+        let order = &self.orders[i];
+        let (side, bid) = order.bid();
+        let Some(book) = self.books.get_mut(&order.ticker) else {
+            return;
+        };
+
+        let mut fills = vec![];
+        let mut remaining = order.remaining_count();
+        for (ask, size) in book.asks(side) {
+            if remaining == 0 || ask > bid {
+                break;
+            }
+            let count = remaining.min(size as u32);
+            remaining -= count;
+            let price = if is_taker { ask } else { bid };
+            fills.push((count, order.contract_price(price)));
+            book.apply_delta(&OrderbookDelta {
+                market_ticker: order.ticker.clone(),
+                price_dollars: ask.complement(),
+                delta: -(count as i64),
+                side: side.opposite(),
+                client_order_id: None,
+            });
+        }
+        for (count, price) in fills {
+            self.fill(i, count, price, is_taker);
+        }
+    }
+
+    fn join_queue(&mut self, i: usize) {
+        let order = &self.orders[i];
+        let (side, bid) = order.bid();
+        let depth = self
+            .books
+            .get(&order.ticker)
+            .map(|book| book.depth_at(side, bid))
+            .unwrap_or(0);
+        self.orders[i].queue_ahead = depth;
+    }
+
+    fn resting_on(&self, ticker: &MarketTicker) -> Vec<usize> {
+        self.orders
+            .iter()
+            .enumerate()
+            .filter(|(_, order)| order.status == OrderStatus::Resting && order.ticker == *ticker)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    fn update_book(&mut self, book: OrderBook) {
+        let ticker = book.ticker().clone();
+        self.books.insert(ticker.clone(), book);
+        for i in self.resting_on(&ticker) {
+            self.cross(i, false);
+            // Whatever left the level may have been ahead of us
+            let (side, bid) = self.orders[i].bid();
+            let depth = self.books[&ticker].depth_at(side, bid);
+            let order = &mut self.orders[i];
+            order.queue_ahead = order.queue_ahead.min(depth);
+        }
+    }
+
+    fn record_trade(&mut self, trade: &Trade) {
+        if !self.seen_trades.insert(trade.trade_id().to_string()) {
+            return;
+        }
+        // The taker bought its side from the bids on the other side
+        let maker_side = trade.taker_side().opposite();
+        let maker_price = match maker_side {
+            Side::Yes => trade.yes_price(),
+            Side::No => trade.no_price(),
+        };
+        for i in self.resting_on(trade.ticker()) {
+            let order = &mut self.orders[i];
+            let (side, bid) = order.bid();
+            if side != maker_side || maker_price > bid {
+                continue;
+            }
+            let reached = if maker_price == bid {
+                let reached = trade.count() as i64 - order.queue_ahead;
+                order.queue_ahead = (order.queue_ahead - trade.count() as i64).max(0);
+                reached.max(0) as u32
+            } else {
+                // Traded through our price, we would have been filled first
+                trade.count()
+            };
+            let count = reached.min(order.remaining_count());
+            if count > 0 {
+                let price = order.contract_price(bid);
+                self.fill(i, count, price, false);
+            }
+        }
+    }
+}
+
+/// Simulates the exchange against real order books, for trading strategies
+/// without money at risk. Resting orders queue behind the contracts already
+/// bid at their price and are only filled once trades went through them.
+pub struct PaperGateway(Mutex<PaperState>);
+
+impl PaperGateway {
+    pub fn new(balance: Money) -> Self {
+        Self(Mutex::new(PaperState {
+            balance,
+            ..Default::default()
+        }))
+    }
+
+    /// Replace the book of a market, filling the orders it crosses
+    pub fn update_book(&self, book: OrderBook) {
+        self.0.lock().unwrap().update_book(book);
+    }
+
+    /// Move resting orders up the queue, or fill them, with a trade between
+    /// other accounts
+    pub fn record_trade(&self, trade: &Trade) {
+        self.0.lock().unwrap().record_trade(trade);
+    }
+
+    /// Apply the trades since the previous poll and the current books of
+    /// `tickers`
+    pub async fn poll(&self, client: &MarketsApiClient, tickers: &[MarketTicker]) -> Result<()> {
+        for ticker in tickers {
+            let params = GetTradesParams {
+                ticker: Some(ticker.clone()),
+                min_ts: Some((Utc::now() - chrono::TimeDelta::minutes(5)).timestamp()),
+                ..Default::default()
+            };
+            let mut trades = client.get_trades(&params).await?.trades;
+            // Oldest first, as they happened
+            trades.reverse();
+            let book = client.get_orderbook(ticker, None).await?;
+
+            let mut state = self.0.lock().unwrap();
+            for trade in &trades {
+                state.record_trade(trade);
+            }
+            state.update_book(book);
+        }
+        Ok(())
+    }
+
+    pub fn balance(&self) -> Money {
+        self.0.lock().unwrap().balance
+    }
+
+    pub fn fills(&self) -> Vec<PaperFill> {
+        self.0.lock().unwrap().fills.clone()
+    }
+
+    /// Cash plus what the positions would sell for at the best bids
+    pub fn portfolio_value(&self) -> Money {
+        let state = self.0.lock().unwrap();
+        let positions: Money = state
+            .positions
+            .iter()
+            .filter_map(|(ticker, position)| {
+                let side = if position.position >= 0 {
+                    Side::Yes
+                } else {
+                    Side::No
+                };
+                let (bid, _) = state.books.get(ticker)?.best_bid(side)?;
+                Some(bid.cost(position.position.unsigned_abs() as u32))
+            })
+            .sum();
+        state.balance + positions
+    }
+}
+
+#[async_trait]
+impl OrderGateway for PaperGateway {
+    async fn create_order(&self, request: &CreateOrderRequest) -> Result<Order> {
+        let mut state = self.0.lock().unwrap();
+        if state
+            .orders
+            .iter()
+            .any(|order| order.client_order_id == *request.client_order_id())
+        {
+            return Err(rejected(
+                "order_already_exists",
+                "An order with this client_order_id already exists",
+            ));
+        }
+        let (OrderType::Limit, Some(price)) = (request.order_type(), request.price()) else {
+            return Err(rejected(
+                "invalid_order",
+                "Paper trading only supports limit orders",
+            ));
+        };
+        if request.action() == Action::Buy
+            && total_cost(Liquidity::Taker, request.count(), *price) > state.balance
+        {
+            return Err(rejected("insufficient_balance", "Insufficient balance"));
+        }
+
+        state.next_id += 1;
+        let order_id = OrderId::from(format!("paper-{:08}", state.next_id));
+        let now = Utc::now();
+        state.orders.push(PaperOrder {
+            order_id,
+            client_order_id: request.client_order_id().clone(),
+            ticker: request.ticker().clone(),
+            side: request.side(),
+            action: request.action(),
+            status: OrderStatus::Resting,
+            price: *price,
+            initial_count: request.count(),
+            fill_count: 0,
+            created_time: now,
+            last_update_time: now,
+            queue_ahead: 0,
+        });
+        let i = state.orders.len() - 1;
+        state.cross(i, true);
+        state.join_queue(i);
+        Ok(state.orders[i].to_order())
+    }
+
+    async fn amend_order(
+        &self,
+        order_id: &OrderId,
+        request: &AmendOrderRequest,
+    ) -> Result<AmendOrderResponse> {
+        let mut state = self.0.lock().unwrap();
+        let i = state.resting(order_id)?;
+        let old_order = state.orders[i].to_order();
+        let order = &mut state.orders[i];
+        if request.count() <= order.fill_count {
+            return Err(rejected(
+                "invalid_order",
+                "Count must exceed the filled count",
+            ));
+        }
+        // Only getting smaller keeps the place in the queue
+        let price = request.price().copied().unwrap_or(order.price);
+        let keeps_place = price == order.price && request.count() <= order.initial_count;
+        order.price = price;
+        order.initial_count = request.count();
+        order.client_order_id = request.updated_client_order_id().clone();
+        order.last_update_time = Utc::now();
+        if !keeps_place {
+            state.cross(i, true);
+            state.join_queue(i);
+        }
+        Ok(AmendOrderResponse {
+            old_order,
+            order: state.orders[i].to_order(),
+        })
+    }
+
+    async fn cancel_order(&self, order_id: &OrderId) -> Result<Order> {
+        let mut state = self.0.lock().unwrap();
+        let i = state.resting(order_id)?;
+        let order = &mut state.orders[i];
+        order.initial_count = order.fill_count;
+        order.status = OrderStatus::Canceled;
+        order.last_update_time = Utc::now();
+        Ok(order.to_order())
+    }
+
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+        let state = self.0.lock().unwrap();
+        Ok(state.orders[state.find(order_id)?].to_order())
+    }
+
+    async fn find_order(
+        &self,
+        _ticker: &MarketTicker,
+        client_order_id: &ClientOrderId,
+    ) -> Result<Option<Order>> {
+        let state = self.0.lock().unwrap();
+        Ok(state
+            .orders
+            .iter()
+            .find(|order| order.client_order_id == *client_order_id)
+            .map(PaperOrder::to_order))
+    }
+
+    async fn open_orders(&self) -> Result<Vec<Order>> {
+        let state = self.0.lock().unwrap();
+        Ok(state
+            .orders
+            .iter()
+            .filter(|order| order.status == OrderStatus::Resting)
+            .map(PaperOrder::to_order)
+            .collect())
+    }
+
+    async fn positions(&self) -> Result<Vec<MarketPosition>> {
+        let state = self.0.lock().unwrap();
+        state
+            .positions
+            .iter()
+            .map(|(ticker, position)| {
+                Ok(serde_json::from_value(json!({
+                    "ticker": ticker,
+                    "position": position.position,
+                    "total_traded_dollars": position.total_traded,
+                    "market_exposure_dollars": position.exposure.max(Money::ZERO),
+                    "realized_pnl_dollars": Money::ZERO,
+                    "fees_paid_dollars": position.fees_paid,
+                    "last_updated_ts": null,
+                }))?)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kalshi_api_spec::usd;
+
+    const TICKER: &str = "KXHIGHNY-25OCT17-B61.5";
+
+    fn cents(cents: u32) -> Price {
+        Price::from_cents(cents).unwrap()
+    }
+
+    fn book(yes: &[(u32, i64)], no: &[(u32, i64)]) -> OrderBook {
+        let levels = |levels: &[(u32, i64)]| -> Vec<(Price, i64)> {
+            levels.iter().map(|(c, n)| (cents(*c), *n)).collect()
+        };
+        OrderBook::from_levels(TICKER.parse().unwrap(), &levels(yes), &levels(no))
+    }
+
+    fn trade(id: &str, taker_side: Side, yes_cents: u32, count: u32) -> Trade {
+        serde_json::from_value(json!({
+            "trade_id": id,
+            "ticker": TICKER,
+            "count": count,
+            "yes_price_dollars": cents(yes_cents),
+            "no_price_dollars": cents(100 - yes_cents),
+            "taker_side": taker_side,
+            "created_time": Utc::now(),
+        }))
+        .unwrap()
+    }
+
+    fn buy_yes(count: u32, price: u32) -> CreateOrderRequest {
+        CreateOrderRequest::limit(
+            TICKER.parse().unwrap(),
+            Side::Yes,
+            Action::Buy,
+            count,
+            cents(price),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_taker_walks_the_book() {
+        let gateway = PaperGateway::new(usd!(100));
+        // YES asks of 10 at 0.42 and 10 at 0.44
+        gateway.update_book(book(&[(40, 50)], &[(58, 10), (56, 10)]));
+
+        let order = gateway.create_order(&buy_yes(15, 44)).await.unwrap();
+        assert_eq!(order.status(), OrderStatus::Executed);
+        let fills = gateway.fills();
+        assert_eq!(fills.len(), 2);
+        assert_eq!((fills[0].count, fills[0].price), (10, cents(42)));
+        assert_eq!((fills[1].count, fills[1].price), (5, cents(44)));
+        // $4.20 + $2.20 plus 0.18 + 0.09 in taker fees, rounded up to the cent
+        assert_eq!(gateway.balance(), usd!(93.33));
+        assert!(gateway.create_order(&buy_yes(1000, 44)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resting_order_waits_for_its_queue() {
+        let gateway = PaperGateway::new(usd!(100));
+        gateway.update_book(book(&[(40, 30)], &[(55, 10)]));
+        let order = gateway.create_order(&buy_yes(20, 40)).await.unwrap();
+        assert_eq!(order.status(), OrderStatus::Resting);
+
+        // 25 of the 30 contracts ahead trade, then 5 of them are cancelled
+        gateway.record_trade(&trade("t-1", Side::No, 40, 25));
+        gateway.update_book(book(&[(40, 0)], &[(55, 10)]));
+        assert!(gateway.fills().is_empty());
+
+        gateway.record_trade(&trade("t-2", Side::No, 40, 8));
+        gateway.record_trade(&trade("t-2", Side::No, 40, 8));
+        let order = gateway.get_order(order.order_id()).await.unwrap();
+        assert_eq!(order.fill_count(), 8);
+        assert!(!gateway.fills()[0].is_taker);
+
+        // The book moving through our price fills the rest at our price
+        gateway.update_book(book(&[], &[(61, 50)]));
+        let order = gateway.get_order(order.order_id()).await.unwrap();
+        assert_eq!(order.status(), OrderStatus::Executed);
+        assert_eq!(gateway.fills()[1].price, cents(40));
+        let positions = gateway.positions().await.unwrap();
+        assert_eq!(positions[0].position(), 20);
+    }
+}
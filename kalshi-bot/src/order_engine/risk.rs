@@ -0,0 +1,413 @@
+use anyhow::{Result, bail};
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use kalshi_api_spec::{market::Market, money::Money, order::Side, ticker::MarketTicker, usd};
+use protocol::protocol::KILL_SWITCH_FILE;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+use crate::order_engine::{intent::Intent, kill_switch::KillSwitch};
+
+#[derive(Debug, Clone)]
+pub struct RiskLimits {
+    max_market_contracts: u32,
+    max_event_contracts: u32,
+    /// Most money all targets together may cost
+    max_exposure: Money,
+    max_daily_loss: Money,
+    max_orders_per_minute: usize,
+    /// Furthest a price may be from the model's fair value, in cents
+    max_cents_from_fair_value: u32,
+    /// No orders this long before a market closes
+    close_buffer: TimeDelta,
+}
+
+impl Default for RiskLimits {
+    fn default() -> Self {
+        Self {
+            max_market_contracts: 100,
+            max_event_contracts: 300,
+            max_exposure: usd!(100),
+            max_daily_loss: usd!(50),
+            max_orders_per_minute: 30,
+            max_cents_from_fair_value: 15,
+            close_buffer: TimeDelta::minutes(10),
+        }
+    }
+}
+
+impl RiskLimits {
+    pub fn with_max_market_contracts(mut self, max_market_contracts: u32) -> Self {
+        self.max_market_contracts = max_market_contracts;
+        self
+    }
+
+    pub fn with_max_event_contracts(mut self, max_event_contracts: u32) -> Self {
+        self.max_event_contracts = max_event_contracts;
+        self
+    }
+
+    pub fn with_max_exposure(mut self, max_exposure: Money) -> Self {
+        self.max_exposure = max_exposure;
+        self
+    }
+
+    pub fn with_max_daily_loss(mut self, max_daily_loss: Money) -> Self {
+        self.max_daily_loss = max_daily_loss;
+        self
+    }
+
+    pub fn with_max_orders_per_minute(mut self, max_orders_per_minute: usize) -> Self {
+        self.max_orders_per_minute = max_orders_per_minute;
+        self
+    }
+
+    pub fn with_max_cents_from_fair_value(mut self, max_cents_from_fair_value: u32) -> Self {
+        self.max_cents_from_fair_value = max_cents_from_fair_value;
+        self
+    }
+
+    pub fn with_close_buffer(mut self, close_buffer: TimeDelta) -> Self {
+        self.close_buffer = close_buffer;
+        self
+    }
+}
+
+/// Pre-trade checks of every intent before the order engine acts on it
+#[derive(Debug, Clone, Default)]
+pub struct RiskManager {
+    limits: RiskLimits,
+    kill_switch: KillSwitch,
+    close_times: HashMap<MarketTicker, DateTime<Utc>>,
+    /// Last accepted intent per market and side
+    targets: HashMap<(MarketTicker, Side), Intent>,
+    daily_pnl: Option<(NaiveDate, Money)>,
+    orders_sent: VecDeque<Instant>,
+}
+
+impl RiskManager {
+    pub fn new(limits: RiskLimits) -> Self {
+        Self {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// Risk manager for trading `markets`, with a kill switch triggered by
+    /// the Telegram `/kill` command or SIGUSR1
+    pub fn for_markets(limits: RiskLimits, markets: &[Market]) -> Result<Self> {
+        let kill_switch = KillSwitch::default();
+        kill_switch.watch_file(KILL_SWITCH_FILE);
+        kill_switch.watch_signal()?;
+        let mut risk = Self::new(limits).with_kill_switch(kill_switch);
+        for market in markets {
+            risk.add_market(market);
+        }
+        Ok(risk)
+    }
+
+    pub fn with_kill_switch(mut self, kill_switch: KillSwitch) -> Self {
+        self.kill_switch = kill_switch;
+        self
+    }
+
+    pub fn kill_switch(&self) -> &KillSwitch {
+        &self.kill_switch
+    }
+
+    /// Markets must be added before trading them, so that orders stop before
+    /// they close
+    pub fn add_market(&mut self, market: &Market) {
+        self.set_close_time(market.ticker().clone(), market.close_time());
+    }
+
+    pub fn set_close_time(&mut self, ticker: MarketTicker, close_time: DateTime<Utc>) {
+        self.close_times.insert(ticker, close_time);
+    }
+
+    /// Realized and unrealized PnL of the day so far. Orders adding risk are
+    /// refused until it is set for the current day.
+    pub fn set_daily_pnl(&mut self, date: NaiveDate, pnl: Money) {
+        self.daily_pnl = Some((date, pnl));
+    }
+
+    /// Check an intent when `held` contracts of its side are held, and
+    /// remember its target if it passes
+    pub fn check(&mut self, intent: &Intent, held: i64, now: DateTime<Utc>) -> Result<()> {
+        let ticker = intent.ticker();
+        if self.kill_switch.is_triggered() {
+            bail!(
+                "Kill switch is on: {}",
+                self.kill_switch.reason().unwrap_or_default()
+            );
+        }
+
+        let Some(close_time) = self.close_times.get(ticker) else {
+            bail!("Unknown close time for {}", ticker);
+        };
+        if now + self.limits.close_buffer >= *close_time {
+            bail!("{} closes at {}", ticker, close_time);
+        }
+
+        if let Some(fair_value) = intent.fair_value() {
+            let distance = intent.price().cents().abs_diff(fair_value.cents());
+            if distance > self.limits.max_cents_from_fair_value {
+                bail!(
+                    "Price {} is {} cents away from the fair value {} of {}",
+                    intent.price(),
+                    distance,
+                    fair_value,
+                    ticker
+                );
+            }
+        }
+
+        // Selling what we hold only lowers the risk
+        if intent.target() as i64 > held {
+            self.check_limits(intent, now)?;
+        }
+
+        self.targets
+            .insert((ticker.clone(), intent.side()), intent.clone());
+        Ok(())
+    }
+
+    /// Check intents meant to be acted on together, each with the contracts
+    /// of its side held, without remembering anything. Passes only if every
+    /// intent passes after the ones before it, and the rate limit leaves
+    /// room for an order each.
+    pub fn check_all(
+        &self,
+        intents: &[(&Intent, i64)],
+        now: DateTime<Utc>,
+        instant: Instant,
+    ) -> Result<()> {
+        let mut risk = self.clone();
+        for (intent, held) in intents {
+            risk.check(intent, *held, now)?;
+        }
+        for _ in intents {
+            risk.take_order_slot(instant)?;
+        }
+        Ok(())
+    }
+
+    fn check_limits(&self, intent: &Intent, now: DateTime<Utc>) -> Result<()> {
+        let ticker = intent.ticker();
+        if intent.target() > self.limits.max_market_contracts {
+            bail!(
+                "Target of {} contracts of {} is above the limit of {}",
+                intent.target(),
+                ticker,
+                self.limits.max_market_contracts
+            );
+        }
+
+        let others = || {
+            self.targets
+                .iter()
+                .filter(|((other, side), _)| (other, *side) != (ticker, intent.side()))
+                .map(|(_, target)| target)
+        };
+        let event = ticker.event();
+        let event_contracts = intent.target()
+            + others()
+                .filter(|target| target.ticker().event() == event)
+                .map(|target| target.target())
+                .sum::<u32>();
+        if event_contracts > self.limits.max_event_contracts {
+            bail!(
+                "Targets of {} contracts in {} are above the limit of {}",
+                event_contracts,
+                event,
+                self.limits.max_event_contracts
+            );
+        }
+
+        let cost = |target: &Intent| target.price().cost(target.target());
+        let exposure = cost(intent) + others().map(cost).sum();
+        if exposure > self.limits.max_exposure {
+            bail!(
+                "Targets costing {} are above the exposure limit of {}",
+                exposure,
+                self.limits.max_exposure
+            );
+        }
+
+        // Without today's PnL the loss limit can't be checked
+        let today = now.date_naive();
+        let Some((_, pnl)) = self.daily_pnl.filter(|(date, _)| *date == today) else {
+            bail!("Unknown PnL for {}", today);
+        };
+        if -pnl > self.limits.max_daily_loss {
+            bail!(
+                "Lost {} today, above the limit of {}",
+                -pnl,
+                self.limits.max_daily_loss
+            );
+        }
+        Ok(())
+    }
+
+    /// Count an order about to be created or amended against the rate limit
+    pub fn take_order_slot(&mut self, now: Instant) -> Result<()> {
+        while let Some(sent) = self.orders_sent.front()
+            && now.duration_since(*sent) >= Duration::from_secs(60)
+        {
+            self.orders_sent.pop_front();
+        }
+        if self.orders_sent.len() >= self.limits.max_orders_per_minute {
+            bail!(
+                "Already sent {} orders in the last minute",
+                self.orders_sent.len()
+            );
+        }
+        self.orders_sent.push_back(now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kalshi_api_spec::money::Price;
+
+    fn intent(ticker: &str, target: u32, cents: u32) -> Intent {
+        Intent::hold(
+            ticker.parse().unwrap(),
+            Side::Yes,
+            target,
+            Price::from_cents(cents).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_limits() {
+        let now = DateTime::parse_from_rfc3339("2025-10-17T15:00:00Z")
+            .unwrap()
+            .to_utc();
+        let limits = RiskLimits::default()
+            .with_max_market_contracts(50)
+            .with_max_event_contracts(80)
+            .with_max_exposure(usd!(30));
+        let mut risk = RiskManager::new(limits);
+        for ticker in ["KXHIGHNY-25OCT17-B61.5", "KXHIGHNY-25OCT17-B63.5"] {
+            risk.set_close_time(ticker.parse().unwrap(), now + TimeDelta::hours(8));
+        }
+        let check =
+            |risk: &mut RiskManager, intent: &Intent, held| risk.check(intent, held, now).is_ok();
+
+        assert!(!check(
+            &mut risk,
+            &intent("KXHIGHNY-25OCT17-B61.5", 10, 30),
+            0
+        ));
+        risk.set_daily_pnl(now.date_naive(), Money::ZERO);
+
+        assert!(!check(
+            &mut risk,
+            &intent("KXHIGHNY-25OCT17-B65.5", 10, 30),
+            0
+        ));
+        assert!(!check(
+            &mut risk,
+            &intent("KXHIGHNY-25OCT17-B61.5", 60, 30),
+            0
+        ));
+        assert!(check(
+            &mut risk,
+            &intent("KXHIGHNY-25OCT17-B61.5", 50, 30),
+            0
+        ));
+        // 50 + 40 contracts in the event
+        assert!(!check(
+            &mut risk,
+            &intent("KXHIGHNY-25OCT17-B63.5", 40, 10),
+            0
+        ));
+        // $15 + $20
+        assert!(!check(
+            &mut risk,
+            &intent("KXHIGHNY-25OCT17-B63.5", 25, 80),
+            0
+        ));
+        assert!(check(
+            &mut risk,
+            &intent("KXHIGHNY-25OCT17-B63.5", 25, 50),
+            0
+        ));
+
+        let fat_finger = intent("KXHIGHNY-25OCT17-B61.5", 50, 90)
+            .with_fair_value(Price::from_cents(30).unwrap());
+        assert!(!check(&mut risk, &fat_finger, 50));
+
+        risk.set_daily_pnl(now.date_naive(), -usd!(60));
+        assert!(!check(
+            &mut risk,
+            &intent("KXHIGHNY-25OCT17-B61.5", 50, 31),
+            40
+        ));
+        // Selling is still allowed
+        assert!(check(
+            &mut risk,
+            &intent("KXHIGHNY-25OCT17-B61.5", 0, 31),
+            40
+        ));
+
+        risk.set_close_time(
+            "KXHIGHNY-25OCT17-B61.5".parse().unwrap(),
+            now + TimeDelta::minutes(5),
+        );
+        assert!(!check(
+            &mut risk,
+            &intent("KXHIGHNY-25OCT17-B61.5", 0, 31),
+            40
+        ));
+    }
+
+    #[test]
+    fn test_check_all() {
+        let now = DateTime::parse_from_rfc3339("2025-10-17T15:00:00Z")
+            .unwrap()
+            .to_utc();
+        let limits = RiskLimits::default()
+            .with_max_event_contracts(30)
+            .with_max_orders_per_minute(2);
+        let mut risk = RiskManager::new(limits);
+        risk.set_daily_pnl(now.date_naive(), Money::ZERO);
+        for ticker in ["KXHIGHNY-25OCT17-B61.5", "KXHIGHNY-25OCT17-B63.5"] {
+            risk.set_close_time(ticker.parse().unwrap(), now + TimeDelta::hours(8));
+        }
+        let b61 = intent("KXHIGHNY-25OCT17-B61.5", 20, 30);
+        let b63 = intent("KXHIGHNY-25OCT17-B63.5", 10, 30);
+        let start = Instant::now();
+        assert!(risk.check_all(&[(&b61, 0), (&b63, 0)], now, start).is_ok());
+
+        // Each passes alone, not both together
+        let more = intent("KXHIGHNY-25OCT17-B63.5", 11, 30);
+        assert!(risk.check_all(&[(&more, 0)], now, start).is_ok());
+        assert!(
+            risk.check_all(&[(&b61, 0), (&more, 0)], now, start)
+                .is_err()
+        );
+
+        // Nothing was remembered, but the rate limit has room for one order
+        risk.take_order_slot(start).unwrap();
+        assert!(risk.check_all(&[(&b61, 0)], now, start).is_ok());
+        assert!(risk.check_all(&[(&b61, 0), (&b63, 0)], now, start).is_err());
+    }
+
+    #[test]
+    fn test_order_rate() {
+        let mut risk = RiskManager::new(RiskLimits::default().with_max_orders_per_minute(2));
+        let start = Instant::now();
+        assert!(risk.take_order_slot(start).is_ok());
+        assert!(risk.take_order_slot(start).is_ok());
+        assert!(risk.take_order_slot(start).is_err());
+        assert!(
+            risk.take_order_slot(start + Duration::from_secs(60))
+                .is_ok()
+        );
+    }
+}
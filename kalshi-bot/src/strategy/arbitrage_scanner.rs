@@ -0,0 +1,221 @@
+use crate::{
+    kalshi::series::StationSeries,
+    ledger::{LEDGER_DIR, store::LedgerStore},
+    math::{
+        arbitrage::{Basket, basket, dips},
+        ladder::BucketLadder,
+    },
+    order_engine::{
+        engine::OrderEngine,
+        gateway::OrderGateway,
+        intent::Intent,
+        risk::{RiskLimits, RiskManager},
+    },
+    strategy::strategy::Strategy,
+};
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use futures::StreamExt;
+use kalshi_api::{
+    credentials::CredentialArgs,
+    markets::MarketsApiClient,
+    orderbook::OrderBookFeed,
+    portfolio::PortfolioApiClient,
+    websocket::{KalshiWebsocketClient, WebsocketEvent},
+};
+use kalshi_api_spec::{
+    money::Money,
+    order::Side,
+    orderbook::OrderBook,
+    ticker::{EventTicker, MarketTicker},
+};
+use std::{path::Path, pin::pin, time::Duration};
+use telegram::client::TelegramClient;
+use weather::station::Station;
+
+/// Most contracts of each market bought for a single basket
+const MAX_BASKET_CONTRACTS: u32 = 10;
+/// Orders and the daily PnL follow a timer, books update many times a second
+const REFRESH_EVERY: Duration = Duration::from_secs(10);
+
+fn basket_text(event_ticker: &EventTicker, basket: &Basket) -> String {
+    format!(
+        "{} asks of {} sum to {}: {} of each make {} after fees",
+        basket.side.to_string().to_uppercase(),
+        event_ticker,
+        basket.asks(),
+        basket.count,
+        basket.profit()
+    )
+}
+
+/// Watches the order books of every market of a day's event for baskets
+/// that make money whatever the outcome, and for prices no single peaked
+/// distribution explains. Reports them on Telegram, and buys the baskets
+/// when trading.
+pub struct ArbitrageScanner {
+    station: Station,
+    credentials: CredentialArgs,
+    trade: bool,
+    telegram_client: TelegramClient,
+    /// Findings of the last report, to only report changes
+    reported: Vec<String>,
+}
+
+impl ArbitrageScanner {
+    pub async fn new(station: Station, credentials: CredentialArgs, trade: bool) -> Self {
+        let telegram_client = TelegramClient::start()
+            .await
+            .expect("Create telegram client");
+        Self {
+            station,
+            credentials,
+            trade,
+            telegram_client,
+            reported: vec![],
+        }
+    }
+
+    async fn report(&mut self, findings: Vec<String>) -> Result<()> {
+        if findings == self.reported {
+            return Ok(());
+        }
+        self.reported = findings.clone();
+        if findings.is_empty() {
+            return Ok(());
+        }
+
+        let mut message = self
+            .telegram_client
+            .message()
+            .with_title("⚖️ Ladder mispricing");
+        for finding in findings {
+            println!("{}", finding);
+            message = message.with_item(finding);
+        }
+        message.send().await
+    }
+}
+
+/// Buy every leg of a profitable basket, or none when one is refused. A leg
+/// of a market where the other side is held would close that position
+/// instead, which leaves the basket unbalanced, so such baskets are skipped.
+async fn buy_basket<G: OrderGateway>(engine: &mut OrderEngine<G>, basket: &Basket) -> Result<()> {
+    let held: Vec<i64> = basket
+        .legs
+        .iter()
+        .map(|(ticker, _)| engine.position(ticker, basket.side))
+        .collect();
+    if let Some(((ticker, _), _)) = basket.legs.iter().zip(&held).find(|(_, held)| **held < 0) {
+        bail!("Holding the other side of {}", ticker);
+    }
+
+    let intents: Vec<Intent> = basket
+        .legs
+        .iter()
+        .zip(held)
+        .map(|((ticker, price), held)| {
+            Intent::hold(
+                ticker.clone(),
+                basket.side,
+                held as u32 + basket.count,
+                *price,
+            )
+        })
+        .collect();
+    engine.submit_all(&intents).await
+}
+
+#[async_trait]
+impl Strategy<WebsocketEvent> for ArbitrageScanner {
+    async fn run(&mut self, date: &NaiveDate) -> Result<()> {
+        let event_ticker = self.station.high_temperature_event(date);
+        let credentials = self.credentials.load()?;
+        let client = credentials.client();
+        let event = MarketsApiClient::from(client.clone())
+            .get_event(&event_ticker)
+            .await?;
+        if !event.event().mutually_exclusive() {
+            bail!("Markets of {} aren't mutually exclusive", event_ticker);
+        }
+        let ladder = BucketLadder::from_event(&event)?;
+        let tickers: Vec<MarketTicker> = ladder.iter().map(|(ticker, _)| ticker.clone()).collect();
+
+        let mut trader = None;
+        if self.trade {
+            let risk = RiskManager::for_markets(RiskLimits::default(), event.markets())?;
+            let mut engine =
+                OrderEngine::new(PortfolioApiClient::from(client.clone()), "arb").with_risk(risk);
+            engine.reconcile().await?;
+            let ledger = LedgerStore::open_read_only(Path::new(LEDGER_DIR)).await?;
+            trader = Some((engine, ledger));
+        }
+
+        let (websocket, events) =
+            KalshiWebsocketClient::connect(client.signer().clone(), credentials.base_url().clone());
+        let mut events = pin!(events);
+        let mut feed = OrderBookFeed::new(websocket);
+        feed.subscribe(&tickers)?;
+
+        let mut refresh = tokio::time::interval(REFRESH_EVERY);
+        loop {
+            tokio::select! {
+                event = events.next() => match event {
+                    Some(event) => feed.handle(&event)?,
+                    None => break,
+                },
+                _ = refresh.tick() => {
+                    if let Some((engine, ledger)) = &mut trader {
+                        engine.refresh().await?;
+                        if let Some(risk) = engine.risk_mut()
+                            && let Err(e) = ledger.update_risk(risk).await
+                        {
+                            eprintln!("Failed to update the daily PnL: {:?}", e);
+                        }
+                    }
+                    continue;
+                }
+            }
+
+            let Some(books) = tickers
+                .iter()
+                .map(|ticker| feed.book(ticker).cloned())
+                .collect::<Option<Vec<OrderBook>>>()
+            else {
+                continue;
+            };
+
+            if let Some((_, ledger)) = &mut trader {
+                for book in &books {
+                    ledger.ledger_mut().mark(book);
+                }
+            }
+
+            let mut findings = vec![];
+            for side in [Side::Yes, Side::No] {
+                let Some(basket) = basket(&books, side, MAX_BASKET_CONTRACTS) else {
+                    continue;
+                };
+                if basket.profit() <= Money::ZERO {
+                    continue;
+                }
+                findings.push(basket_text(&event_ticker, &basket));
+                if let Some((engine, ..)) = &mut trader
+                    && let Err(e) = buy_basket(engine, &basket).await
+                {
+                    eprintln!("Failed to buy the {} basket: {:?}", side, e);
+                }
+            }
+            for dip in dips(&ladder, &books) {
+                findings.push(format!(
+                    "{} is asked at {} below bids of {} on {} and {} on {}",
+                    dip.ticker, dip.ask, dip.colder.1, dip.colder.0, dip.warmer.1, dip.warmer.0
+                ));
+            }
+            self.report(findings).await?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,10 +1,24 @@
 use std::sync::Arc;
 
-use crate::strategy::{strategy::Strategy, utils::check_dates_match};
+use crate::{
+    kalshi::series::StationSeries,
+    math::ladder::BucketLadder,
+    order_engine::{
+        engine::OrderEngine,
+        gateway::OrderGateway,
+        intent::Intent,
+        risk::{RiskLimits, RiskManager},
+    },
+    strategy::{strategy::Strategy, utils::ObservedMax},
+};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, NaiveDate, NaiveTime};
 use chrono_tz::Tz;
+use kalshi_api::{
+    credentials::CredentialArgs, markets::MarketsApiClient, portfolio::PortfolioApiClient,
+};
+use kalshi_api_spec::{money::Price, order::Side, ticker::MarketTicker};
 use protocol::protocol::{Event, MultiServiceSubscriber, ServiceName};
 use telegram::client::TelegramClient;
 use tokio::sync::Mutex;
@@ -92,89 +106,148 @@ async fn handle_event(
         maybe_update_max_temp(seen, source, observed_max, telegram_client).await
     };
 
-    match event {
+    let (max, source) = match event {
         WeatherEvents::HourlyWeatherTimeseries(data) => {
-            let data: Vec<_> = data
-                .0
-                .into_iter()
-                .filter(|obs| {
-                    let dt: DateTime<Tz> = obs.datetime.into();
-                    check_dates_match(date, &dt)
-                })
-                .collect();
-
-            if let Some(max_temp) = data.iter().max_by_key(|t| &t.temperature) {
-                maybe_update(max_temp.temperature, "hourly time series").await?;
-            }
-
-            if let Some(max_six_h_temp) = data
-                .iter()
-                .filter_map(|t| t.six_hr_max_temperature.as_ref())
-                .max()
-            {
-                maybe_update(*max_six_h_temp, "hourly time series").await?;
-            }
-        }
-        WeatherEvents::HourlyWeatherTable(data) => {
-            let data: Vec<_> = data
-                .0
-                .into_iter()
-                .filter(|obs| {
-                    let dt: DateTime<Tz> = obs.datetime.into();
-                    check_dates_match(date, &dt)
-                })
-                .collect();
-
-            if let Some(max_temp) = data.iter().max_by_key(|t| &t.temperature) {
-                maybe_update(max_temp.temperature, "hourly table").await?;
-            }
-
-            if let Some(max_six_h_temp) = data
-                .iter()
-                .filter_map(|t| t.six_hr_max_temperature.as_ref())
-                .max()
-            {
-                maybe_update(*max_six_h_temp, "hourly table").await?;
-            }
-        }
-        WeatherEvents::DailyWeatherReport(data) => {
-            let dt: DateTime<Tz> = data.datetime.into();
-            if check_dates_match(date, &dt) {
-                maybe_update(data.max_temperature, "daily report").await?;
-            }
+            (data.observed_max(date), "hourly time series")
         }
+        WeatherEvents::HourlyWeatherTable(data) => (data.observed_max(date), "hourly table"),
+        WeatherEvents::DailyWeatherReport(data) => (data.observed_max(date), "daily report"),
+    };
+    if let Some(max) = max {
+        maybe_update(max, source).await?;
     }
     Ok(())
 }
 
+/// Intents selling what can only lose now that the day reached
+/// `observed_max`: YES held in buckets below it, and NO held in the open ended
+/// bucket it reached. `held` gives the contracts of a side held in a market.
+pub fn exit_intents(
+    ladder: &BucketLadder,
+    held: impl Fn(&MarketTicker, Side) -> i64,
+    observed_max: f64,
+    min_price: Price,
+) -> Vec<Intent> {
+    ladder
+        .iter()
+        .filter_map(|(ticker, bucket)| {
+            let losing = if !bucket.can_win(observed_max) {
+                Side::Yes
+            } else if bucket.is_certain(observed_max) {
+                Side::No
+            } else {
+                return None;
+            };
+            (held(ticker, losing) > 0).then(|| Intent::close(ticker.clone(), losing, min_price))
+        })
+        .collect()
+}
+
 pub struct DumpIfTempHigher {
     station: Station,
+    credentials: CredentialArgs,
+    /// Lowest price positions that can't win are sold at
+    min_price: Price,
+    /// Only report what would be sold otherwise
+    trade: bool,
     observed_max: Arc<Mutex<Option<(Temperature, String)>>>,
     telegram_client: Arc<Mutex<TelegramClient>>,
 }
 
 impl DumpIfTempHigher {
-    pub async fn new(station: Station) -> Self {
+    pub async fn new(
+        station: Station,
+        credentials: CredentialArgs,
+        min_price: Price,
+        trade: bool,
+    ) -> Self {
         let telegram_client = TelegramClient::start()
             .await
             .expect("Create telegram client");
         Self {
             station,
+            credentials,
+            min_price,
+            trade,
             observed_max: Arc::new(Mutex::new(None)),
             telegram_client: Arc::new(Mutex::new(telegram_client)),
         }
     }
+
+    /// Close the positions that can only lose, or only say which when not
+    /// trading, and report them
+    async fn dump<G: OrderGateway>(
+        &self,
+        engine: &mut OrderEngine<G>,
+        ladder: &BucketLadder,
+        observed_max: Temperature,
+    ) -> Result<()> {
+        // Fills since the last dump moved the positions
+        engine.reconcile().await?;
+        let intents = exit_intents(
+            ladder,
+            |ticker, side| engine.position(ticker, side),
+            observed_max.as_fahrenheit(),
+            self.min_price,
+        );
+
+        let mut closed = vec![];
+        for intent in intents {
+            let sell = format!(
+                "Sell {} {} of {} at {} or more",
+                engine.position(intent.ticker(), intent.side()),
+                intent.side(),
+                intent.ticker(),
+                intent.price()
+            );
+            if !self.trade {
+                closed.push(format!("Not trading: {}", sell));
+                continue;
+            }
+            match engine.submit(&intent).await {
+                Ok(()) => closed.push(sell),
+                Err(e) => eprintln!("Failed to close {}: {:?}", intent.ticker(), e),
+            }
+        }
+        if closed.is_empty() {
+            return Ok(());
+        }
+
+        let mut telegram_client = self.telegram_client.lock().await;
+        let mut message = telegram_client
+            .message()
+            .with_title("🗑️ Dumping positions")
+            .with_item(format!(
+                "Max observation: {}F",
+                observed_max.as_fahrenheit()
+            ));
+        for item in closed {
+            println!("{}", item);
+            message = message.with_item(item);
+        }
+        message.send().await
+    }
 }
 
 #[async_trait]
 impl Strategy<WeatherEvents> for DumpIfTempHigher {
     async fn run(&mut self, date: &NaiveDate) -> Result<()> {
+        let event_ticker = self.station.high_temperature_event(date);
         let date = date
             .and_time(NaiveTime::default())
             .and_local_timezone(self.station.timezone())
             .single()
             .context("Expected a sigle timestamp from the station's timezone")?;
 
+        let credentials = self.credentials.load()?;
+        let event = MarketsApiClient::from(credentials.client())
+            .get_event(&event_ticker)
+            .await?;
+        let ladder = BucketLadder::from_event(&event)?;
+        let risk = RiskManager::for_markets(RiskLimits::default(), event.markets())?;
+        let mut engine = OrderEngine::new(PortfolioApiClient::from(credentials.client()), "dump")
+            .with_risk(risk);
+
         let mut client = MultiServiceSubscriber::<WeatherEvents>::default();
         client
             .add_subscription::<NWSHourlyTimeseriesTemperatures>(
@@ -188,16 +261,69 @@ impl Strategy<WeatherEvents> for DumpIfTempHigher {
             .add_subscription::<NWSDailyReport>(ServiceName::DailyWeatherReport)
             .await?;
 
-        let observed_max = self.observed_max.clone();
-        let telegram_client = self.telegram_client.clone();
-        client
-            .listen_all(|event| {
-                let observed_max = observed_max.clone();
-                let telegram_client = telegram_client.clone();
-                async move { handle_event(event, &date, &observed_max, &telegram_client).await }
-            })
-            .await?;
+        let mut dumped_at: Option<Temperature> = None;
+        while let Some(event) = client.next().await {
+            handle_event(event, &date, &self.observed_max, &self.telegram_client).await?;
+
+            let observed_max = self.observed_max.lock().await.as_ref().map(|(t, _)| *t);
+            if let Some(observed_max) = observed_max
+                && dumped_at.is_none_or(|dumped_at| observed_max > dumped_at)
+            {
+                if let Err(e) = self.dump(&mut engine, &ladder, observed_max).await {
+                    eprintln!("Failed to dump positions: {:?}", e);
+                    continue;
+                }
+                dumped_at = Some(observed_max);
+            }
+        }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ladder::test_utils::market;
+
+    #[test]
+    fn test_exit_intents() {
+        let markets = [
+            market("T61", "less", None, Some(61.)),
+            market("B61.5", "between", Some(61.), Some(62.)),
+            market("T62", "greater", Some(62.), None),
+        ];
+        let ladder = BucketLadder::from_markets(&markets).unwrap();
+        let min_price = Price::from_cents(2).unwrap();
+        // 10 YES everywhere, and 5 NO of the warmest bucket instead
+        let held =
+            |ticker: &MarketTicker, side: Side| match (ticker.to_string().ends_with("T62"), side) {
+                (false, Side::Yes) => 10,
+                (false, Side::No) => -10,
+                (true, Side::Yes) => -5,
+                (true, Side::No) => 5,
+            };
+        let closed = |observed_max| {
+            exit_intents(&ladder, held, observed_max, min_price)
+                .iter()
+                .map(|intent| (intent.ticker().to_string(), intent.side(), intent.target()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(closed(60.0), vec![]);
+        assert_eq!(
+            closed(62.0),
+            vec![("KXHIGHNY-25OCT17-T61".to_string(), Side::Yes, 0)]
+        );
+        assert_eq!(
+            closed(63.0),
+            vec![
+                ("KXHIGHNY-25OCT17-T61".to_string(), Side::Yes, 0),
+                ("KXHIGHNY-25OCT17-B61.5".to_string(), Side::Yes, 0),
+                ("KXHIGHNY-25OCT17-T62".to_string(), Side::No, 0),
+            ]
+        );
+        let intents = exit_intents(&ladder, held, 63.0, min_price);
+        assert!(intents.iter().all(|intent| intent.price() == min_price));
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{
+    kalshi::series::StationSeries,
+    math::{ladder::BucketLadder, stats::DailyMax},
+    strategy::{
+        strategy::Strategy,
+        utils::{ObservedMax, check_dates_match},
+        weather_better::WeatherEvents,
+    },
+};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use kalshi_api::{credentials::CredentialArgs, markets::MarketsApiClient};
+use kalshi_api_spec::{money::Price, order::Side, orderbook::OrderBook};
+use protocol::{
+    pricing::{FairValues, MarketFairValue},
+    protocol::{Event, MultiServiceSubscriber, ServiceName, ServicePublisher},
+};
+use std::{collections::BTreeMap, time::Duration};
+use weather::{
+    forecast::{
+        fetcher::{SingleWeatherForecast, WeatherForecast},
+        model::Model,
+    },
+    observations::{
+        nws_daily_report::NWSDailyReport, nws_hourly_table::NWSHourlyTableTemperatures,
+        nws_hourly_timeseries::NWSHourlyTimeseriesTemperatures,
+    },
+    station::Station,
+    temperature::Temperature,
+};
+
+/// Order books move without the forecast changing
+const REFRESH_PRICES_EVERY: Duration = Duration::from_secs(30);
+
+fn dollars(price: Price) -> f64 {
+    price.cents() as f64 / 100.0
+}
+
+/// Fair YES price of every market of the ladder given the distribution of
+/// the day's highest temperature, next to the market's quotes
+pub fn fair_values(
+    ladder: &BucketLadder,
+    books: &[OrderBook],
+    max: &DailyMax,
+) -> Vec<MarketFairValue> {
+    ladder
+        .probabilities(max)
+        .into_iter()
+        .map(|(ticker, probability)| {
+            let book = books.iter().find(|book| *book.ticker() == ticker);
+            let quote = |best: Option<(Price, i64)>| best.map(|(price, _)| dollars(price));
+            MarketFairValue {
+                ticker: ticker.to_string(),
+                fair_yes: probability,
+                yes_bid: book.and_then(|book| quote(book.best_bid(Side::Yes))),
+                yes_ask: book.and_then(|book| quote(book.best_ask(Side::Yes))),
+            }
+        })
+        .collect()
+}
+
+/// Turns the forecast highest temperature into fair prices for the day's
+/// markets and publishes them with the current quotes. Once temperatures
+/// are observed only the rest of the day is forecast.
+pub struct ForecastPricer {
+    station: Station,
+    credentials: CredentialArgs,
+    model: Model,
+    forecast: BTreeMap<DateTime<Tz>, SingleWeatherForecast>,
+    observed_max: Option<f64>,
+}
+
+impl ForecastPricer {
+    pub fn new(station: Station, credentials: CredentialArgs, model: Model) -> Self {
+        Self {
+            station,
+            credentials,
+            model,
+            forecast: BTreeMap::new(),
+            observed_max: None,
+        }
+    }
+
+    /// Highest forecast temperature of the rest of the day in °F and its
+    /// standard deviation at the forecast's lead time
+    fn remaining_forecast_max(&self, now: DateTime<Utc>) -> Option<(f64, f64)> {
+        let (_, max) = self
+            .forecast
+            .iter()
+            .filter(|(dt, _)| **dt >= now)
+            .max_by_key(|(_, forecast)| forecast.temperature)?;
+        Some((
+            max.temperature.as_fahrenheit(),
+            self.model.stdev(max._lead_time),
+        ))
+    }
+
+    fn daily_max(&self, now: DateTime<Utc>) -> Option<DailyMax> {
+        match (self.remaining_forecast_max(now), self.observed_max) {
+            (Some((mu, sigma)), None) => Some(DailyMax::forecast(mu, sigma)),
+            (Some((mu, sigma)), Some(observed)) => {
+                Some(DailyMax::forecast(mu, sigma).with_observed(observed))
+            }
+            (None, Some(observed)) => Some(DailyMax::observed(observed)),
+            (None, None) => None,
+        }
+    }
+
+    fn observe(&mut self, temperature: Option<Temperature>) {
+        let Some(temperature) = temperature.map(|t| t.as_fahrenheit()) else {
+            return;
+        };
+        if self.observed_max.is_none_or(|max| temperature > max) {
+            println!("Max observation: {:.1}F", temperature);
+            self.observed_max = Some(temperature);
+        }
+    }
+
+    fn handle_event(&mut self, event: WeatherEvents, date: &DateTime<Tz>) {
+        let on_date = |dt: DateTime<Tz>| check_dates_match(date, &dt);
+        match event {
+            WeatherEvents::WeatherForecast(data) => self.forecast.extend(
+                data.message
+                    .forecast
+                    .into_iter()
+                    .map(|(dt, forecast)| (dt.into(), forecast))
+                    .filter(|(dt, _)| on_date(*dt)),
+            ),
+            WeatherEvents::HourlyWeatherTimeseries(data) => self.observe(data.observed_max(date)),
+            WeatherEvents::HourlyWeatherTable(data) => self.observe(data.observed_max(date)),
+            WeatherEvents::DailyWeatherReport(data) => self.observe(data.observed_max(date)),
+        }
+    }
+}
+
+#[async_trait]
+impl Strategy<WeatherEvents> for ForecastPricer {
+    async fn run(&mut self, date: &NaiveDate) -> Result<()> {
+        let event_ticker = self.station.high_temperature_event(date);
+        let date = date
+            .and_time(NaiveTime::default())
+            .and_local_timezone(self.station.timezone())
+            .single()
+            .context("Expected a single timestamp from the station's timezone")?;
+
+        let client = MarketsApiClient::from(self.credentials.client()?);
+        let event = client.get_event(&event_ticker).await?;
+        let ladder = BucketLadder::from_event(&event)?;
+
+        let mut publisher = ServicePublisher::<FairValues>::new(ServiceName::FairValue).await?;
+        let mut events = MultiServiceSubscriber::<WeatherEvents>::default();
+        events
+            .add_subscription::<WeatherForecast>(ServiceName::WeatherForecast)
+            .await?;
+        events
+            .add_subscription::<NWSHourlyTimeseriesTemperatures>(
+                ServiceName::HourlyWeatherTimeseries,
+            )
+            .await?;
+        events
+            .add_subscription::<NWSHourlyTableTemperatures>(ServiceName::HourlyWeatherTable)
+            .await?;
+        events
+            .add_subscription::<NWSDailyReport>(ServiceName::DailyWeatherReport)
+            .await?;
+
+        let mut refresh = tokio::time::interval(REFRESH_PRICES_EVERY);
+        let mut event_id = 0;
+        loop {
+            tokio::select! {
+                event = events.next() => {
+                    let Some(event) = event else {
+                        return Ok(());
+                    };
+                    self.handle_event(event, &date);
+                }
+                _ = refresh.tick() => {}
+            }
+
+            let now = Utc::now();
+            let Some(max) = self.daily_max(now) else {
+                continue;
+            };
+            let mut books = vec![];
+            for (ticker, _) in ladder.iter() {
+                match client.get_orderbook(ticker, None).await {
+                    Ok(book) => books.push(book),
+                    Err(e) => eprintln!("Failed to get the order book of {}: {}", ticker, e),
+                }
+            }
+
+            let markets = fair_values(&ladder, &books, &max);
+            for market in &markets {
+                let quote = |price: Option<f64>| {
+                    price.map_or("-".to_string(), |price| format!("{:.2}", price))
+                };
+                println!(
+                    "{}: fair {:.2}, bid {}, ask {}",
+                    market.ticker,
+                    market.fair_yes,
+                    quote(market.yes_bid),
+                    quote(market.yes_ask)
+                );
+            }
+            let remaining = self.remaining_forecast_max(now);
+            let fair_values = FairValues {
+                event_ticker: event_ticker.to_string(),
+                forecast_max: remaining.map(|(mu, _)| mu),
+                stdev: remaining.map(|(_, sigma)| sigma),
+                observed_max: self.observed_max,
+                markets,
+            };
+            publisher.publish(Event::new(event_id, fair_values)).await?;
+            event_id += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ladder::test_utils::market;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_fair_values() {
+        let markets = [
+            market("T61", "less", None, Some(61.)),
+            market("B61.5", "between", Some(61.), Some(62.)),
+            market("T62", "greater", Some(62.), None),
+        ];
+        let ladder = BucketLadder::from_markets(&markets).unwrap();
+        let price = |cents| Price::from_cents(cents).unwrap();
+        let book = OrderBook::from_levels(
+            "KXHIGHNY-25OCT17-B61.5".parse().unwrap(),
+            &[(price(40), 10)],
+            &[(price(45), 10)],
+        );
+
+        let values = fair_values(&ladder, &[book], &DailyMax::forecast(61.5, 1.5));
+        let total: f64 = values.iter().map(|value| value.fair_yes).sum();
+        assert_relative_eq!(total, 1.0, epsilon = 1e-9);
+        assert_eq!(values[0].yes_bid, None);
+
+        let between = &values[1];
+        assert_eq!(between.yes_bid, Some(0.4));
+        assert_eq!(between.yes_ask, Some(0.55));
+        assert_relative_eq!(
+            between.yes_edge().unwrap(),
+            between.fair_yes - 0.55,
+            epsilon = 1e-9
+        );
+    }
+}
@@ -0,0 +1,418 @@
+use crate::{
+    kalshi::series::StationSeries,
+    ledger::{LEDGER_DIR, store::LedgerStore},
+    order_engine::{
+        engine::OrderEngine,
+        gateway::OrderGateway,
+        intent::Intent,
+        paper::PaperGateway,
+        risk::{RiskLimits, RiskManager},
+    },
+    strategy::strategy::Strategy,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Timelike, Utc};
+use futures::StreamExt;
+use kalshi_api::{
+    credentials::CredentialArgs, markets::MarketsApiClient, portfolio::PortfolioApiClient,
+};
+use kalshi_api_spec::{
+    money::{Money, Price},
+    order::Side,
+    ticker::{EventTicker, MarketTicker},
+};
+use protocol::{
+    pricing::{FairValues, MarketFairValue},
+    protocol::{Event, ServiceName, ServiceSubscriber},
+};
+use std::{path::Path, pin::pin, sync::Arc, time::Duration};
+use weather::station::Station;
+
+/// Quotes follow the books between fair value updates
+const REQUOTE_EVERY: Duration = Duration::from_secs(10);
+/// Fair values are published every 30 seconds, older ones mean the pricer
+/// or its data stopped
+const MAX_FAIR_VALUE_AGE: chrono::TimeDelta = chrono::TimeDelta::minutes(2);
+/// Stations report their hourly observation a few minutes before the hour,
+/// which can move fair values faster than quotes follow
+const OBSERVATION_RELEASE_MINUTE: u32 = 50;
+/// Cash the paper gateway starts with
+const PAPER_BALANCE_CENTS: i64 = 100_000;
+
+/// Price of a whole number of cents, none outside of the tradable range
+fn price_of_cents(cents: f64) -> Option<Price> {
+    if !(1.0..=99.0).contains(&cents) {
+        return None;
+    }
+    Price::from_cents(cents as u32).ok()
+}
+
+/// YES prices to rest orders at in a market, none on a side not quoted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quote {
+    pub bid: Option<Price>,
+    pub ask: Option<Price>,
+}
+
+/// Where quotes go around the fair value
+#[derive(Debug, Clone)]
+pub struct QuoteParams {
+    /// Half the spread when the forecast is certain, in dollars
+    base_half_spread: f64,
+    /// Half spread added per °F of forecast standard deviation
+    half_spread_per_degree: f64,
+    /// Quotes move down by this much per YES contract held, and up per NO
+    skew_per_contract: f64,
+    size: u32,
+    /// No more quotes adding to a position this big
+    max_inventory: u32,
+}
+
+impl Default for QuoteParams {
+    fn default() -> Self {
+        Self {
+            base_half_spread: 0.02,
+            half_spread_per_degree: 0.01,
+            skew_per_contract: 0.002,
+            size: 5,
+            max_inventory: 25,
+        }
+    }
+}
+
+impl QuoteParams {
+    /// Quote of a market holding `held` YES contracts, negative for NO, when
+    /// the forecast highest temperature has standard deviation `stdev`. The
+    /// quote never crosses the market's best bid and ask.
+    pub fn quote(&self, market: &MarketFairValue, stdev: f64, held: i64) -> Quote {
+        let center = market.fair_yes - self.skew_per_contract * held as f64;
+        let half_spread = self.base_half_spread + self.half_spread_per_degree * stdev;
+
+        let mut bid = center - half_spread;
+        if let Some(yes_ask) = market.yes_ask {
+            bid = bid.min(yes_ask - 0.01);
+        }
+        let mut ask = center + half_spread;
+        if let Some(yes_bid) = market.yes_bid {
+            ask = ask.max(yes_bid + 0.01);
+        }
+
+        // Round away from the fair value, never tighter than the spread. The
+        // tolerance keeps float error from adding a cent.
+        let max_inventory = self.max_inventory as i64;
+        Quote {
+            bid: (held < max_inventory)
+                .then(|| price_of_cents((bid * 100.0 + 1e-6).floor()))
+                .flatten(),
+            ask: (-held < max_inventory)
+                .then(|| price_of_cents((ask * 100.0 - 1e-6).ceil()))
+                .flatten(),
+        }
+    }
+}
+
+/// Rest an order buying `size` contracts of `side` at `price`, or cancel
+/// the orders of that side without a price. Targets can't go past zero, so
+/// the order reducing a position offers all of it when it is bigger.
+async fn rest<G: OrderGateway>(
+    engine: &mut OrderEngine<G>,
+    ticker: &MarketTicker,
+    side: Side,
+    price: Option<Price>,
+    size: u32,
+    fair_value: Option<Price>,
+) -> Result<()> {
+    let Some(price) = price else {
+        let working: Vec<_> = engine
+            .orders()
+            .filter(|order| {
+                !order.state().is_terminal()
+                    && order.order_id().is_some()
+                    && order.ticker() == ticker
+                    && order.side() == side
+            })
+            .map(|order| order.client_order_id().clone())
+            .collect();
+        for client_order_id in working {
+            engine.cancel(&client_order_id).await?;
+        }
+        return Ok(());
+    };
+
+    let held = engine.position(ticker, side);
+    let target = (held + size as i64).max(0) as u32;
+    let mut intent = Intent::hold(ticker.clone(), side, target, price);
+    if let Some(fair_value) = fair_value {
+        intent = intent.with_fair_value(fair_value);
+    }
+    engine.submit(&intent).await
+}
+
+/// Quotes both sides of every market of a day's event around the fair values
+/// published by the forecast pricer. Bids buy YES and asks buy NO at the
+/// complement. Quotes are pulled when fair values are stale and around the
+/// release of hourly observations.
+pub struct MarketMaker {
+    station: Station,
+    credentials: CredentialArgs,
+    params: QuoteParams,
+    /// Trade against simulated fills on the real books instead
+    paper: bool,
+}
+
+impl MarketMaker {
+    pub fn new(
+        station: Station,
+        credentials: CredentialArgs,
+        params: QuoteParams,
+        paper: bool,
+    ) -> Self {
+        Self {
+            station,
+            credentials,
+            params,
+            paper,
+        }
+    }
+
+    /// Why quotes shouldn't rest now, if they shouldn't
+    fn pull_reason(
+        &self,
+        latest: Option<&Event<FairValues>>,
+        now: DateTime<Utc>,
+    ) -> Option<String> {
+        let Some(latest) = latest else {
+            return Some("No fair values yet".to_string());
+        };
+        let age = now.signed_duration_since(DateTime::<chrono_tz::Tz>::from(latest.ts));
+        if age > MAX_FAIR_VALUE_AGE {
+            return Some(format!("Fair values are {}s old", age.num_seconds()));
+        }
+        if now.with_timezone(&self.station.timezone()).minute() >= OBSERVATION_RELEASE_MINUTE {
+            return Some("Hourly observation is due".to_string());
+        }
+        None
+    }
+
+    async fn quote_all<G: OrderGateway>(
+        &self,
+        engine: &mut OrderEngine<G>,
+        fair_values: &FairValues,
+    ) -> Result<()> {
+        let stdev = fair_values.stdev.unwrap_or_default();
+        for market in &fair_values.markets {
+            let ticker: MarketTicker = market.ticker.parse()?;
+            let held = engine.position(&ticker, Side::Yes);
+            let quote = self.params.quote(market, stdev, held);
+            let fair_yes = price_of_cents((market.fair_yes * 100.0).round());
+
+            let bid = rest(
+                engine,
+                &ticker,
+                Side::Yes,
+                quote.bid,
+                self.params.size,
+                fair_yes,
+            )
+            .await;
+            let ask = rest(
+                engine,
+                &ticker,
+                Side::No,
+                quote.ask.map(|ask| ask.complement()),
+                self.params.size,
+                fair_yes.map(|fair| fair.complement()),
+            )
+            .await;
+            for result in [bid, ask] {
+                if let Err(e) = result {
+                    eprintln!("Failed to quote {}: {:?}", ticker, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn make_markets<G: OrderGateway>(
+        &self,
+        engine: &mut OrderEngine<G>,
+        paper: Option<(&PaperGateway, &MarketsApiClient)>,
+        mut ledger: Option<&mut LedgerStore>,
+        event_ticker: &EventTicker,
+        tickers: &[MarketTicker],
+    ) -> Result<()> {
+        let event_ticker = event_ticker.to_string();
+        let fair_values = ServiceSubscriber::<FairValues>::new(ServiceName::FairValue)
+            .await?
+            .listen();
+        let mut fair_values = pin!(fair_values);
+        let mut requote = tokio::time::interval(REQUOTE_EVERY);
+        let mut latest: Option<Event<FairValues>> = None;
+        let mut pulled = false;
+        let mut fills_seen = 0;
+
+        loop {
+            tokio::select! {
+                event = fair_values.next() => match event {
+                    Some(Ok(event)) if event.message.event_ticker == event_ticker => {
+                        latest = Some(event);
+                    }
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => eprintln!("Stream error: {}", e),
+                    None => break,
+                },
+                _ = requote.tick() => {}
+            }
+
+            if let Some((gateway, client)) = paper {
+                if let Err(e) = gateway.poll(client, tickers).await {
+                    eprintln!("Failed to update the paper books: {:?}", e);
+                }
+                let fills = gateway.fills();
+                if fills.len() > fills_seen {
+                    for fill in &fills[fills_seen..] {
+                        println!(
+                            "Paper fill: {} {} {} of {} at {}",
+                            fill.action, fill.count, fill.side, fill.ticker, fill.price
+                        );
+                    }
+                    fills_seen = fills.len();
+                    println!("Paper portfolio value: {}", gateway.portfolio_value());
+                }
+            }
+            engine.refresh().await?;
+            if let Some(risk) = engine.risk_mut() {
+                if let Some((gateway, _)) = paper {
+                    let pnl = gateway.portfolio_value() - Money::from_cents(PAPER_BALANCE_CENTS);
+                    risk.set_daily_pnl(Utc::now().date_naive(), pnl);
+                }
+                if let Some(ledger) = &mut ledger
+                    && let Err(e) = ledger.update_risk(risk).await
+                {
+                    eprintln!("Failed to update the daily PnL: {:?}", e);
+                }
+            }
+
+            match self.pull_reason(latest.as_ref(), Utc::now()) {
+                Some(reason) => {
+                    if !pulled {
+                        println!("Pulling quotes: {}", reason);
+                        engine.cancel_all().await;
+                        pulled = true;
+                    }
+                }
+                None => {
+                    pulled = false;
+                    if let Some(latest) = &latest {
+                        self.quote_all(engine, &latest.message).await?;
+                    }
+                }
+            }
+        }
+
+        engine.cancel_all().await;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Strategy<FairValues> for MarketMaker {
+    async fn run(&mut self, date: &NaiveDate) -> Result<()> {
+        let event_ticker = self.station.high_temperature_event(date);
+        let client = self.credentials.client()?;
+        let markets = MarketsApiClient::from(client.clone());
+        let event = markets.get_event(&event_ticker).await?;
+        let tickers: Vec<MarketTicker> = event
+            .markets()
+            .iter()
+            .map(|market| market.ticker().clone())
+            .collect();
+
+        let risk = RiskManager::for_markets(RiskLimits::default(), event.markets())?;
+
+        if self.paper {
+            let gateway = Arc::new(PaperGateway::new(Money::from_cents(PAPER_BALANCE_CENTS)));
+            let mut engine = OrderEngine::new(gateway.clone(), "mm-paper").with_risk(risk);
+            self.make_markets(
+                &mut engine,
+                Some((&gateway, &markets)),
+                None,
+                &event_ticker,
+                &tickers,
+            )
+            .await
+        } else {
+            let mut engine =
+                OrderEngine::new(PortfolioApiClient::from(client), "mm").with_risk(risk);
+            engine.reconcile().await?;
+            let mut ledger = LedgerStore::open_read_only(Path::new(LEDGER_DIR)).await?;
+            self.make_markets(
+                &mut engine,
+                None,
+                Some(&mut ledger),
+                &event_ticker,
+                &tickers,
+            )
+            .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(fair_yes: f64, yes_bid: f64, yes_ask: f64) -> MarketFairValue {
+        MarketFairValue {
+            ticker: "KXHIGHNY-25OCT17-B61.5".to_string(),
+            fair_yes,
+            yes_bid: Some(yes_bid),
+            yes_ask: Some(yes_ask),
+        }
+    }
+
+    fn cents(quote: Quote) -> (Option<u32>, Option<u32>) {
+        (
+            quote.bid.map(|price| price.cents()),
+            quote.ask.map(|price| price.cents()),
+        )
+    }
+
+    #[test]
+    fn test_quote() {
+        let params = QuoteParams::default();
+        let wide_market = market(0.5, 0.3, 0.7);
+
+        assert_eq!(
+            cents(params.quote(&wide_market, 1.0, 0)),
+            (Some(47), Some(53))
+        );
+        // A less certain forecast widens the spread
+        assert_eq!(
+            cents(params.quote(&wide_market, 3.0, 0)),
+            (Some(45), Some(55))
+        );
+        // Holding YES lowers both quotes to sell it
+        assert_eq!(
+            cents(params.quote(&wide_market, 1.0, 10)),
+            (Some(45), Some(51))
+        );
+        // The bid stops adding to a full position
+        assert_eq!(cents(params.quote(&wide_market, 1.0, 25)), (None, Some(48)));
+        assert_eq!(
+            cents(params.quote(&wide_market, 1.0, -25)),
+            (Some(52), None)
+        );
+
+        // Never crossing the book
+        assert_eq!(
+            cents(params.quote(&market(0.6, 0.3, 0.5), 1.0, 0)),
+            (Some(49), Some(63))
+        );
+        assert_eq!(
+            cents(params.quote(&market(0.4, 0.5, 0.7), 1.0, 0)),
+            (Some(37), Some(51))
+        );
+    }
+}
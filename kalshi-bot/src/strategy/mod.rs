@@ -1,17 +1,28 @@
+mod arbitrage_scanner;
 mod dump_if_temp_higher;
 mod forecast_notifier;
+mod forecast_pricer;
+mod market_maker;
 pub mod name;
 pub mod strategy;
 mod utils;
 mod weather_better;
 
 use crate::strategy::{
-    dump_if_temp_higher::DumpIfTempHigher, forecast_notifier::ForecastNotifier, name::StrategyName,
-    strategy::Strategy, weather_better::WeatherBetter,
+    arbitrage_scanner::ArbitrageScanner,
+    dump_if_temp_higher::DumpIfTempHigher,
+    forecast_notifier::ForecastNotifier,
+    forecast_pricer::ForecastPricer,
+    market_maker::{MarketMaker, QuoteParams},
+    name::StrategyName,
+    strategy::Strategy,
+    weather_better::WeatherBetter,
 };
 use anyhow::Result;
 use chrono::NaiveDate;
 use clap::Args;
+use kalshi_api::credentials::CredentialArgs;
+use kalshi_api_spec::money::Price;
 use weather::{forecast::model::Model, station::Station};
 
 #[derive(Debug, Clone, Args)]
@@ -20,6 +31,22 @@ pub struct StrategyCommand {
 
     #[arg(short, long)]
     date: NaiveDate,
+
+    /// Lowest price, in dollars, positions that can't win are sold at
+    #[arg(long, default_value = "0.01")]
+    min_price: Price,
+
+    /// Let ArbitrageScanner buy the baskets it finds and DumpIfTempHigher
+    /// sell the positions that can't win, not only report them
+    #[arg(long)]
+    trade: bool,
+
+    /// Let MarketMaker trade against simulated fills instead of the exchange
+    #[arg(long)]
+    paper: bool,
+
+    #[command(flatten)]
+    credentials: CredentialArgs,
 }
 
 pub async fn run_strategy(command: &StrategyCommand) -> Result<()> {
@@ -29,9 +56,35 @@ pub async fn run_strategy(command: &StrategyCommand) -> Result<()> {
             strategy.run(&command.date).await.unwrap()
         }
         StrategyName::DumpIfTempHigher => {
-            let mut strategy = DumpIfTempHigher::new(Station::KNYC).await;
+            let mut strategy = DumpIfTempHigher::new(
+                Station::KNYC,
+                command.credentials.clone(),
+                command.min_price,
+                command.trade,
+            )
+            .await;
             strategy.run(&command.date).await.unwrap()
         }
+        StrategyName::ForecastPricer => {
+            let mut strategy =
+                ForecastPricer::new(Station::KNYC, command.credentials.clone(), Model::HRRR);
+            strategy.run(&command.date).await?
+        }
+        StrategyName::ArbitrageScanner => {
+            let mut strategy =
+                ArbitrageScanner::new(Station::KNYC, command.credentials.clone(), command.trade)
+                    .await;
+            strategy.run(&command.date).await?
+        }
+        StrategyName::MarketMaker => {
+            let mut strategy = MarketMaker::new(
+                Station::KNYC,
+                command.credentials.clone(),
+                QuoteParams::default(),
+                command.paper,
+            );
+            strategy.run(&command.date).await?
+        }
         StrategyName::WeatherBetter => {
             let mut strategy = WeatherBetter::new(Station::KNYC, Model::HRRR).await;
             strategy.run(&command.date).await.unwrap()
@@ -6,6 +6,9 @@ use strum_macros::EnumIter;
 #[strum(serialize_all = "kebab-case")]
 pub enum StrategyName {
     ForecastNotifier,
+    ForecastPricer,
     DumpIfTempHigher,
     WeatherBetter,
+    ArbitrageScanner,
+    MarketMaker,
 }
@@ -1,6 +1,87 @@
 use chrono::DateTime;
 use chrono_tz::Tz;
+use protocol::datetime::DateTimeZoned;
+use std::iter::once;
+use weather::{
+    observations::{
+        nws_daily_report::NWSDailyReport, nws_hourly_table::NWSHourlyTableTemperatures,
+        nws_hourly_timeseries::NWSHourlyTimeseriesTemperatures,
+    },
+    temperature::Temperature,
+};
 
 pub fn check_dates_match(d1: &DateTime<Tz>, d2: &DateTime<Tz>) -> bool {
     d1.with_timezone(&d2.timezone()).date_naive() == d2.date_naive()
 }
+
+/// Observations the highest temperature of a day can be read from
+pub trait ObservedMax {
+    /// Highest temperature observed on the day of `date`, if any
+    fn observed_max(&self, date: &DateTime<Tz>) -> Option<Temperature>;
+}
+
+/// Highest of the hourly temperatures and six hour maxima of `date`
+fn hourly_max(
+    observations: impl Iterator<Item = (DateTimeZoned, Temperature, Option<Temperature>)>,
+    date: &DateTime<Tz>,
+) -> Option<Temperature> {
+    observations
+        .filter(|(datetime, ..)| check_dates_match(date, &(*datetime).into()))
+        .flat_map(|(_, temperature, six_hr_max)| once(temperature).chain(six_hr_max))
+        .max()
+}
+
+impl ObservedMax for NWSHourlyTimeseriesTemperatures {
+    fn observed_max(&self, date: &DateTime<Tz>) -> Option<Temperature> {
+        let observations = self
+            .0
+            .iter()
+            .map(|obs| (obs.datetime, obs.temperature, obs.six_hr_max_temperature));
+        hourly_max(observations, date)
+    }
+}
+
+impl ObservedMax for NWSHourlyTableTemperatures {
+    fn observed_max(&self, date: &DateTime<Tz>) -> Option<Temperature> {
+        let observations = self
+            .0
+            .iter()
+            .map(|obs| (obs.datetime, obs.temperature, obs.six_hr_max_temperature));
+        hourly_max(observations, date)
+    }
+}
+
+impl ObservedMax for NWSDailyReport {
+    fn observed_max(&self, date: &DateTime<Tz>) -> Option<Temperature> {
+        check_dates_match(date, &self.datetime.into()).then_some(self.max_temperature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use weather::{observations::nws_hourly_table::NWSHourlyTableTemperature, station::Station};
+
+    #[test]
+    fn test_hourly_max_of_the_day() {
+        let tz = Station::KNYC.timezone();
+        let at = |day, hour| tz.with_ymd_and_hms(2025, 10, day, hour, 51, 0).unwrap();
+        let obs = |day, hour, temperature, six_hr_max: Option<f64>| NWSHourlyTableTemperature {
+            datetime: at(day, hour).into(),
+            station: Station::KNYC,
+            temperature: Temperature::Fahrenheit(temperature),
+            six_hr_max_temperature: six_hr_max.map(Temperature::Fahrenheit),
+        };
+        let table = NWSHourlyTableTemperatures(vec![
+            obs(16, 23, 70.0, None),
+            obs(17, 10, 61.0, None),
+            obs(17, 13, 63.0, Some(64.0)),
+            obs(17, 14, 62.0, None),
+        ]);
+
+        let max = table.observed_max(&at(17, 0)).unwrap();
+        assert_eq!(max.as_fahrenheit(), 64.0);
+        assert!(table.observed_max(&at(18, 0)).is_none());
+    }
+}
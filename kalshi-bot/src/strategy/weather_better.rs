@@ -1,5 +1,5 @@
 use crate::strategy::strategy::Strategy;
-use crate::strategy::utils::check_dates_match;
+use crate::strategy::utils::{ObservedMax, check_dates_match};
 use anyhow::Context;
 use anyhow::Result;
 use async_trait::async_trait;
@@ -165,53 +165,18 @@ async fn handle_event(
             }
         }
         WeatherEvents::HourlyWeatherTimeseries(data) => {
-            let data: Vec<_> = data
-                .0
-                .into_iter()
-                .filter(|obs| {
-                    let dt: DateTime<Tz> = obs.datetime.into();
-                    check_dates_match(date, &dt)
-                })
-                .collect();
-
-            if let Some(max_temp) = data.iter().max_by_key(|t| &t.temperature) {
-                maybe_update(max_temp.temperature).await?;
-            }
-
-            if let Some(max_six_h_temp) = data
-                .iter()
-                .filter_map(|t| t.six_hr_max_temperature.as_ref())
-                .max()
-            {
-                maybe_update(*max_six_h_temp).await?;
+            if let Some(max) = data.observed_max(date) {
+                maybe_update(max).await?;
             }
         }
         WeatherEvents::HourlyWeatherTable(data) => {
-            let data: Vec<_> = data
-                .0
-                .into_iter()
-                .filter(|obs| {
-                    let dt: DateTime<Tz> = obs.datetime.into();
-                    check_dates_match(date, &dt)
-                })
-                .collect();
-
-            if let Some(max_temp) = data.iter().max_by_key(|t| &t.temperature) {
-                maybe_update(max_temp.temperature).await?;
-            }
-
-            if let Some(max_six_h_temp) = data
-                .iter()
-                .filter_map(|t| t.six_hr_max_temperature.as_ref())
-                .max()
-            {
-                maybe_update(*max_six_h_temp).await?;
+            if let Some(max) = data.observed_max(date) {
+                maybe_update(max).await?;
             }
         }
         WeatherEvents::DailyWeatherReport(data) => {
-            let dt: DateTime<Tz> = data.datetime.into();
-            if check_dates_match(date, &dt) {
-                maybe_update(data.max_temperature).await?;
+            if let Some(max) = data.observed_max(date) {
+                maybe_update(max).await?;
             }
         }
     }
@@ -4,6 +4,7 @@ use chrono::{Local, NaiveDate};
 use clap::Args;
 use colored::{Color, Colorize};
 use futures::future::try_join_all;
+use kalshi_api::credentials::CredentialArgs;
 use std::process::Stdio;
 use std::time::Duration;
 use strum::IntoEnumIterator;
@@ -81,6 +82,10 @@ fn run_in_subprocess(service: CommandSpec) -> JoinHandle<Result<()>> {
 pub struct SystemCommand {
     #[arg(short, long)]
     date: Option<NaiveDate>,
+
+    /// Passed on to the ledger and the strategies
+    #[command(flatten)]
+    credentials: CredentialArgs,
 }
 
 pub async fn start_system(command: &SystemCommand) -> Result<()> {
@@ -106,6 +111,22 @@ pub async fn start_system(command: &SystemCommand) -> Result<()> {
             color: Color::Cyan,
             name: "telegram".into(),
         },
+        CommandSpec {
+            cmd: exe.clone(),
+            args: [
+                vec![
+                    "ledger".into(),
+                    "sync".into(),
+                    "--every".into(),
+                    "60".into(),
+                ],
+                command.credentials.to_args(),
+            ]
+            .concat(),
+            delay_secs: None,
+            color: Color::Yellow,
+            name: "ledger".into(),
+        },
     ];
 
     for data_source in DataSourceName::iter() {
@@ -120,14 +141,20 @@ pub async fn start_system(command: &SystemCommand) -> Result<()> {
 
     let date = command.date.unwrap_or(Local::now().date_naive());
     for strategy in StrategyName::iter() {
+        let mut args = vec![
+            "strategy".into(),
+            strategy.to_string(),
+            "--date".into(),
+            date.to_string(),
+        ];
+        // Quotes only rest on the exchange when started on purpose
+        if matches!(strategy, StrategyName::MarketMaker) {
+            args.push("--paper".into());
+        }
+        args.extend(command.credentials.to_args());
         services.push(CommandSpec {
             cmd: exe.clone(),
-            args: vec![
-                "strategy".into(),
-                strategy.to_string(),
-                "--date".into(),
-                date.to_string(),
-            ],
+            args,
             delay_secs: None,
             color: Color::Magenta,
             name: strategy.to_string(),
@@ -0,0 +1,90 @@
+use anyhow::{Context, Result, anyhow};
+use axum::{
+    extract::{OriginalUri, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use base64::{Engine as _, engine::general_purpose};
+use chrono::Utc;
+use rsa::{
+    Pss, RsaPrivateKey, RsaPublicKey,
+    pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey},
+    pkcs8::DecodePublicKey,
+};
+use sha2::{Digest, Sha256};
+use std::{path::Path, sync::Arc};
+
+use crate::{error::MockError, server::MockState};
+
+/// Requests signed further away than this from our clock are refused
+const MAX_CLOCK_DRIFT_MS: i64 = 30_000;
+
+/// Read a public key, or derive it from a private key since that is what
+/// Kalshi hands out
+pub fn public_key_from_pem(content: &str) -> Result<RsaPublicKey> {
+    RsaPublicKey::from_public_key_pem(content)
+        .or_else(|_| RsaPublicKey::from_pkcs1_pem(content))
+        .or_else(|_| RsaPrivateKey::from_pkcs1_pem(content).map(|key| key.to_public_key()))
+        .map_err(|_| anyhow!("Expected an RSA public or private key in PEM format"))
+}
+
+pub fn public_key_from_file(path: &Path) -> Result<RsaPublicKey> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Reading key {}", path.display()))?;
+    public_key_from_pem(&content)
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Result<&'a str, MockError> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| MockError::unauthorized(format!("Missing {name} header")))
+}
+
+/// Check the request signature the same way the exchange does: over the
+/// timestamp, method and path without the query string
+pub fn verify(
+    public_key: &RsaPublicKey,
+    headers: &HeaderMap,
+    method: &str,
+    path: &str,
+) -> Result<(), MockError> {
+    header(headers, "KALSHI-ACCESS-KEY")?;
+    let timestamp = header(headers, "KALSHI-ACCESS-TIMESTAMP")?;
+    let signature = header(headers, "KALSHI-ACCESS-SIGNATURE")?;
+
+    let millis: i64 = timestamp
+        .parse()
+        .map_err(|_| MockError::unauthorized("Invalid timestamp"))?;
+    if (Utc::now().timestamp_millis() - millis).abs() > MAX_CLOCK_DRIFT_MS {
+        return Err(MockError::unauthorized("Request timestamp is too far off"));
+    }
+
+    let signature = general_purpose::STANDARD
+        .decode(signature)
+        .map_err(|_| MockError::unauthorized("Signature is not base64"))?;
+    let digest = Sha256::digest(format!("{timestamp}{method}{path}").as_bytes());
+    public_key
+        .verify(Pss::new::<Sha256>(), &digest, &signature)
+        .map_err(|_| MockError::unauthorized("Invalid signature"))
+}
+
+pub async fn authenticate(
+    State(state): State<Arc<MockState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, MockError> {
+    // Nested routers only see the path below their prefix
+    let path = match request.extensions().get::<OriginalUri>() {
+        Some(uri) => uri.path().to_string(),
+        None => request.uri().path().to_string(),
+    };
+    verify(
+        &state.public_key,
+        request.headers(),
+        request.method().as_str(),
+        &path,
+    )?;
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,557 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use kalshi_api_spec::{
+    fee::{Liquidity, fee, total_cost},
+    money::{Money, Price},
+    order::{
+        Action, AmendOrderRequest, ClientOrderId, CreateOrderRequest, DecreaseOrderRequest,
+        OrderStatus, OrderType, Side,
+    },
+    ticker::MarketTicker,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use crate::error::MockError;
+
+/// Top of the book of a market at one step of the price path
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Quote {
+    pub yes_bid: Price,
+    pub yes_ask: Price,
+}
+
+impl Quote {
+    pub fn bid(&self, side: Side) -> Price {
+        match side {
+            Side::Yes => self.yes_bid,
+            Side::No => self.yes_ask.complement(),
+        }
+    }
+
+    pub fn ask(&self, side: Side) -> Price {
+        match side {
+            Side::Yes => self.yes_ask,
+            Side::No => self.yes_bid.complement(),
+        }
+    }
+}
+
+/// Scripted quotes per market. Markets keep their last quote once their path
+/// runs out.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PricePath(BTreeMap<MarketTicker, Vec<Quote>>);
+
+impl PricePath {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    fn quote(&self, ticker: &MarketTicker, step: usize) -> Option<Quote> {
+        let quotes = self.0.get(ticker)?;
+        quotes.get(step).or(quotes.last()).copied()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MockOrder {
+    order_id: String,
+    client_order_id: ClientOrderId,
+    ticker: MarketTicker,
+    side: Side,
+    action: Action,
+    order_type: OrderType,
+    status: OrderStatus,
+    /// Limit price of the contract on `side`
+    price: Price,
+    initial_count: u32,
+    fill_count: u32,
+    created_time: DateTime<Utc>,
+    last_update_time: DateTime<Utc>,
+}
+
+impl MockOrder {
+    fn remaining_count(&self) -> u32 {
+        self.initial_count.saturating_sub(self.fill_count)
+    }
+
+    fn crosses(&self, quote: &Quote) -> bool {
+        match self.action {
+            Action::Buy => quote.ask(self.side) <= self.price,
+            Action::Sell => quote.bid(self.side) >= self.price,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "order_id": self.order_id,
+            "client_order_id": self.client_order_id,
+            "ticker": self.ticker,
+            "side": self.side,
+            "action": self.action,
+            "type": self.order_type,
+            "status": self.status,
+            "yes_price_dollars": side_price(self.side, Side::Yes, self.price),
+            "no_price_dollars": side_price(self.side, Side::No, self.price),
+            "fill_count": self.fill_count,
+            "remaining_count": self.remaining_count(),
+            "initial_count": self.initial_count,
+            "created_time": self.created_time,
+            "last_update_time": self.last_update_time,
+            "expiration_time": null,
+        })
+    }
+}
+
+/// Price of `wanted` given the price of a contract on `side`
+fn side_price(side: Side, wanted: Side, price: Price) -> Price {
+    if side == wanted {
+        price
+    } else {
+        price.complement()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MockFill {
+    fill_id: String,
+    order_id: String,
+    client_order_id: ClientOrderId,
+    ticker: MarketTicker,
+    side: Side,
+    action: Action,
+    count: u32,
+    price: Price,
+    is_taker: bool,
+    created_time: DateTime<Utc>,
+}
+
+impl MockFill {
+    fn to_json(&self) -> Value {
+        json!({
+            "fill_id": self.fill_id,
+            "trade_id": self.fill_id,
+            "order_id": self.order_id,
+            "client_order_id": self.client_order_id,
+            "ticker": self.ticker,
+            "side": self.side,
+            "action": self.action,
+            "count": self.count,
+            "yes_price_dollars": side_price(self.side, Side::Yes, self.price),
+            "no_price_dollars": side_price(self.side, Side::No, self.price),
+            "is_taker": self.is_taker,
+            "created_time": self.created_time,
+        })
+    }
+
+    /// The fill as the public trade history shows it, from the taker's side
+    fn to_trade_json(&self) -> Value {
+        let bought = match self.action {
+            Action::Buy => self.side,
+            Action::Sell => self.side.opposite(),
+        };
+        let taker_side = if self.is_taker {
+            bought
+        } else {
+            bought.opposite()
+        };
+        json!({
+            "trade_id": self.fill_id,
+            "ticker": self.ticker,
+            "count": self.count,
+            "yes_price_dollars": side_price(self.side, Side::Yes, self.price),
+            "no_price_dollars": side_price(self.side, Side::No, self.price),
+            "taker_side": taker_side,
+            "created_time": self.created_time,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct MockPosition {
+    /// Positive for YES contracts, negative for NO contracts
+    position: i64,
+    total_traded: Money,
+    /// Net amount paid for the contracts currently held
+    exposure: Money,
+    fees_paid: Money,
+    last_updated: Option<DateTime<Utc>>,
+}
+
+/// Fills orders in full as soon as the scripted quote crosses their limit
+/// price. There is no depth: the quote is assumed to be deep enough for any
+/// order.
+pub struct MatchingEngine {
+    path: PricePath,
+    step: usize,
+    balance_cents: i64,
+    orders: Vec<MockOrder>,
+    fills: Vec<MockFill>,
+    positions: BTreeMap<MarketTicker, MockPosition>,
+    next_id: u64,
+}
+
+impl MatchingEngine {
+    pub fn new(path: PricePath, balance_cents: i64) -> Self {
+        Self {
+            path,
+            step: 0,
+            balance_cents,
+            orders: vec![],
+            fills: vec![],
+            positions: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    pub fn quote(&self, ticker: &MarketTicker) -> Option<Quote> {
+        self.path.quote(ticker, self.step)
+    }
+
+    /// Move the price path forward and fill the resting orders it crosses
+    pub fn advance(&mut self) -> usize {
+        self.step += 1;
+        let resting: Vec<usize> = self
+            .orders
+            .iter()
+            .enumerate()
+            .filter(|(_, order)| order.status == OrderStatus::Resting)
+            .map(|(i, _)| i)
+            .collect();
+        for i in resting {
+            self.try_match(i, false);
+        }
+        self.step
+    }
+
+    fn next_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{prefix}-{:08}", self.next_id)
+    }
+
+    fn find(&self, order_id: &str) -> Result<usize, MockError> {
+        self.orders
+            .iter()
+            .position(|order| order.order_id == order_id)
+            .ok_or_else(|| MockError::not_found("order_not_found", "Order not found"))
+    }
+
+    /// Contracts of `side` held in the market
+    fn held(&self, ticker: &MarketTicker, side: Side) -> i64 {
+        let position = self.positions.get(ticker).map_or(0, |p| p.position);
+        match side {
+            Side::Yes => position,
+            Side::No => -position,
+        }
+    }
+
+    fn try_match(&mut self, i: usize, is_taker: bool) {
+        let order = self.orders[i].clone();
+        let Some(quote) = self.quote(&order.ticker) else {
+            return;
+        };
+        if order.remaining_count() == 0 || !order.crosses(&quote) {
+            return;
+        }
+        // Sells only close contracts held, which buying the other side since
+        // the order was placed may have closed already
+        if order.action == Action::Sell
+            && self.held(&order.ticker, order.side) < order.remaining_count() as i64
+        {
+            return;
+        }
+
+        // Takers get the quote, resting orders their own price
+        let price = match (is_taker, order.action) {
+            (true, Action::Buy) => quote.ask(order.side),
+            (true, Action::Sell) => quote.bid(order.side),
+            (false, _) => order.price,
+        };
+        let count = order.remaining_count();
+        let amount = price.cost(count);
+        let fee = fee(Liquidity::from_is_taker(is_taker), count, price);
+        let signed = match (order.side, order.action) {
+            (Side::Yes, Action::Buy) | (Side::No, Action::Sell) => count as i64,
+            (Side::No, Action::Buy) | (Side::Yes, Action::Sell) => -(count as i64),
+        };
+        let cash = match order.action {
+            Action::Buy => -amount,
+            Action::Sell => amount,
+        };
+
+        let now = Utc::now();
+        let fill = MockFill {
+            fill_id: self.next_id("fill"),
+            order_id: order.order_id.clone(),
+            client_order_id: order.client_order_id.clone(),
+            ticker: order.ticker.clone(),
+            side: order.side,
+            action: order.action,
+            count,
+            price,
+            is_taker,
+            created_time: now,
+        };
+
+        let position = self.positions.entry(fill.ticker.clone()).or_default();
+        position.position += signed;
+        position.total_traded = position.total_traded + amount;
+        position.exposure = position.exposure - cash;
+        position.fees_paid = position.fees_paid + fee;
+        position.last_updated = Some(now);
+        self.balance_cents += (cash - fee).cents();
+
+        let order = &mut self.orders[i];
+        order.fill_count += count;
+        order.status = OrderStatus::Executed;
+        order.last_update_time = now;
+        self.fills.push(fill);
+    }
+
+    pub fn create_order(&mut self, request: &CreateOrderRequest) -> Result<Value, MockError> {
+        if self
+            .orders
+            .iter()
+            .any(|order| order.client_order_id == *request.client_order_id())
+        {
+            return Err(MockError::conflict(
+                "order_already_exists",
+                "An order with this client_order_id already exists",
+            ));
+        }
+        let quote = self.quote(request.ticker());
+        let price = match (request.order_type(), request.price()) {
+            (OrderType::Limit, Some(price)) => *price,
+            (OrderType::Limit, None) => {
+                return Err(MockError::bad_request("invalid_order", "Missing price"));
+            }
+            (OrderType::Market, _) => match (quote, request.action()) {
+                (Some(quote), Action::Buy) => quote.ask(request.side()),
+                (Some(quote), Action::Sell) => quote.bid(request.side()),
+                (None, _) => {
+                    return Err(MockError::bad_request(
+                        "market_closed",
+                        "Market has no quote",
+                    ));
+                }
+            },
+        };
+        if request.action() == Action::Buy {
+            let cost = total_cost(Liquidity::Taker, request.count(), price);
+            if cost.cents() > self.balance_cents {
+                return Err(MockError::bad_request(
+                    "insufficient_balance",
+                    "Insufficient balance",
+                ));
+            }
+        } else {
+            let selling: i64 = self
+                .orders
+                .iter()
+                .filter(|order| order.status == OrderStatus::Resting)
+                .filter(|order| order.action == Action::Sell)
+                .filter(|order| order.ticker == *request.ticker() && order.side == request.side())
+                .map(|order| order.remaining_count() as i64)
+                .sum();
+            if self.held(request.ticker(), request.side()) - selling < request.count() as i64 {
+                return Err(MockError::bad_request(
+                    "insufficient_position",
+                    "Cannot sell more contracts than held",
+                ));
+            }
+        }
+
+        let now = Utc::now();
+        let order = MockOrder {
+            order_id: self.next_id("order"),
+            client_order_id: request.client_order_id().clone(),
+            ticker: request.ticker().clone(),
+            side: request.side(),
+            action: request.action(),
+            order_type: request.order_type(),
+            status: OrderStatus::Resting,
+            price,
+            initial_count: request.count(),
+            fill_count: 0,
+            created_time: now,
+            last_update_time: now,
+        };
+        self.orders.push(order);
+        let i = self.orders.len() - 1;
+        self.try_match(i, true);
+        if self.orders[i].order_type == OrderType::Market
+            && self.orders[i].status == OrderStatus::Resting
+        {
+            self.orders[i].status = OrderStatus::Canceled;
+        }
+        Ok(self.orders[i].to_json())
+    }
+
+    pub fn get_order(&self, order_id: &str) -> Result<Value, MockError> {
+        Ok(self.orders[self.find(order_id)?].to_json())
+    }
+
+    pub fn get_orders(
+        &self,
+        ticker: Option<&MarketTicker>,
+        status: Option<OrderStatus>,
+    ) -> Vec<Value> {
+        self.orders
+            .iter()
+            .filter(|order| ticker.is_none_or(|ticker| order.ticker == *ticker))
+            .filter(|order| status.is_none_or(|status| order.status == status))
+            .map(MockOrder::to_json)
+            .collect()
+    }
+
+    fn resting(&mut self, order_id: &str) -> Result<usize, MockError> {
+        let i = self.find(order_id)?;
+        if self.orders[i].status != OrderStatus::Resting {
+            return Err(MockError::bad_request(
+                "order_not_resting",
+                "Order is not resting",
+            ));
+        }
+        Ok(i)
+    }
+
+    /// Returns the order before and after the amendment
+    pub fn amend_order(
+        &mut self,
+        order_id: &str,
+        request: &AmendOrderRequest,
+    ) -> Result<(Value, Value), MockError> {
+        let i = self.resting(order_id)?;
+        let old = self.orders[i].to_json();
+        let order = &mut self.orders[i];
+        if request.count() <= order.fill_count {
+            return Err(MockError::bad_request(
+                "invalid_order",
+                "Count must exceed the filled count",
+            ));
+        }
+        if let Some(price) = request.price() {
+            order.price = *price;
+        }
+        order.initial_count = request.count();
+        order.client_order_id = request.updated_client_order_id().clone();
+        order.last_update_time = Utc::now();
+        self.try_match(i, true);
+        Ok((old, self.orders[i].to_json()))
+    }
+
+    pub fn decrease_order(
+        &mut self,
+        order_id: &str,
+        request: &DecreaseOrderRequest,
+    ) -> Result<Value, MockError> {
+        let i = self.resting(order_id)?;
+        let order = &mut self.orders[i];
+        let remaining = match request {
+            DecreaseOrderRequest::ReduceBy(by) => order.remaining_count().saturating_sub(*by),
+            DecreaseOrderRequest::ReduceTo(to) => order.remaining_count().min(*to),
+        };
+        order.initial_count = order.fill_count + remaining;
+        if remaining == 0 {
+            order.status = OrderStatus::Canceled;
+        }
+        order.last_update_time = Utc::now();
+        Ok(order.to_json())
+    }
+
+    /// Returns the canceled order and how many contracts were taken off the book
+    pub fn cancel_order(&mut self, order_id: &str) -> Result<(Value, u32), MockError> {
+        let i = self.resting(order_id)?;
+        let order = &mut self.orders[i];
+        let reduced_by = order.remaining_count();
+        order.initial_count = order.fill_count;
+        order.status = OrderStatus::Canceled;
+        order.last_update_time = Utc::now();
+        Ok((order.to_json(), reduced_by))
+    }
+
+    pub fn balance(&self) -> Value {
+        let portfolio_value: i64 = self
+            .positions
+            .iter()
+            .filter_map(|(ticker, position)| {
+                let quote = self.quote(ticker)?;
+                let side = if position.position >= 0 {
+                    Side::Yes
+                } else {
+                    Side::No
+                };
+                let count = position.position.unsigned_abs() as u32;
+                Some(quote.bid(side).cost(count).cents())
+            })
+            .sum();
+        json!({
+            "balance": self.balance_cents,
+            "portfolio_value": portfolio_value,
+            "updated_ts": Utc::now().timestamp(),
+        })
+    }
+
+    pub fn positions(&self, ticker: Option<&MarketTicker>) -> Vec<Value> {
+        let resting: HashMap<&MarketTicker, usize> = self
+            .orders
+            .iter()
+            .filter(|order| order.status == OrderStatus::Resting)
+            .fold(HashMap::new(), |mut counts, order| {
+                *counts.entry(&order.ticker).or_default() += 1;
+                counts
+            });
+        self.positions
+            .iter()
+            .filter(|(t, _)| ticker.is_none_or(|ticker| *t == ticker))
+            .map(|(t, position)| {
+                json!({
+                    "ticker": t,
+                    "position": position.position,
+                    "total_traded_dollars": position.total_traded,
+                    "market_exposure_dollars": position.exposure.max(Money::ZERO),
+                    "realized_pnl_dollars": Money::ZERO,
+                    "fees_paid_dollars": position.fees_paid,
+                    "resting_orders_count": resting.get(t).copied().unwrap_or(0),
+                    "last_updated_ts": position.last_updated,
+                })
+            })
+            .collect()
+    }
+
+    pub fn fills(&self, ticker: Option<&MarketTicker>, order_id: Option<&str>) -> Vec<Value> {
+        self.fills
+            .iter()
+            .rev()
+            .filter(|fill| ticker.is_none_or(|ticker| fill.ticker == *ticker))
+            .filter(|fill| order_id.is_none_or(|order_id| fill.order_id == order_id))
+            .map(MockFill::to_json)
+            .collect()
+    }
+
+    /// Our own fills are the only trades the mock exchange ever sees
+    pub fn trades(
+        &self,
+        ticker: Option<&MarketTicker>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> Vec<Value> {
+        self.fills
+            .iter()
+            .rev()
+            .filter(|fill| ticker.is_none_or(|ticker| fill.ticker == *ticker))
+            .filter(|fill| min_ts.is_none_or(|ts| fill.created_time.timestamp() >= ts))
+            .filter(|fill| max_ts.is_none_or(|ts| fill.created_time.timestamp() <= ts))
+            .map(MockFill::to_trade_json)
+            .collect()
+    }
+}
@@ -0,0 +1,5 @@
+pub mod auth;
+pub mod engine;
+pub mod error;
+pub mod recording;
+pub mod server;
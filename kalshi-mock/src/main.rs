@@ -0,0 +1,75 @@
+use anyhow::Result;
+use clap::Parser;
+use kalshi_mock::{
+    auth::public_key_from_file,
+    engine::{MatchingEngine, PricePath},
+    recording::Recording,
+    server::MockExchange,
+};
+use std::{path::PathBuf, time::Duration};
+use tokio::net::TcpListener;
+
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+struct Cli {
+    /// Key used to verify request signatures. A private key works too.
+    #[arg(long)]
+    public_key: PathBuf,
+
+    /// Directory of recorded `GET /events/{ticker}` responses
+    #[arg(long)]
+    recording: PathBuf,
+
+    /// JSON object of market ticker to list of `{yes_bid, yes_ask}` quotes
+    #[arg(long)]
+    price_path: Option<PathBuf>,
+
+    /// Starting balance in cents
+    #[arg(long, default_value_t = 100_000)]
+    balance: i64,
+
+    #[arg(long, default_value_t = 8080)]
+    port: u16,
+
+    /// Advance the price path every this many seconds. Without it the path
+    /// only moves on `POST /mock/advance`.
+    #[arg(long)]
+    step_interval: Option<u64>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let public_key = public_key_from_file(&cli.public_key)?;
+    let recording = Recording::from_dir(&cli.recording)?;
+    let path = match cli.price_path {
+        Some(path) => PricePath::from_file(&path)?,
+        None => PricePath::default(),
+    };
+    let exchange = MockExchange::new(
+        public_key,
+        recording,
+        MatchingEngine::new(path, cli.balance),
+    );
+
+    if let Some(interval) = cli.step_interval {
+        let exchange = exchange.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(interval));
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let step = exchange.advance();
+                eprintln!("Advanced price path to step {}", step);
+            }
+        });
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", cli.port)).await?;
+    eprintln!(
+        "Mock Kalshi exchange listening on {}",
+        listener.local_addr()?
+    );
+    exchange.serve(listener).await
+}
@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use kalshi_api_spec::event::EventResponse;
+use serde_json::Value;
+use std::path::Path;
+
+/// Events and markets recorded from the real exchange. Kept as raw JSON so
+/// they are served back exactly as recorded.
+#[derive(Debug, Default)]
+pub struct Recording {
+    events: Vec<Value>,
+    /// Markets along with the series of their event
+    markets: Vec<(String, Value)>,
+}
+
+fn field<'a>(value: &'a Value, name: &str) -> Option<&'a str> {
+    value.get(name).and_then(Value::as_str)
+}
+
+impl Recording {
+    /// Load every `*.json` file in `dir`, each holding a `GET /events/{ticker}`
+    /// response
+    pub fn from_dir(dir: &Path) -> Result<Self> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .with_context(|| format!("Reading recording {}", dir.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        let mut recording = Self::default();
+        for path in paths {
+            let content = std::fs::read_to_string(&path)?;
+            recording
+                .add_event_response(serde_json::from_str(&content)?)
+                .with_context(|| format!("Loading {}", path.display()))?;
+        }
+        Ok(recording)
+    }
+
+    pub fn add_event_response(&mut self, response: Value) -> Result<()> {
+        // Refuse recordings our own client could not read
+        serde_json::from_value::<EventResponse>(response.clone())?;
+
+        let event = response["event"].clone();
+        let series = field(&event, "series_ticker")
+            .unwrap_or_default()
+            .to_string();
+        if let Some(markets) = response["markets"].as_array() {
+            for market in markets {
+                self.markets.push((series.clone(), market.clone()));
+            }
+        }
+        self.events.push(event);
+        Ok(())
+    }
+
+    pub fn events(&self, series_ticker: Option<&str>) -> Vec<Value> {
+        self.events
+            .iter()
+            .filter(|event| {
+                series_ticker.is_none_or(|series| field(event, "series_ticker") == Some(series))
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn event(&self, event_ticker: &str) -> Option<Value> {
+        self.events
+            .iter()
+            .find(|event| field(event, "event_ticker") == Some(event_ticker))
+            .cloned()
+    }
+
+    pub fn markets(
+        &self,
+        series_ticker: Option<&str>,
+        event_ticker: Option<&str>,
+        status: Option<&str>,
+    ) -> Vec<Value> {
+        self.markets
+            .iter()
+            .filter(|(series, _)| series_ticker.is_none_or(|ticker| series == ticker))
+            .map(|(_, market)| market)
+            .filter(|market| {
+                event_ticker.is_none_or(|ticker| field(market, "event_ticker") == Some(ticker))
+            })
+            .filter(|market| {
+                // Recorded as "active" while the filter is called "open"
+                status.is_none_or(|status| match field(market, "status") {
+                    Some("active") => status == "open",
+                    Some(recorded) => recorded == status,
+                    None => true,
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn market(&self, ticker: &str) -> Option<Value> {
+        self.markets
+            .iter()
+            .map(|(_, market)| market)
+            .find(|market| field(market, "ticker") == Some(ticker))
+            .cloned()
+    }
+}
@@ -0,0 +1,540 @@
+use anyhow::Result;
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    middleware,
+    routing::{get, post},
+};
+use kalshi_api_spec::{
+    order::{AmendOrderRequest, CreateOrderRequest, DecreaseOrderRequest, OrderStatus, Side},
+    ticker::MarketTicker,
+};
+use rsa::RsaPublicKey;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+use crate::{auth::authenticate, engine::MatchingEngine, error::MockError, recording::Recording};
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+/// The engine fills any size at the quote, the book shows this much on each side
+const BOOK_DEPTH: i64 = 1_000;
+
+pub struct MockState {
+    pub(crate) public_key: RsaPublicKey,
+    recording: Recording,
+    engine: Mutex<MatchingEngine>,
+}
+
+/// Query parameters of every list endpoint we serve
+#[derive(Debug, Default, Deserialize)]
+struct ListQuery {
+    series_ticker: Option<String>,
+    event_ticker: Option<String>,
+    ticker: Option<MarketTicker>,
+    order_id: Option<String>,
+    status: Option<String>,
+    min_ts: Option<i64>,
+    max_ts: Option<i64>,
+    cursor: Option<String>,
+    limit: Option<usize>,
+}
+
+impl ListQuery {
+    /// Cursors are plain offsets into the full list
+    fn page(&self, items: Vec<Value>) -> (Vec<Value>, String) {
+        let offset: usize = self
+            .cursor
+            .as_deref()
+            .and_then(|cursor| cursor.parse().ok())
+            .unwrap_or(0);
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+        let cursor = if offset + limit < items.len() {
+            (offset + limit).to_string()
+        } else {
+            String::new()
+        };
+        let page = items.into_iter().skip(offset).take(limit).collect();
+        (page, cursor)
+    }
+}
+
+/// A local stand-in for the Kalshi REST API
+#[derive(Clone)]
+pub struct MockExchange(Arc<MockState>);
+
+impl MockExchange {
+    pub fn new(public_key: RsaPublicKey, recording: Recording, engine: MatchingEngine) -> Self {
+        Self(Arc::new(MockState {
+            public_key,
+            recording,
+            engine: Mutex::new(engine),
+        }))
+    }
+
+    pub fn router(&self) -> Router {
+        let api = Router::new()
+            .route("/events", get(get_events))
+            .route("/events/{event_ticker}", get(get_event))
+            .route("/markets", get(get_markets))
+            .route("/markets/trades", get(get_trades))
+            .route("/markets/{ticker}", get(get_market))
+            .route("/markets/{ticker}/orderbook", get(get_orderbook))
+            .route("/portfolio/balance", get(get_balance))
+            .route("/portfolio/positions", get(get_positions))
+            .route("/portfolio/fills", get(get_fills))
+            .route("/portfolio/settlements", get(get_settlements))
+            .route("/portfolio/orders", get(get_orders).post(create_order))
+            .route(
+                "/portfolio/orders/{order_id}",
+                get(get_order).delete(cancel_order),
+            )
+            .route("/portfolio/orders/{order_id}/amend", post(amend_order))
+            .route(
+                "/portfolio/orders/{order_id}/decrease",
+                post(decrease_order),
+            )
+            .route_layer(middleware::from_fn_with_state(self.0.clone(), authenticate));
+
+        // The exchange status is public, clients read the time off its Date
+        // header before signing anything
+        let api = api.route("/exchange/status", get(get_exchange_status));
+
+        Router::new()
+            .nest("/trade-api/v2", api)
+            .route("/mock/advance", post(advance))
+            .with_state(self.0.clone())
+    }
+
+    /// Move the price path one step forward
+    pub fn advance(&self) -> usize {
+        self.0.engine.lock().unwrap().advance()
+    }
+
+    pub async fn serve(self, listener: TcpListener) -> Result<()> {
+        axum::serve(listener, self.router()).await?;
+        Ok(())
+    }
+}
+
+impl MockState {
+    /// Recorded market with the quote of the current step of the price path
+    fn live_market(&self, mut market: Value) -> Value {
+        let ticker = market["ticker"].as_str().and_then(|t| t.parse().ok());
+        let quote =
+            ticker.and_then(|ticker: MarketTicker| self.engine.lock().unwrap().quote(&ticker));
+        if let Some(quote) = quote {
+            market["yes_bid_dollars"] = json!(quote.bid(Side::Yes));
+            market["yes_ask_dollars"] = json!(quote.ask(Side::Yes));
+            market["no_bid_dollars"] = json!(quote.bid(Side::No));
+            market["no_ask_dollars"] = json!(quote.ask(Side::No));
+        }
+        market
+    }
+}
+
+type MockResult = Result<Json<Value>, MockError>;
+
+async fn advance(State(state): State<Arc<MockState>>) -> Json<Value> {
+    let step = state.engine.lock().unwrap().advance();
+    Json(json!({ "step": step }))
+}
+
+async fn get_exchange_status() -> Json<Value> {
+    Json(json!({ "exchange_active": true, "trading_active": true }))
+}
+
+async fn get_events(
+    State(state): State<Arc<MockState>>,
+    Query(query): Query<ListQuery>,
+) -> MockResult {
+    let events = state.recording.events(query.series_ticker.as_deref());
+    let (events, cursor) = query.page(events);
+    Ok(Json(json!({ "events": events, "cursor": cursor })))
+}
+
+async fn get_event(
+    State(state): State<Arc<MockState>>,
+    Path(event_ticker): Path<String>,
+) -> MockResult {
+    let event = state
+        .recording
+        .event(&event_ticker)
+        .ok_or_else(|| MockError::not_found("event_not_found", "Event not found"))?;
+    let markets: Vec<Value> = state
+        .recording
+        .markets(None, Some(&event_ticker), None)
+        .into_iter()
+        .map(|market| state.live_market(market))
+        .collect();
+    Ok(Json(json!({ "event": event, "markets": markets })))
+}
+
+async fn get_markets(
+    State(state): State<Arc<MockState>>,
+    Query(query): Query<ListQuery>,
+) -> MockResult {
+    let markets = state.recording.markets(
+        query.series_ticker.as_deref(),
+        query.event_ticker.as_deref(),
+        query.status.as_deref(),
+    );
+    let (markets, cursor) = query.page(markets);
+    let markets: Vec<Value> = markets
+        .into_iter()
+        .map(|market| state.live_market(market))
+        .collect();
+    Ok(Json(json!({ "markets": markets, "cursor": cursor })))
+}
+
+async fn get_market(State(state): State<Arc<MockState>>, Path(ticker): Path<String>) -> MockResult {
+    let market = state
+        .recording
+        .market(&ticker)
+        .ok_or_else(|| MockError::not_found("market_not_found", "Market not found"))?;
+    Ok(Json(json!({ "market": state.live_market(market) })))
+}
+
+/// A single level on each side at the quote of the current step
+async fn get_orderbook(
+    State(state): State<Arc<MockState>>,
+    Path(ticker): Path<String>,
+) -> MockResult {
+    state
+        .recording
+        .market(&ticker)
+        .ok_or_else(|| MockError::not_found("market_not_found", "Market not found"))?;
+    let quote = ticker
+        .parse()
+        .ok()
+        .and_then(|ticker: MarketTicker| state.engine.lock().unwrap().quote(&ticker));
+    let levels = |side| {
+        quote
+            .map(|quote| vec![json!([quote.bid(side), BOOK_DEPTH])])
+            .unwrap_or_default()
+    };
+    Ok(Json(json!({
+        "orderbook": {
+            "yes_dollars": levels(Side::Yes),
+            "no_dollars": levels(Side::No),
+        }
+    })))
+}
+
+async fn get_trades(
+    State(state): State<Arc<MockState>>,
+    Query(query): Query<ListQuery>,
+) -> MockResult {
+    let trades =
+        state
+            .engine
+            .lock()
+            .unwrap()
+            .trades(query.ticker.as_ref(), query.min_ts, query.max_ts);
+    let (trades, cursor) = query.page(trades);
+    Ok(Json(json!({ "trades": trades, "cursor": cursor })))
+}
+
+async fn get_balance(State(state): State<Arc<MockState>>) -> MockResult {
+    Ok(Json(state.engine.lock().unwrap().balance()))
+}
+
+async fn get_positions(
+    State(state): State<Arc<MockState>>,
+    Query(query): Query<ListQuery>,
+) -> MockResult {
+    let positions = state
+        .engine
+        .lock()
+        .unwrap()
+        .positions(query.ticker.as_ref());
+    let (positions, cursor) = query.page(positions);
+    Ok(Json(json!({
+        "market_positions": positions,
+        "event_positions": [],
+        "cursor": cursor,
+    })))
+}
+
+async fn get_fills(
+    State(state): State<Arc<MockState>>,
+    Query(query): Query<ListQuery>,
+) -> MockResult {
+    let fills = state
+        .engine
+        .lock()
+        .unwrap()
+        .fills(query.ticker.as_ref(), query.order_id.as_deref());
+    let (fills, cursor) = query.page(fills);
+    Ok(Json(json!({ "fills": fills, "cursor": cursor })))
+}
+
+async fn get_settlements() -> MockResult {
+    Ok(Json(json!({ "settlements": [], "cursor": "" })))
+}
+
+async fn get_orders(
+    State(state): State<Arc<MockState>>,
+    Query(query): Query<ListQuery>,
+) -> MockResult {
+    let status = match query.status.as_deref() {
+        Some(status) => Some(
+            serde_json::from_value::<OrderStatus>(json!(status))
+                .map_err(|_| MockError::bad_request("invalid_parameters", "Unknown status"))?,
+        ),
+        None => None,
+    };
+    let orders = state
+        .engine
+        .lock()
+        .unwrap()
+        .get_orders(query.ticker.as_ref(), status);
+    let (orders, cursor) = query.page(orders);
+    Ok(Json(json!({ "orders": orders, "cursor": cursor })))
+}
+
+async fn create_order(
+    State(state): State<Arc<MockState>>,
+    Json(request): Json<CreateOrderRequest>,
+) -> MockResult {
+    if state
+        .recording
+        .market(&request.ticker().to_string())
+        .is_none()
+    {
+        return Err(MockError::not_found("market_not_found", "Market not found"));
+    }
+    let order = state.engine.lock().unwrap().create_order(&request)?;
+    Ok(Json(json!({ "order": order })))
+}
+
+async fn get_order(
+    State(state): State<Arc<MockState>>,
+    Path(order_id): Path<String>,
+) -> MockResult {
+    let order = state.engine.lock().unwrap().get_order(&order_id)?;
+    Ok(Json(json!({ "order": order })))
+}
+
+async fn amend_order(
+    State(state): State<Arc<MockState>>,
+    Path(order_id): Path<String>,
+    Json(request): Json<AmendOrderRequest>,
+) -> MockResult {
+    let (old_order, order) = state
+        .engine
+        .lock()
+        .unwrap()
+        .amend_order(&order_id, &request)?;
+    Ok(Json(json!({ "old_order": old_order, "order": order })))
+}
+
+async fn decrease_order(
+    State(state): State<Arc<MockState>>,
+    Path(order_id): Path<String>,
+    Json(request): Json<DecreaseOrderRequest>,
+) -> MockResult {
+    let order = state
+        .engine
+        .lock()
+        .unwrap()
+        .decrease_order(&order_id, &request)?;
+    Ok(Json(json!({ "order": order })))
+}
+
+async fn cancel_order(
+    State(state): State<Arc<MockState>>,
+    Path(order_id): Path<String>,
+) -> MockResult {
+    let (order, reduced_by) = state.engine.lock().unwrap().cancel_order(&order_id)?;
+    Ok(Json(json!({ "order": order, "reduced_by": reduced_by })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{auth::public_key_from_pem, engine::PricePath};
+    use kalshi_api::{
+        client::{BaseUrl, KalshiApiClient},
+        error::KalshiApiError,
+        keys::{ApiKey, PrivateKey},
+        markets::MarketsApiClient,
+        portfolio::PortfolioApiClient,
+    };
+    use kalshi_api_spec::{
+        history::GetTradesParams, market::GetMarketsParams, order::Action,
+        portfolio::GetFillsParams, ticker::EventTicker,
+    };
+
+    const KEY: &str = include_str!("../../kalshi-api/fixtures/test_key.pem");
+
+    async fn start(balance_cents: i64) -> (MockExchange, KalshiApiClient) {
+        let mut recording = Recording::default();
+        let event =
+            serde_json::from_str(include_str!("../fixtures/recording/KXHIGHNY-25OCT17.json"));
+        recording.add_event_response(event.unwrap()).unwrap();
+        let path: PricePath =
+            serde_json::from_str(include_str!("../fixtures/price_path.json")).unwrap();
+        let exchange = MockExchange::new(
+            public_key_from_pem(KEY).unwrap(),
+            recording,
+            MatchingEngine::new(path, balance_cents),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(exchange.clone().serve(listener));
+
+        let client = KalshiApiClient::new(
+            ApiKey::new("mock"),
+            PrivateKey::from_pem(KEY).unwrap(),
+            BaseUrl::Custom(format!("http://{address}")),
+        );
+        (exchange, client)
+    }
+
+    #[tokio::test]
+    async fn test_serves_recorded_markets_with_live_quotes() {
+        let (_, client) = start(10_000).await;
+        let markets = MarketsApiClient::from(client);
+
+        let event_ticker: EventTicker = "KXHIGHNY-25OCT17".parse().unwrap();
+        let response = markets.get_event(&event_ticker).await.unwrap();
+        let as_json = serde_json::to_value(&response).unwrap();
+        assert_eq!(as_json["markets"].as_array().unwrap().len(), 3);
+        assert_eq!(as_json["markets"][1]["yes_ask_dollars"], "0.25");
+
+        let params = GetMarketsParams {
+            event_ticker: Some(event_ticker),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let page = markets.get_markets(&params).await.unwrap();
+        assert_eq!(page.markets.len(), 2);
+        assert_eq!(page.cursor.as_deref(), Some("2"));
+    }
+
+    #[tokio::test]
+    async fn test_serves_books_and_trades_from_the_path() {
+        let (exchange, client) = start(10_000).await;
+        let markets = MarketsApiClient::from(client.clone());
+        let portfolio = PortfolioApiClient::from(client);
+
+        let ticker: MarketTicker = "KXHIGHNY-25OCT17-B61.5".parse().unwrap();
+        let book = markets.get_orderbook(&ticker, None).await.unwrap();
+        assert_eq!(
+            book.best_bid(Side::Yes),
+            Some(("0.22".parse().unwrap(), 1_000))
+        );
+        assert_eq!(
+            book.best_bid(Side::No),
+            Some(("0.75".parse().unwrap(), 1_000))
+        );
+
+        exchange.advance();
+        let book = markets.get_orderbook(&ticker, Some(1)).await.unwrap();
+        assert_eq!(book.best_ask(Side::Yes).unwrap().0, "0.20".parse().unwrap());
+
+        let params = GetTradesParams {
+            ticker: Some(ticker.clone()),
+            ..Default::default()
+        };
+        assert!(markets.get_trades(&params).await.unwrap().trades.is_empty());
+        let request = CreateOrderRequest::market(ticker.clone(), Side::Yes, Action::Buy, 5);
+        portfolio.create_order(&request).await.unwrap();
+        let trades = markets.get_trades(&params).await.unwrap().trades;
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].count(), 5);
+        assert_eq!(trades[0].yes_price(), "0.20".parse().unwrap());
+        assert_eq!(trades[0].taker_side(), Side::Yes);
+
+        let missing: MarketTicker = "KXHIGHNY-25OCT17-B99.5".parse().unwrap();
+        assert!(markets.get_orderbook(&missing, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resting_order_fills_when_path_crosses() {
+        let (exchange, client) = start(10_000).await;
+        let portfolio = PortfolioApiClient::from(client);
+
+        let ticker: MarketTicker = "KXHIGHNY-25OCT17-B61.5".parse().unwrap();
+        let request = CreateOrderRequest::limit(
+            ticker.clone(),
+            Side::Yes,
+            Action::Buy,
+            10,
+            "0.20".parse().unwrap(),
+        );
+        let order = portfolio.create_order(&request).await.unwrap();
+        assert_eq!(order.status(), OrderStatus::Resting);
+
+        // The ask drops to 0.20 at the second step
+        exchange.advance();
+        let order = portfolio.get_order(order.order_id()).await.unwrap();
+        assert_eq!(order.status(), OrderStatus::Executed);
+        assert_eq!(order.fill_count(), 10);
+
+        let balance = portfolio.get_balance().await.unwrap();
+        // 10 contracts at 0.20 plus the maker fee, 0.0175 * 10 * 0.2 * 0.8 rounded up
+        assert_eq!(balance.balance_cents(), 10_000 - 200 - 3);
+        let fills = portfolio
+            .get_fills(&GetFillsParams::default())
+            .await
+            .unwrap();
+        assert_eq!(fills.fills.len(), 1);
+        assert!(!fills.fills[0].is_taker());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_orders_beyond_balance() {
+        let (_, client) = start(100).await;
+        let portfolio = PortfolioApiClient::from(client);
+
+        let ticker: MarketTicker = "KXHIGHNY-25OCT17-B61.5".parse().unwrap();
+        let request = CreateOrderRequest::market(ticker, Side::Yes, Action::Buy, 10);
+        let error = portfolio.create_order(&request).await.unwrap_err();
+        let error = error.downcast_ref::<KalshiApiError>().unwrap();
+        assert_eq!(error.code(), Some("insufficient_balance"));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_sells_beyond_position() {
+        let (_, client) = start(10_000).await;
+        let portfolio = PortfolioApiClient::from(client);
+
+        let ticker: MarketTicker = "KXHIGHNY-25OCT17-B61.5".parse().unwrap();
+        let sell =
+            |count| CreateOrderRequest::market(ticker.clone(), Side::Yes, Action::Sell, count);
+        let error = portfolio.create_order(&sell(5)).await.unwrap_err();
+        let error = error.downcast_ref::<KalshiApiError>().unwrap();
+        assert_eq!(error.code(), Some("insufficient_position"));
+
+        let buy = CreateOrderRequest::market(ticker.clone(), Side::Yes, Action::Buy, 5);
+        portfolio.create_order(&buy).await.unwrap();
+        assert!(portfolio.create_order(&sell(6)).await.is_err());
+        let order = portfolio.create_order(&sell(5)).await.unwrap();
+        assert_eq!(order.status(), OrderStatus::Executed);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_bad_signatures() {
+        let (exchange, _) = start(10_000).await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(exchange.serve(listener));
+
+        let response = reqwest::Client::new()
+            .get(format!("http://{address}/trade-api/v2/portfolio/balance"))
+            .header("KALSHI-ACCESS-KEY", "mock")
+            .header(
+                "KALSHI-ACCESS-TIMESTAMP",
+                chrono::Utc::now().timestamp_millis(),
+            )
+            .header("KALSHI-ACCESS-SIGNATURE", "bm90IGEgc2lnbmF0dXJl")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 401);
+    }
+}
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+/// Model prices of the markets of an event, published on
+/// `ServiceName::FairValue`. Prices are in dollars.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FairValues {
+    pub event_ticker: String,
+    /// Forecast highest temperature of the rest of the day in °F, none once
+    /// the day is over
+    pub forecast_max: Option<f64>,
+    pub stdev: Option<f64>,
+    /// Highest temperature observed so far in °F
+    pub observed_max: Option<f64>,
+    pub markets: Vec<MarketFairValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketFairValue {
+    pub ticker: String,
+    /// Probability of settling YES, and so the fair YES price
+    pub fair_yes: f64,
+    pub yes_bid: Option<f64>,
+    pub yes_ask: Option<f64>,
+}
+
+impl MarketFairValue {
+    /// Expected profit per contract of buying YES at the ask, before fees
+    pub fn yes_edge(&self) -> Option<f64> {
+        Some(self.fair_yes - self.yes_ask?)
+    }
+
+    /// Expected profit per contract of buying NO, i.e. selling YES at the bid,
+    /// before fees
+    pub fn no_edge(&self) -> Option<f64> {
+        Some(self.yes_bid? - self.fair_yes)
+    }
+}
@@ -27,8 +27,13 @@ pub enum ServiceName {
     HourlyWeatherTimeseries,
     HourlyWeatherTable,
     DailyWeatherReport,
+    OrderEngine,
+    FairValue,
 }
 
+/// Trading stops while this file exists, see the order engine's kill switch
+pub const KILL_SWITCH_FILE: &str = "/tmp/kill_switch";
+
 impl ServiceName {
     fn unix_path(&self) -> String {
         let name = self.to_string();
@@ -249,6 +254,11 @@ impl<E> MultiServiceSubscriber<E> {
         Ok(())
     }
 
+    /// Next event of any of the subscriptions, none once they all ended
+    pub async fn next(&mut self) -> Option<E> {
+        self.streams.next().await
+    }
+
     pub async fn listen_all<F, Fut>(mut self, mut handler: F) -> Result<()>
     where
         F: FnMut(E) -> Fut,
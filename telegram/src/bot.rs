@@ -1,8 +1,9 @@
+use ::protocol::pricing::FairValues;
 use anyhow::Result;
 use protocol::protocol;
-use std::env;
+use std::{env, sync::Arc, time::Duration};
 use teloxide::{prelude::*, types::ParseMode, utils::command::BotCommands};
-use tokio::{net::UnixStream, try_join};
+use tokio::{net::UnixStream, sync::RwLock, try_join};
 
 use crate::client::TelegramMessage;
 
@@ -16,12 +17,43 @@ pub enum Command {
     Help,
     #[command(description = "display the status of the service.")]
     Status(String),
+    #[command(description = "cancel every order and stop trading.")]
+    Kill(String),
+    #[command(description = "allow trading again once the bot restarts.")]
+    Resume,
+    #[command(description = "display the fair values of the day's markets.")]
+    Prices,
+}
+
+/// Latest fair values published by the pricing strategy
+type LatestFairValues = Arc<RwLock<Option<FairValues>>>;
+
+fn fair_values_text(fair_values: &FairValues) -> String {
+    let price = |price: Option<f64>| price.map_or("-".to_string(), |p| format!("{:.2}", p));
+    let mut lines = vec![fair_values.event_ticker.clone()];
+    if let (Some(max), Some(stdev)) = (fair_values.forecast_max, fair_values.stdev) {
+        lines.push(format!("Forecast max: {:.1}F±{:.1}", max, stdev));
+    }
+    if let Some(observed) = fair_values.observed_max {
+        lines.push(format!("Observed max: {:.1}F", observed));
+    }
+    for market in &fair_values.markets {
+        lines.push(format!(
+            "{} fair {:.2} bid {} ask {}",
+            market.ticker,
+            market.fair_yes,
+            price(market.yes_bid),
+            price(market.yes_ask)
+        ));
+    }
+    lines.join("\n")
 }
 
 #[derive(Clone)]
 pub struct TelegramBot {
     bot: Bot,
     chat_id: ChatId,
+    fair_values: LatestFairValues,
 }
 
 impl Default for TelegramBot {
@@ -34,11 +66,17 @@ impl Default for TelegramBot {
         Self {
             bot,
             chat_id: ChatId(chat_id),
+            fair_values: Arc::default(),
         }
     }
 }
 
-pub async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()> {
+pub async fn answer(
+    bot: Bot,
+    msg: Message,
+    cmd: Command,
+    fair_values: LatestFairValues,
+) -> ResponseResult<()> {
     match cmd {
         Command::Help => {
             bot.send_message(msg.chat.id, Command::descriptions().to_string())
@@ -48,6 +86,27 @@ pub async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()>
             bot.send_message(msg.chat.id, format!("The service is {service}."))
                 .await?
         }
+        Command::Kill(reason) => {
+            let text = match tokio::fs::write(protocol::KILL_SWITCH_FILE, reason).await {
+                Ok(()) => "Kill switch is on, cancelling every order.".to_string(),
+                Err(e) => format!("Failed to turn the kill switch on: {e}"),
+            };
+            bot.send_message(msg.chat.id, text).await?
+        }
+        Command::Resume => {
+            let text = match tokio::fs::remove_file(protocol::KILL_SWITCH_FILE).await {
+                Ok(()) => "Kill switch is off, trading resumes after a restart.".to_string(),
+                Err(e) => format!("Failed to turn the kill switch off: {e}"),
+            };
+            bot.send_message(msg.chat.id, text).await?
+        }
+        Command::Prices => {
+            let text = match fair_values.read().await.as_ref() {
+                Some(fair_values) => fair_values_text(fair_values),
+                None => "No fair values published yet.".to_string(),
+            };
+            bot.send_message(msg.chat.id, text).await?
+        }
     };
 
     Ok(())
@@ -62,6 +121,7 @@ impl TelegramBot {
             .endpoint(answer);
 
         Dispatcher::builder(self.bot.clone(), handler)
+            .dependencies(dptree::deps![self.fair_values.clone()])
             .build()
             .dispatch()
             .await;
@@ -108,10 +168,30 @@ impl TelegramBot {
         }
     }
 
+    async fn read_fair_values(&self) -> Result<()> {
+        let mut stream = protocol::create_unix_stream(protocol::ServiceName::FairValue).await?;
+        loop {
+            let event = protocol::read::<FairValues>(&mut stream).await?;
+            *self.fair_values.write().await = Some(event.message);
+        }
+    }
+
+    /// Keep the latest fair values for `/prices`, whenever the pricing
+    /// strategy runs
+    async fn follow_fair_values(&self) -> Result<()> {
+        loop {
+            if let Err(err) = self.read_fair_values().await {
+                eprintln!("Not following fair values: {}", err);
+            }
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        }
+    }
+
     pub async fn run(&self) -> Result<()> {
         let command_bot = self.start_command_bot();
         let logger = self.start_logger();
-        let _ = try_join!(command_bot, logger);
+        let fair_values = self.follow_fair_values();
+        let _ = try_join!(command_bot, logger, fair_values);
         Ok(())
     }
 }